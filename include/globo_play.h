@@ -0,0 +1,74 @@
+#ifndef GLOBO_PLAY_H
+#define GLOBO_PLAY_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit by hand */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// A client created by `globo_play_client_new`
+typedef struct GloboPlayClient GloboPlayClient;
+
+// Receives the number of bytes written to the output file so far, on the thread that called
+// `globo_play_download`
+typedef void (*GloboPlayProgressCallback)(uint64_t bytes_written, void *user_data);
+
+// Creates a client, or returns NULL on failure
+//
+// # Arguments
+// * `cookie_file` - Netscape-format cookie file with the session cookies, or NULL for no session
+// * `quality` - Quality to pick ("max", "min", a height like "720p" or a bitrate like "2500k"), or NULL for the default
+//
+// # Safety
+// The arguments must be NULL or NUL-terminated strings.
+struct GloboPlayClient *globo_play_client_new(const char *cookie_file, const char *quality);
+
+// Releases a client
+//
+// # Safety
+// `client` must be NULL or a client from `globo_play_client_new` that isn't used afterwards.
+void globo_play_client_free(struct GloboPlayClient *client);
+
+// Creates a playback session for a video and returns it as JSON, or NULL on failure
+//
+// The string must be released with `globo_play_string_free`.
+//
+// # Safety
+// `client` must come from `globo_play_client_new` and `video_id` must be a NUL-terminated string.
+char *globo_play_fetch_session_json(const struct GloboPlayClient *client, const char *video_id);
+
+// Downloads a video's best stream for the client's quality to `output_path`
+//
+// `progress`, if not NULL, is called with `user_data` about twice a second while the download
+// runs, and once more when it has finished. Builds without ffmpeg save MPEG-TS streams next
+// to `output_path` with a `.ts` extension instead.
+//
+// # Returns
+// 0 on success, otherwise the exit code of the command-line tool for the failure (3 for auth,
+// 4 geo-blocked, 5 DRM, 6 not found, 7 network, 8 ffmpeg, 10 subscription required, 11 age
+// restricted, 12 no longer available, 1 anything else)
+//
+// # Safety
+// `client` must come from `globo_play_client_new`, `video_id` and `output_path` must be
+// NUL-terminated strings, and `progress` must be safe to call with `user_data`.
+int globo_play_download(const struct GloboPlayClient *client,
+                        const char *video_id,
+                        const char *output_path,
+                        GloboPlayProgressCallback progress,
+                        void *user_data);
+
+// Releases a string returned by the library
+//
+// # Safety
+// `value` must be NULL or a string returned by this library that isn't used afterwards.
+void globo_play_string_free(char *value);
+
+// Describes the last failure on the calling thread, or returns NULL if nothing failed yet
+//
+// The string is owned by the library and stays valid until the next failure on the same thread.
+const char *globo_play_last_error(void);
+
+#endif  /* GLOBO_PLAY_H */
@@ -1,202 +1,243 @@
 // src/api.rs
-use crate::config::AppConfig;
-use crate::models::{ApiErrorResponse, DatedVideosResponse, VideoSession};
+//! The API calls of `GloboClient`
+//!
+//! Requests are built and responses parsed by `protocol::MetadataClient`; this module adds the
+//! paginated streams, which need the tokio timer between pages.
+use crate::client::GloboClient;
+use crate::models::{
+    Broadcast, Category, ContinueWatchingItem, DatedVideoItem, DatedVideosResponse, EpgSlot, Episode, HighlightRail, MyList, ResultPage, SearchResults,
+    TitleDetails, TitleSummary, Video, VideoSession,
+};
 use crate::constants;
-use anyhow::Result;
-use reqwest::StatusCode;
-use thiserror::Error;
-use uuid;
-use urlencoding;
-
-#[derive(Error, Debug)]
-pub enum ApiError {
-    #[error("Request failed: {0}")]
-    Request(#[from] reqwest::Error),
-    #[error("HTTP error: {status} - {body}")]
-    Http {
-        status: StatusCode,
-        body: String,
-    },
-    #[error("Failed to deserialize JSON response: {0}")]
-    JsonDeserialization(#[source] serde_json::Error),
-    #[error("API returned an error: {0}")]
-    GloboApi(String),
-}
+use crate::dates::Day;
+use crate::error::Result;
+use crate::ids::{TitleId, VideoId};
+use futures_util::stream::{self, Stream, StreamExt, TryStreamExt};
+use std::collections::HashSet;
+use std::time::Duration;
+
+pub use crate::protocol::{GraphQlQuery, VideoSessionRequest};
+
+/// Each call forwards to the `protocol::MetadataClient` method of the same name, sending its
+/// requests through the client's `HttpClient`
+impl GloboClient {
+    /// Creates a playback session for a video, with its stream sources and metadata
+    pub async fn fetch_video_session(&self, video_id: &VideoId) -> Result<VideoSession> {
+        self.api.fetch_video_session(video_id).await
+    }
+
+    /// Creates a playback session with other player fields than the client's
+    pub async fn fetch_video_session_with(&self, video_id: &VideoId, request: &VideoSessionRequest) -> Result<VideoSession> {
+        self.api.fetch_video_session_with(video_id, request).await
+    }
+
+    /// Sends a GraphQL operation to the Jarvis API and returns the whole response body, `errors` included
+    ///
+    /// `PersistedWithFallback` queries are retried with their document when the hash is unknown.
+    pub async fn graphql_raw_request(
+        &self,
+        operation_name: &str,
+        query: GraphQlQuery<'_>,
+        variables: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        self.api.graphql_raw_request(operation_name, query, variables).await
+    }
+
+    /// Sends a GraphQL operation to the Jarvis API and returns its `data` object
+    pub async fn graphql_request(
+        &self,
+        operation_name: &str,
+        query: GraphQlQuery<'_>,
+        variables: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        self.api.graphql_request(operation_name, query, variables).await
+    }
+
+    /// Fetches one page of a title's videos in a date range (YYYY-MM-DD)
+    pub async fn fetch_videos_by_date(
+        &self,
+        title_id: &TitleId,
+        from_date: &str,
+        to_date: &str,
+        page: u32,
+        per_page: u32,
+    ) -> Result<DatedVideosResponse> {
+        self.api.fetch_videos_by_date(title_id, from_date, to_date, page, per_page).await
+    }
 
-pub async fn fetch_video_session(
-    video_id: &str,
-    config: &AppConfig,
-) -> Result<VideoSession, ApiError> {
-    let url = format!("{}{}", constants::PLAYBACK_API_BASE_URL, constants::VIDEO_SESSION_URL_TEMPLATE);
-    
-    if config.debug_mode {
-        println!("Fetching video session for ID: {}", video_id);
-        println!("URL: {}", url);
-    }
-    
-    // Following the pattern from marine-traffic/gp-common-functions
-    let vsid = uuid::Uuid::new_v4().to_string();
-    let request_body = serde_json::json!({
-        "player_type": "desktop",
-        "video_id": video_id,
-        "quality": config.video_quality,
-        "content_protection": "widevine",
-        "vsid": vsid,
-        "tz": "-03:00",
-        "capabilities": {
-            "low_latency": true
-        },
-        "consumption": "streaming",
-        "metadata": {
-            "name": "web",
-            "device": {
-                "type": "desktop",
-                "os": {}
+    /// Fetches every page of a title's videos in a date range, following `next` until it is empty
+    ///
+    /// A range longer than `DATE_WINDOW_MAX_DAYS` is fetched one calendar month at a time and the
+    /// months' items merged, skipping videos already seen. Pages are requested sequentially with a
+    /// short delay between them and at most `PAGE_FOLLOW_MAX_PAGES` pages are followed per month.
+    /// When the cap is reached the returned `next` is left set so callers can tell the result set
+    /// is incomplete.
+    ///
+    /// # Arguments
+    /// * `title_id` - The ID of the title/program
+    /// * `from_date` - Start date (YYYY-MM-DD)
+    /// * `to_date` - End date (YYYY-MM-DD)
+    /// * `start_page` - First page to fetch (1-based) of the first month
+    /// * `per_page` - Results per page
+    ///
+    /// # Returns
+    /// A single response with the items of all fetched pages
+    pub async fn fetch_all_videos_by_date(
+        &self,
+        title_id: &TitleId,
+        from_date: &str,
+        to_date: &str,
+        start_page: u32,
+        per_page: u32,
+    ) -> Result<DatedVideosResponse> {
+        let windows = match (from_date.parse::<Day>(), to_date.parse::<Day>()) {
+            (Ok(from), Ok(to)) => Day::windows(from, to, constants::DATE_WINDOW_MAX_DAYS)
+                .into_iter()
+                .map(|(from, to)| (from.to_string(), to.to_string()))
+                .collect(),
+            _ => vec![(from_date.to_string(), to_date.to_string())],
+        };
+        let mut response = DatedVideosResponse { items: Vec::new(), count: Some(0), next: None };
+        let mut seen = HashSet::new();
+        for (index, (from_date, to_date)) in windows.iter().enumerate() {
+            // `start_page` is where the range starts; later months are read from their first page
+            let page = if index == 0 { start_page } else { 1 };
+            let pages = self
+                .videos_by_date_pages(title_id, from_date, to_date, page, per_page)
+                .take(constants::PAGE_FOLLOW_MAX_PAGES as usize);
+            futures_util::pin_mut!(pages);
+            let (mut next, mut count) = (None, None);
+            while let Some(page) = pages.try_next().await? {
+                response.items.extend(page.items.into_iter().filter(|item| seen.insert(item.id.clone())));
+                next = page.next;
+                count = page.count.or(count);
             }
-        },
-        "version": 1
-    });
-    
-    let response = config.http_client
-        .post(&url)
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(ApiError::Request)?;
-        
-    let status = response.status();
-    if status.is_success() {
-        let text_body = response.text().await.map_err(ApiError::Request)?;
-        if config.debug_mode {
-            println!("Response body: {}", text_body);
+            // The total is only known if every month reported its own
+            response.count = response.count.zip(count).map(|(total, count)| total + count);
+            response.next = response.next.or(next);
         }
-        serde_json::from_str::<VideoSession>(&text_body).map_err(|e| {
-            if config.debug_mode {
-                eprintln!("Failed to parse JSON: {}, body was: {}", e, text_body);
+
+        Ok(response)
+    }
+
+    /// Lazily fetches a title's videos in a date range page by page, following `next` until it is empty
+    ///
+    /// A page is only requested when the previous one has been consumed, with a short delay in
+    /// between. There is no page cap; limit the stream with `take` if needed.
+    ///
+    /// # Arguments
+    /// * `title_id` - The ID of the title/program
+    /// * `from_date` - Start date (YYYY-MM-DD)
+    /// * `to_date` - End date (YYYY-MM-DD)
+    /// * `start_page` - First page to fetch (1-based)
+    /// * `per_page` - Results per page
+    pub fn videos_by_date_pages<'a>(
+        &'a self,
+        title_id: &TitleId,
+        from_date: &str,
+        to_date: &str,
+        start_page: u32,
+        per_page: u32,
+    ) -> impl Stream<Item = Result<DatedVideosResponse>> + 'a {
+        let range = (title_id.clone(), from_date.to_string(), to_date.to_string());
+        stream::try_unfold(Some(start_page), move |page| {
+            let (title_id, from_date, to_date) = range.clone();
+            async move {
+                let Some(page) = page else {
+                    return Ok(None);
+                };
+                if page > start_page {
+                    tokio::time::sleep(Duration::from_millis(constants::PAGE_FOLLOW_DELAY_MS)).await;
+                }
+                let response = self.fetch_videos_by_date(&title_id, &from_date, &to_date, page, per_page).await?;
+                tracing::debug!(page, items = response.items.len(), "fetched videos-by-date page");
+                let next = response.next.is_some().then_some(page + 1);
+                Ok(Some((response, next)))
             }
-            ApiError::JsonDeserialization(e)
         })
-    } else {
-        let text_body = response.text().await.map_err(ApiError::Request)?;
-        if config.debug_mode {
-            eprintln!("Error response body: {}", text_body);
-        }
-        // Try to parse Globo API error structure
-        if let Ok(api_error) = serde_json::from_str::<ApiErrorResponse>(&text_body) {
-            Err(ApiError::GloboApi(api_error.message))
-        } else {
-            Err(ApiError::Http {
-                status,
-                body: text_body,
-            })
-        }
     }
-}
 
-#[allow(clippy::too_many_arguments)]
-pub async fn fetch_videos_by_date(
-    title_id: &str,
-    from_date: &str, // YYYY-MM-DD
-    to_date: &str,   // YYYY-MM-DD
-    page: u32,
-    per_page: u32,
-    config: &AppConfig,
-) -> Result<DatedVideosResponse, ApiError> {
-    // Build GraphQL request based on get-videos-by-date script
-    let operation_name = "getTitleVideosByDateView";
-    let query_hash = "d4d95fd5770f9672dc1247e3343c13cafff725f339c95eb28c6e61dac9501c5d";
-    
-    // Build variables JSON
-    let variables = serde_json::json!({
-        "titleId": title_id,
-        "gte": from_date,
-        "lte": to_date,
-        "page": page,
-        "perPage": per_page
-    });
-    
-    // Build extensions JSON
-    let extensions = serde_json::json!({
-        "persistedQuery": {
-            "version": 1,
-            "sha256Hash": query_hash
-        }
-    });
-    
-    // URL encode parameters for URL
-    let variables_string = variables.to_string();
-    let extensions_string = extensions.to_string();
-    let encoded_variables = urlencoding::encode(&variables_string);
-    let encoded_extensions = urlencoding::encode(&extensions_string);
-    
-    // Construct the URL
-    let url = format!(
-        "{}?operationName={}&variables={}&extensions={}",
-        constants::GRAPHQL_API_BASE_URL,
-        operation_name,
-        encoded_variables,
-        encoded_extensions
-    );
-    
-    if config.debug_mode {
-        println!("GraphQL request URL: {}", url);
-    }
-    
-    // Make the request with appropriate headers
-    let response = config.http_client
-        .get(&url)
-        .header("x-tenant-id", "globo-play")
-        .header("x-platform-id", "web")
-        .header("x-device-id", "desktop")
-        .send()
-        .await
-        .map_err(ApiError::Request)?;
-    
-    let status = response.status();
-    if !status.is_success() {
-        let text_body = response.text().await.map_err(ApiError::Request)?;
-        return Err(ApiError::Http {
-            status,
-            body: text_body,
-        });
-    }
-    
-    // Parse the GraphQL response format, which is different from the API response
-    let text_body = response.text().await.map_err(ApiError::Request)?;
-    if config.debug_mode {
-        println!("GraphQL response: {}", text_body);
-    }
-    
-    // First parse the outer GraphQL structure
-    let graphql_response: serde_json::Value = serde_json::from_str(&text_body)
-        .map_err(ApiError::JsonDeserialization)?;
-    
-    // Extract the data.title.structure.excerpts.resources array
-    let resources = graphql_response
-        .get("data")
-        .and_then(|data| data.get("title"))
-        .and_then(|title| title.get("structure"))
-        .and_then(|structure| structure.get("excerpts"))
-        .and_then(|excerpts| excerpts.get("resources"))
-        .ok_or_else(|| ApiError::GloboApi("Missing resources in GraphQL response".to_string()))?;
-    
-    // Convert to our DatedVideosResponse format
-    let resources_json = serde_json::to_string(resources)
-        .map_err(|e| ApiError::JsonDeserialization(e))?;
-    
-    let videos_response: DatedVideosResponse = serde_json::from_str(&resources_json)
-        .map_err(|e| ApiError::JsonDeserialization(e))?;
-    
-    Ok(videos_response)
-}
+    /// Lazily yields a title's videos in a date range, fetching the next page as the consumer reaches it
+    ///
+    /// Same arguments as `videos_by_date_pages`; only one page of items is held at a time.
+    ///
+    /// ```no_run
+    /// # async fn example(client: &globo_play::client::GloboClient) -> globo_play::Result<()> {
+    /// use futures_util::TryStreamExt;
+    /// let title_id: globo_play::ids::TitleId = "12345".parse()?;
+    /// let videos = client.videos_by_date_stream(&title_id, "2024-01-01", "2024-12-31", 1, 100);
+    /// futures_util::pin_mut!(videos);
+    /// while let Some(video) = videos.try_next().await? {
+    ///     println!("{} {}", video.id, video.title);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn videos_by_date_stream<'a>(
+        &'a self,
+        title_id: &TitleId,
+        from_date: &str,
+        to_date: &str,
+        start_page: u32,
+        per_page: u32,
+    ) -> impl Stream<Item = Result<DatedVideoItem>> + 'a {
+        self.videos_by_date_pages(title_id, from_date, to_date, start_page, per_page)
+            .map_ok(|page| stream::iter(page.items.into_iter().map(Ok)))
+            .try_flatten()
+    }
 
-// Placeholder for fetching a single video's general info (not session)
-// This might be useful if there's an endpoint for just metadata without sources.
-// pub async fn fetch_video_details(video_id: &str, config: &AppConfig) -> Result<Video, ApiError> {
-//     let url = format!(
-//         "{}{}",
-//         constants::GLOBO_API_BASE_URL,
-//         constants::VIDEO_DETAILS_URL_TEMPLATE.replace("{}", video_id)
-//     );
-//     fetch_json(&config.http_client, &url, config).await
-// }
+    /// Searches titles, videos and channels matching `query`
+    pub async fn search(&self, query: &str, page: u32, per_page: u32) -> Result<SearchResults> {
+        self.api.search(query, page, per_page).await
+    }
+
+    /// Fetches a title's program metadata by title ID or slug (values containing a dash)
+    pub async fn fetch_title(&self, id_or_slug: &str) -> Result<TitleDetails> {
+        self.api.fetch_title(id_or_slug).await
+    }
+
+    /// Fetches one page of a title's episodes, optionally restricted to a season
+    pub async fn fetch_episodes(&self, title_id: &TitleId, season_id: Option<&str>, page: u32, per_page: u32) -> Result<ResultPage<Episode>> {
+        self.api.fetch_episodes(title_id, season_id, page, per_page).await
+    }
+
+    /// Fetches the live broadcasts available to the account, with their current programming
+    pub async fn fetch_broadcasts(&self) -> Result<Vec<Broadcast>> {
+        self.api.fetch_broadcasts().await
+    }
+
+    /// Fetches the electronic program guide of a live broadcast for one day (YYYY-MM-DD)
+    pub async fn fetch_epg(&self, media_id: &VideoId, date: &str) -> Result<Vec<EpgSlot>> {
+        self.api.fetch_epg(media_id, date).await
+    }
+
+    /// Fetches the catalog categories
+    pub async fn fetch_categories(&self) -> Result<Vec<Category>> {
+        self.api.fetch_categories().await
+    }
+
+    /// Fetches one page of the titles in a catalog category
+    pub async fn fetch_category_titles(&self, slug: &str, page: u32, per_page: u32) -> Result<ResultPage<TitleSummary>> {
+        self.api.fetch_category_titles(slug, page, per_page).await
+    }
+
+    /// Fetches one page of the authenticated account's continue-watching list
+    pub async fn fetch_continue_watching(&self, page: u32, per_page: u32) -> Result<ResultPage<ContinueWatchingItem>> {
+        self.api.fetch_continue_watching(page, per_page).await
+    }
+
+    /// Fetches one page of the titles and videos saved to the authenticated account's "My List"
+    pub async fn fetch_my_list(&self, page: u32, per_page: u32) -> Result<MyList> {
+        self.api.fetch_my_list(page, per_page).await
+    }
+
+    /// Fetches the home-page highlight rails (most watched, editor highlights, ...)
+    pub async fn fetch_highlights(&self) -> Result<Vec<HighlightRail>> {
+        self.api.fetch_highlights().await
+    }
+
+    /// Fetches a video's metadata (title, program, duration, availability) without creating a playback session
+    pub async fn fetch_video_details(&self, video_id: &VideoId) -> Result<Video> {
+        self.api.fetch_video_details(video_id).await
+    }
+}
@@ -0,0 +1,132 @@
+// src/blocking.rs
+//! Synchronous wrapper around the async `GloboClient`, for scripts and codebases without an async runtime
+//!
+//! ```no_run
+//! # fn example() -> globo_play::Result<()> {
+//! let client = globo_play::client::GloboClient::builder().cookie_file("cookies.txt").build_blocking()?;
+//! let session = client.video_session(&"12345678".parse()?)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Each client runs its requests on a private single-threaded tokio runtime, so its methods must
+//! not be called from inside an async context (tokio panics if they are).
+use crate::api::{GraphQlQuery, VideoSessionRequest};
+use crate::client;
+use crate::error::{Error, Result};
+use crate::ids::{TitleId, VideoId};
+use crate::models::{
+    Broadcast, Category, ContinueWatchingItem, DatedVideoItem, DatedVideosResponse, EpgSlot, Episode, HighlightRail, MyList, ResultPage,
+    SearchResults, TitleDetails, TitleSummary, Video, VideoSession,
+};
+use futures_util::StreamExt;
+use std::future::Future;
+
+/// Blocking counterpart of `client::GloboClient`; each method waits for the async call of the same name
+/// (without the `fetch_` prefix)
+#[derive(Debug)]
+pub struct GloboClient {
+    inner: client::GloboClient,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl GloboClient {
+    /// Wraps an async client, starting the runtime its requests run on
+    pub fn new(inner: client::GloboClient) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| Error::io("Failed to start the tokio runtime", e))?;
+        Ok(GloboClient { inner, runtime })
+    }
+
+    /// The wrapped async client, e.g. for its settings
+    pub fn inner(&self) -> &client::GloboClient {
+        &self.inner
+    }
+
+    pub(crate) fn wait<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    pub fn video_session(&self, video_id: &VideoId) -> Result<VideoSession> {
+        self.wait(self.inner.fetch_video_session(video_id))
+    }
+
+    pub fn video_session_with(&self, video_id: &VideoId, request: &VideoSessionRequest) -> Result<VideoSession> {
+        self.wait(self.inner.fetch_video_session_with(video_id, request))
+    }
+
+    pub fn video_details(&self, video_id: &VideoId) -> Result<Video> {
+        self.wait(self.inner.fetch_video_details(video_id))
+    }
+
+    pub fn graphql_request(&self, operation_name: &str, query: GraphQlQuery<'_>, variables: serde_json::Value) -> Result<serde_json::Value> {
+        self.wait(self.inner.graphql_request(operation_name, query, variables))
+    }
+
+    pub fn graphql_raw_request(&self, operation_name: &str, query: GraphQlQuery<'_>, variables: serde_json::Value) -> Result<serde_json::Value> {
+        self.wait(self.inner.graphql_raw_request(operation_name, query, variables))
+    }
+
+    pub fn videos_by_date(&self, title_id: &TitleId, from_date: &str, to_date: &str, page: u32, per_page: u32) -> Result<DatedVideosResponse> {
+        self.wait(self.inner.fetch_videos_by_date(title_id, from_date, to_date, page, per_page))
+    }
+
+    pub fn all_videos_by_date(&self, title_id: &TitleId, from_date: &str, to_date: &str, start_page: u32, per_page: u32) -> Result<DatedVideosResponse> {
+        self.wait(self.inner.fetch_all_videos_by_date(title_id, from_date, to_date, start_page, per_page))
+    }
+
+    /// Iterates over a title's videos in a date range, fetching the next page when the iterator reaches it
+    pub fn videos_by_date_iter<'a>(
+        &'a self,
+        title_id: &TitleId,
+        from_date: &str,
+        to_date: &str,
+        start_page: u32,
+        per_page: u32,
+    ) -> impl Iterator<Item = Result<DatedVideoItem>> + 'a {
+        let mut videos = Box::pin(self.inner.videos_by_date_stream(title_id, from_date, to_date, start_page, per_page));
+        std::iter::from_fn(move || self.wait(videos.next()))
+    }
+
+    pub fn search(&self, query: &str, page: u32, per_page: u32) -> Result<SearchResults> {
+        self.wait(self.inner.search(query, page, per_page))
+    }
+
+    pub fn title(&self, id_or_slug: &str) -> Result<TitleDetails> {
+        self.wait(self.inner.fetch_title(id_or_slug))
+    }
+
+    pub fn episodes(&self, title_id: &TitleId, season_id: Option<&str>, page: u32, per_page: u32) -> Result<ResultPage<Episode>> {
+        self.wait(self.inner.fetch_episodes(title_id, season_id, page, per_page))
+    }
+
+    pub fn broadcasts(&self) -> Result<Vec<Broadcast>> {
+        self.wait(self.inner.fetch_broadcasts())
+    }
+
+    pub fn epg(&self, media_id: &VideoId, date: &str) -> Result<Vec<EpgSlot>> {
+        self.wait(self.inner.fetch_epg(media_id, date))
+    }
+
+    pub fn categories(&self) -> Result<Vec<Category>> {
+        self.wait(self.inner.fetch_categories())
+    }
+
+    pub fn category_titles(&self, slug: &str, page: u32, per_page: u32) -> Result<ResultPage<TitleSummary>> {
+        self.wait(self.inner.fetch_category_titles(slug, page, per_page))
+    }
+
+    pub fn continue_watching(&self, page: u32, per_page: u32) -> Result<ResultPage<ContinueWatchingItem>> {
+        self.wait(self.inner.fetch_continue_watching(page, per_page))
+    }
+
+    pub fn my_list(&self, page: u32, per_page: u32) -> Result<MyList> {
+        self.wait(self.inner.fetch_my_list(page, per_page))
+    }
+
+    pub fn highlights(&self) -> Result<Vec<HighlightRail>> {
+        self.wait(self.inner.fetch_highlights())
+    }
+}
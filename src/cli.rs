@@ -1,39 +1,299 @@
 // src/cli.rs
 
-use clap::{Parser, Subcommand};
+use crate::constants;
+use crate::dates::Day;
+use crate::downloader::DownloaderKind;
+use crate::filename::SpaceStyle;
+use crate::i18n::Lang;
+use crate::ids::TitleId;
+use crate::layout::LibraryLayout;
+use crate::quality::Quality;
+use clap::{Parser, Subcommand, ValueEnum};
+
+/// Kind of video to keep when listing a title's videos
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum VideoKind {
+    /// Short clips, teasers and trailers
+    Excerpt,
+    /// Full episodes
+    Episode,
+    /// Everything
+    All,
+}
+
+/// Format of the debug log on stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// One human-readable line per event
+    Text,
+    /// One JSON object per event, with the event's fields and spans
+    Json,
+}
 
 /// Globo Play API Tool - A comprehensive tool for interacting with Globo Play API in Rust
 #[derive(Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
+#[clap(author, version, about, long_about = None, args_override_self = true)]
 pub struct Cli {
     #[clap(subcommand)]
     pub command: Option<Commands>,
 
+    /// Config file to read instead of config.toml in the platform config directory
+    #[clap(long, global = true, env = "GLOBO_PLAY_CONFIG", value_name = "PATH")]
+    pub config: Option<String>,
+
+    /// Use the settings of this profile from the config file ([profiles.NAME])
+    #[clap(long, global = true, env = "GLOBO_PLAY_PROFILE")]
+    pub profile: Option<String>,
+
     /// Path to cookie file for authentication
-    #[clap(long, short, global = true)]
+    #[clap(long, short, global = true, env = "GLOBO_PLAY_COOKIE")]
     pub cookie: Option<String>,
 
-    /// Set video quality (low, medium, high, max)
-    #[clap(long, global = true, default_value = "max")]
-    pub quality: String,
+    /// Don't write cookies refreshed by the server back to the cookie file or the stored login at exit
+    #[clap(long, global = true, env = "GLOBO_PLAY_NO_SAVE_COOKIES")]
+    pub no_save_cookies: bool,
+
+    /// Set video quality: max, min, a height such as 720p or a bitrate such as 2500k [default: max]
+    #[clap(long, global = true, env = "GLOBO_PLAY_QUALITY")]
+    pub quality: Option<Quality>,
+
+    /// Print each result item with a template instead of --output, e.g. "{id}\t{headline}\t{date_formated}"
+    #[clap(long, global = true, env = "GLOBO_PLAY_PRINT")]
+    pub print: Option<String>,
+
+    /// Output format (json, ndjson, compact, pretty, csv) [default: pretty]
+    #[clap(long, global = true, env = "GLOBO_PLAY_OUTPUT", value_parser = constants::OUTPUT_FORMATS)]
+    pub output: Option<String>,
 
-    /// Output format (json, compact, pretty)
-    #[clap(long, global = true, default_value = "pretty")]
-    pub output: String,
+    /// Only print results, warnings and errors (no banner or progress messages)
+    #[clap(long, short, global = true, env = "GLOBO_PLAY_QUIET", conflicts_with = "verbose")]
+    pub quiet: bool,
 
-    /// Enable debug mode
-    #[clap(long, short, global = true)]
+    /// Print more detail; repeat (-vv) to also enable debug output
+    #[clap(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Disable colored output (also disabled by NO_COLOR or when not writing to a terminal)
+    #[clap(long, global = true, env = "GLOBO_PLAY_NO_COLOR")]
+    pub no_color: bool,
+
+    /// Language of the banner, reports, prompts, notifications and error hints; defaults to the system locale
+    #[clap(long, global = true, env = "GLOBO_PLAY_LANG", value_enum)]
+    pub lang: Option<Lang>,
+
+    /// Enable debug mode: log the crate's debug events to stderr (RUST_LOG filters the log in detail)
+    #[clap(long, short, global = true, env = "GLOBO_PLAY_DEBUG")]
     pub debug: bool,
 
-    /// Directory for downloaded videos
-    #[clap(long, global = true, default_value = ".")]
-    pub output_dir: String,
+    /// Format of the debug log
+    #[clap(long, global = true, env = "GLOBO_PLAY_LOG_FORMAT", value_enum, default_value = "text")]
+    pub log_format: LogFormat,
+
+    /// Also append timestamped JSON logs of requests, downloads, warnings and errors to this file,
+    /// whatever the console verbosity
+    #[clap(long, global = true, env = "GLOBO_PLAY_LOG_FILE", value_name = "PATH")]
+    pub log_file: Option<String>,
+
+    /// Write a redacted diagnostics bundle (zip) of this run to PATH for a bug report: API requests
+    /// and responses, the debug log, the effective config and versions
+    #[clap(long, global = true, env = "GLOBO_PLAY_DEBUG_DUMP", value_name = "PATH")]
+    pub debug_dump: Option<String>,
+
+    /// Directory for downloaded videos [default: .]
+    #[clap(long, global = true, env = "GLOBO_PLAY_OUTPUT_DIR")]
+    pub output_dir: Option<String>,
+
+    /// Skip videos already in the download library unless their upstream version changed
+    #[clap(long, global = true, env = "GLOBO_PLAY_REDOWNLOAD_IF_CHANGED")]
+    pub redownload_if_changed: bool,
+
+    /// Write downloads into a media-server folder structure (Show/Season XX/Show - SxxExx - Title.mp4)
+    #[clap(long, global = true, env = "GLOBO_PLAY_LIBRARY_LAYOUT", value_enum)]
+    pub library_layout: Option<LibraryLayout>,
+
+    /// How spaces in titles are written in file names [default: keep]
+    #[clap(long, global = true, env = "GLOBO_PLAY_FILENAME_SPACES", value_enum, value_name = "STYLE")]
+    pub filename_spaces: Option<SpaceStyle>,
+
+    /// Download backend: ffmpeg, or fetching HLS segments natively [default: ffmpeg if built in]
+    #[clap(long, global = true, env = "GLOBO_PLAY_DOWNLOADER", value_enum)]
+    pub downloader: Option<DownloaderKind>,
+
+    /// Start downloads without first checking that the stream URL answers (a HEAD request, or a
+    /// one-byte GET when HEAD is rejected)
+    #[clap(long, global = true, env = "GLOBO_PLAY_NO_PRECHECK")]
+    pub no_precheck: bool,
+
+    /// Keep parsed HLS manifests on disk for this many seconds (0 = in-memory only)
+    #[clap(long, global = true, env = "GLOBO_PLAY_MANIFEST_CACHE_TTL", default_value_t = 0)]
+    pub manifest_cache_ttl: u64,
+
+    /// Reuse API metadata responses cached on disk for this many seconds (0 = no response cache)
+    #[clap(long, global = true, env = "GLOBO_PLAY_CACHE_TTL", value_name = "SECONDS", default_value_t = 0)]
+    pub cache_ttl: u64,
+
+    /// Neither read nor write the response cache, even with --cache-ttl
+    #[clap(long, global = true, env = "GLOBO_PLAY_NO_CACHE")]
+    pub no_cache: bool,
+
+    /// Maximum number of API requests per second, e.g. for --all-pages and batch commands (0 = unlimited)
+    #[clap(long, global = true, env = "GLOBO_PLAY_API_RATE_LIMIT", value_name = "REQUESTS_PER_SECOND", default_value_t = constants::DEFAULT_API_RATE_LIMIT)]
+    pub api_rate_limit: f64,
+
+    /// Filename template for downloads, e.g. "{program} - S{season}E{episode} - {title}.{ext}"
+    #[clap(long, global = true, env = "GLOBO_PLAY_OUTPUT_TEMPLATE")]
+    pub output_template: Option<String>,
+
+    /// Normalize audio loudness after each download (ffmpeg loudnorm)
+    #[clap(long, global = true, env = "GLOBO_PLAY_NORMALIZE_AUDIO")]
+    pub normalize_audio: bool,
+
+    /// Integrated loudness target in LUFS for --normalize-audio
+    #[clap(long, global = true, env = "GLOBO_PLAY_LOUDNESS_TARGET", default_value_t = -16.0, allow_negative_numbers = true)]
+    pub loudness_target: f64,
+
+    /// Use faster single-pass loudnorm instead of measuring first
+    #[clap(long, global = true, env = "GLOBO_PLAY_LOUDNORM_SINGLE_PASS")]
+    pub loudnorm_single_pass: bool,
+
+    /// Report how long each phase (session, manifest, download, post-process) took
+    #[clap(long, global = true, env = "GLOBO_PLAY_TIMINGS")]
+    pub timings: bool,
+
+    /// Audio language(s) to include, e.g. "pt", "en", "original" or "pt,original"
+    #[clap(long, global = true, env = "GLOBO_PLAY_AUDIO_LANG", value_delimiter = ',')]
+    pub audio_lang: Vec<String>,
+
+    /// After each download, assemble sampled frames into a single contact-sheet image
+    #[clap(long, global = true, env = "GLOBO_PLAY_CONTACT_SHEET")]
+    pub contact_sheet: bool,
+
+    /// Contact sheet grid as COLUMNSxROWS
+    #[clap(long, global = true, env = "GLOBO_PLAY_CONTACT_SHEET_GRID", default_value = "5x6")]
+    pub contact_sheet_grid: String,
+
+    /// Contact sheet image format
+    #[clap(long, global = true, env = "GLOBO_PLAY_CONTACT_SHEET_FORMAT", default_value = "jpg", value_parser = ["jpg", "png"])]
+    pub contact_sheet_format: String,
+
+    /// Save the video description/summary to a .description file next to the download
+    #[clap(long, global = true, env = "GLOBO_PLAY_WRITE_DESCRIPTION")]
+    pub write_description: bool,
+
+    /// Regional affiliate (e.g. EPTV, RBS) for local programming and the live Globo feed
+    #[clap(long, global = true, env = "GLOBO_PLAY_REGION")]
+    pub region: Option<String>,
+
+    /// Player type sent when creating video sessions, e.g. mobile [default: desktop]
+    #[clap(long, global = true, env = "GLOBO_PLAY_PLAYER_TYPE")]
+    pub player_type: Option<String>,
+
+    /// UTC offset of the viewer sent when creating video sessions [default: -03:00]
+    #[clap(long, global = true, env = "GLOBO_PLAY_SESSION_TIMEZONE", value_name = "OFFSET", allow_hyphen_values = true)]
+    pub session_timezone: Option<String>,
+
+    /// Request streams meant for streaming or for download [default: streaming]
+    #[clap(long, global = true, env = "GLOBO_PLAY_CONSUMPTION", value_parser = ["streaming", "download"])]
+    pub consumption: Option<String>,
+
+    /// Turn a player capability on or off when creating video sessions, e.g. low_latency=false (repeatable)
+    #[clap(long, global = true, value_name = "NAME=BOOL")]
+    pub session_capability: Vec<String>,
+
+    /// Override a persisted-query hash, e.g. getTitleVideosByDateView=<sha256> (repeatable)
+    #[clap(long, global = true, value_name = "OPERATION=HASH")]
+    pub query_hash: Vec<String>,
+
+    /// Only list content rated for children (L/livre or up to 10 years) in search, browse and videos-by-date
+    #[clap(long, global = true, env = "GLOBO_PLAY_KIDS_ONLY", conflicts_with = "exclude_kids")]
+    pub kids_only: bool,
+
+    /// Leave children's content (L/livre or up to 10 years) out of search, browse and videos-by-date
+    #[clap(long, global = true, env = "GLOBO_PLAY_EXCLUDE_KIDS")]
+    pub exclude_kids: bool,
+
+    /// Ask which one to use when a video has several streams, a channel several feeds, or an episode number several videos
+    #[clap(long, global = true, env = "GLOBO_PLAY_INTERACTIVE")]
+    pub interactive: bool,
+
+    /// Show a desktop notification when a download or batch of downloads finishes or fails
+    #[clap(long, global = true, env = "GLOBO_PLAY_NOTIFY")]
+    pub notify: bool,
+
+    /// POST a JSON event to this URL when downloads start, complete or fail and when watch finds a new episode (repeatable)
+    #[clap(long, global = true, env = "GLOBO_PLAY_WEBHOOK", value_name = "URL")]
+    pub webhook: Vec<String>,
+
+    /// Seconds to wait for a connection to be established (0 = no limit) [default: 10]
+    #[clap(long, global = true, env = "GLOBO_PLAY_CONNECT_TIMEOUT", value_name = "SECONDS")]
+    pub connect_timeout: Option<u64>,
+
+    /// Seconds a download may go without receiving data before it is aborted (0 = no limit) [default: 30]
+    #[clap(long, global = true, env = "GLOBO_PLAY_READ_TIMEOUT", value_name = "SECONDS")]
+    pub read_timeout: Option<u64>,
+
+    /// Seconds an API or page request may take in total (0 = no limit) [default: 60]
+    #[clap(long, global = true, env = "GLOBO_PLAY_TIMEOUT", value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+
+    /// Seconds a download may take in total before it is aborted (0 = no limit) [default: 0]
+    #[clap(long, global = true, env = "GLOBO_PLAY_DOWNLOAD_TIMEOUT", value_name = "SECONDS")]
+    pub download_timeout: Option<u64>,
+
+    /// Times a failed request or download is retried (0 = no retries) [default: 3]
+    #[clap(long, global = true, env = "GLOBO_PLAY_RETRIES", value_name = "N")]
+    pub retries: Option<u32>,
+
+    /// Milliseconds to wait before the first retry, doubled for each further one [default: 500]
+    #[clap(long, global = true, env = "GLOBO_PLAY_RETRY_BACKOFF", value_name = "MS")]
+    pub retry_backoff: Option<u64>,
+
+    /// Longest wait in seconds between retries [default: 30]
+    #[clap(long, global = true, env = "GLOBO_PLAY_RETRY_MAX_BACKOFF", value_name = "SECONDS")]
+    pub retry_max_backoff: Option<u64>,
+
+    /// HTTP statuses to retry besides connection errors and timeouts, comma-separated [default: 429,500,502,503,504]
+    #[clap(long, global = true, env = "GLOBO_PLAY_RETRY_ON", value_name = "STATUS", value_delimiter = ',', value_parser = clap::value_parser!(u16).range(100..=599))]
+    pub retry_on: Vec<u16>,
+
+    /// Idle connections kept open per host for reuse [default: no limit]
+    #[clap(long, global = true, env = "GLOBO_PLAY_POOL_MAX_IDLE", value_name = "N")]
+    pub pool_max_idle: Option<usize>,
+
+    /// Seconds an idle connection is kept open for reuse (0 = until the server closes it) [default: 90]
+    #[clap(long, global = true, env = "GLOBO_PLAY_POOL_IDLE_TIMEOUT", value_name = "SECONDS")]
+    pub pool_idle_timeout: Option<u64>,
+
+    /// Seconds between TCP keep-alive probes (0 = off) [default: 0]
+    #[clap(long, global = true, env = "GLOBO_PLAY_TCP_KEEPALIVE", value_name = "SECONDS")]
+    pub tcp_keepalive: Option<u64>,
+
+    /// Seconds between HTTP/2 pings keeping connections alive (0 = off) [default: 0]
+    #[clap(long, global = true, env = "GLOBO_PLAY_HTTP2_KEEP_ALIVE", value_name = "SECONDS")]
+    pub http2_keep_alive: Option<u64>,
+
+    /// Grow HTTP/2 flow-control windows with the measured bandwidth
+    #[clap(long, global = true, env = "GLOBO_PLAY_HTTP2_ADAPTIVE_WINDOW")]
+    pub http2_adaptive_window: bool,
+
+    /// PEM bundle of extra root certificates to trust, e.g. the CA of a TLS-intercepting corporate proxy
+    #[clap(long, global = true, env = "GLOBO_PLAY_CA_CERT", value_name = "PATH")]
+    pub ca_cert: Option<String>,
+
+    /// Skip TLS certificate verification for API requests and ffmpeg downloads (DANGEROUS: allows interception)
+    #[clap(long, global = true, env = "GLOBO_PLAY_INSECURE")]
+    pub insecure: bool,
+
+    /// Request body for --webhook with {event}, {video_id}, {title}, {title_id}, {path}, {error} and {timestamp} placeholders, or @FILE
+    #[clap(long, global = true, env = "GLOBO_PLAY_WEBHOOK_TEMPLATE")]
+    pub webhook_template: Option<String>,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Get basic info about a video
     Video {
+        /// Video ID or globoplay.globo.com/v/... URL
         video_id: String,
         /// Download video(s)
         #[clap(long)]
@@ -41,15 +301,20 @@ pub enum Commands {
         /// Custom filename for downloaded video
         #[clap(long)]
         filename: Option<String>,
-        /// Set video quality (low, medium, high, max) - overrides global
+        /// Set video quality (max, min, 720p, 2500k...) - overrides global
         #[clap(long)]
-        quality: Option<String>,
+        quality: Option<Quality>,
         /// Directory for downloaded videos - overrides global
         #[clap(long)]
         output_dir: Option<String>,
+        /// List every available stream (like the formats command) instead
+        #[clap(long, conflicts_with = "download")]
+        list_formats: bool,
     },
     /// Get detailed info with sources
+    #[clap(visible_alias = "info")]
     VideoInfo {
+        /// Video ID or globoplay.globo.com/v/... URL
         video_id: String,
         /// Download video(s)
         #[clap(long)]
@@ -57,22 +322,307 @@ pub enum Commands {
         /// Custom filename for downloaded video
         #[clap(long)]
         filename: Option<String>,
-        /// Set video quality (low, medium, high, max) - overrides global
+        /// Set video quality (max, min, 720p, 2500k...) - overrides global
         #[clap(long)]
-        quality: Option<String>,
+        quality: Option<Quality>,
         /// Directory for downloaded videos - overrides global
         #[clap(long)]
         output_dir: Option<String>,
+        /// List every available stream (like the formats command) instead
+        #[clap(long, conflicts_with = "download")]
+        list_formats: bool,
     },
     /// Get videos by date range
+    #[clap(visible_alias = "by-date")]
     VideosByDate {
-        title_id: String,
-        from_date: Option<String>, // Optional, will use default if not provided
-        to_date: Option<String>,   // Optional, will use default if not provided (or same as from_date)
+        title_id: TitleId,
+        /// Start date: YYYY-MM-DD, DD/MM/YYYY, today, yesterday, last-week, last-month or N-days-ago [default: today]
+        from_date: Option<Day>,
+        /// End date, in the same forms [default: the start date]
+        to_date: Option<Day>,
         /// Download all fetched videos
         #[clap(long)]
         download_all: bool,
+        /// Only list (and download) excerpts, full episodes, or both
+        #[clap(long, value_enum, default_value_t = VideoKind::All)]
+        kind: VideoKind,
+        /// Page number of results (first page fetched with --all-pages)
+        #[clap(long, default_value_t = 1)]
+        page: u32,
+        /// Results per page
+        #[clap(long, default_value_t = 20)]
+        per_page: u32,
+        /// Keep fetching pages until the API reports no next page
+        #[clap(long)]
+        all_pages: bool,
+    },
+    /// Search programs, videos and channels
+    Search {
+        query: String,
+        /// Page number of results
+        #[clap(long, default_value_t = 1)]
+        page: u32,
+        /// Results per page for each result type
+        #[clap(long, default_value_t = 20)]
+        per_page: u32,
+    },
+    /// Get program/show information for a title
+    Title {
+        /// Title ID or slug (e.g. "jornal-nacional")
+        title_id: String,
+    },
+    /// List the episodes of a title, by season
+    #[clap(visible_alias = "eps")]
+    Episodes {
+        title_id: TitleId,
+        /// Season number; defaults to the title's default season
+        #[clap(long)]
+        season: Option<u32>,
+        /// Download all listed episodes
+        #[clap(long)]
+        download_all: bool,
+    },
+    /// List the account's in-progress videos (requires --cookie)
+    #[clap(visible_alias = "cw")]
+    ContinueWatching {
+        /// Download the listed videos
+        #[clap(long)]
+        download: bool,
+    },
+    /// List the titles and videos saved to the account's My List (requires --cookie)
+    #[clap(visible_alias = "ml")]
+    MyList {
+        /// Download full episodes of saved titles published since the last run
+        #[clap(long)]
+        download_new: bool,
+    },
+    /// List what's currently featured on the home page (most watched, highlights)
+    Trending {
+        /// Only show rails whose name contains this text (case-insensitive)
+        #[clap(long)]
+        rail: Option<String>,
+    },
+    /// List a novela's chapters in order, optionally downloading a range
+    Chapters {
+        title_id: TitleId,
+        /// First chapter to list/download
+        #[clap(long)]
+        from_chapter: Option<u32>,
+        /// Last chapter to list/download
+        #[clap(long)]
+        to_chapter: Option<u32>,
+        /// Download the listed chapters
+        #[clap(long)]
+        download: bool,
+    },
+    /// Send a raw GraphQL operation and print the JSON response
+    #[clap(group(clap::ArgGroup::new("source").required(true).args(["hash", "query"])))]
+    Graphql {
+        /// Operation name
+        operation: String,
+        /// Variables as inline JSON, or @FILE to read them from a file
+        #[clap(long, default_value = "{}")]
+        variables: String,
+        /// Persisted-query sha256 hash
+        #[clap(long)]
+        hash: Option<String>,
+        /// Full query text, or @FILE to read it from a file
+        #[clap(long)]
+        query: Option<String>,
+    },
+    /// Download a video; shorthand for `video <ID> --download`
+    Dl {
+        /// Video ID or globoplay.globo.com/v/... URL
+        video_id: String,
+        /// Custom filename for downloaded video
+        #[clap(long)]
+        filename: Option<String>,
+        /// Set video quality (max, min, 720p, 2500k...) - overrides global
+        #[clap(long)]
+        quality: Option<Quality>,
+        /// Directory for downloaded videos - overrides global
+        #[clap(long)]
+        output_dir: Option<String>,
+    },
+    /// Show or download whatever a Globo Play URL points at (a video or a program)
+    Get {
+        /// Video or program page URL, video ID or title ID
+        url: String,
+        /// Download the video, or the latest episode of a program
+        #[clap(long)]
+        download: bool,
+        /// Set video quality (max, min, 720p, 2500k...) - overrides global
+        #[clap(long)]
+        quality: Option<Quality>,
+        /// Directory for downloaded videos - overrides global
+        #[clap(long)]
+        output_dir: Option<String>,
+    },
+    /// Poll a title and download new episodes as they are published
+    Watch {
+        /// ID of the title/program to watch
+        title_id: TitleId,
+        /// Time between checks, e.g. 30m, 1h, 2h30m
+        #[clap(long, default_value = "30m")]
+        interval: String,
+    },
+    /// Run the scheduled jobs from the config file until stopped
+    Daemon,
+    /// List recorded downloads from the download history
+    History {
+        /// Only entries whose video ID or title contains this text
+        #[clap(long)]
+        search: Option<String>,
+        /// Only entries with this result
+        #[clap(long, value_parser = ["downloaded", "skipped", "failed"])]
+        status: Option<String>,
+        /// Only entries from this day on: YYYY-MM-DD, DD/MM/YYYY, today, yesterday, last-week, last-month or N-days-ago
+        #[clap(long)]
+        since: Option<Day>,
+        /// Show at most this many of the most recent entries
+        #[clap(long, default_value_t = 50)]
+        limit: usize,
+    },
+    /// List every stream of a video: label, resolution, codecs, CDN, type and expiration
+    Formats {
+        /// Video ID or globoplay.globo.com/v/... URL
+        video_id: String,
+    },
+    /// Browse and search the catalog interactively, queueing episodes for download
+    Tui,
+    /// Create, show or change the config file
+    Config {
+        #[clap(subcommand)]
+        action: ConfigAction,
+    },
+    /// Store the cookies of a browser-exported cookie file so --cookie isn't needed
+    Login {
+        /// Netscape-format cookie file; it can be deleted afterwards
+        cookie_file: String,
+    },
+    /// Delete the cookies stored by `login`
+    Logout,
+    /// Print a shell completion script to stdout
+    Completions {
+        /// Shell to generate completions for
+        #[clap(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// List live channels and their current programming
+    Channels,
+    /// Show or record a live channel
+    Live {
+        /// Channel ID/slug from `channels`, or a live media ID
+        channel_id: String,
+        /// Record the live stream
+        #[clap(long, requires = "duration")]
+        record: bool,
+        /// Recording length, e.g. 1h30m, 45m, 90s
+        #[clap(long)]
+        duration: Option<String>,
+        /// Custom filename for the recording
+        #[clap(long)]
+        filename: Option<String>,
+    },
+    /// Show the broadcast schedule (EPG) of a live channel
+    #[clap(visible_alias = "epg")]
+    Schedule {
+        /// Channel ID/slug from `channels`, or a live media ID
+        channel_id: String,
+        /// Day to list: YYYY-MM-DD, DD/MM/YYYY, today, yesterday, last-week, last-month or N-days-ago [default: today]
+        #[clap(long)]
+        date: Option<Day>,
+    },
+    /// Browse catalog categories, or the titles within one
+    Browse {
+        /// Category slug (e.g. "novelas"); lists all categories when omitted
+        category: Option<String>,
+        /// Page number of titles
+        #[clap(long, default_value_t = 1)]
+        page: u32,
+        /// Titles per page
+        #[clap(long, default_value_t = 20)]
+        per_page: u32,
+    },
+    /// Dump the metadata of titles to dated JSON files for a historical record of the catalog
+    Snapshot {
+        /// Title/program IDs to snapshot; defaults to the config file's `snapshot` list, or else the titles of its sync jobs
+        title_ids: Vec<TitleId>,
+        /// Start date: YYYY-MM-DD, DD/MM/YYYY, today, yesterday, last-week, last-month or N-days-ago; defaults to January 1 of the title's release year
+        #[clap(long)]
+        from_date: Option<Day>,
+        /// End date, in the same forms; defaults to today
+        #[clap(long)]
+        to_date: Option<Day>,
+        /// Directory for snapshot files; defaults to <output-dir>/snapshots
+        #[clap(long)]
+        snapshot_dir: Option<String>,
+    },
+    /// Find the episode that follows a given episode number of a title
+    #[clap(visible_alias = "next")]
+    NextEpisode {
+        title_id: TitleId,
+        /// Episode number already watched/downloaded; defaults to the latest episode found
+        #[clap(long)]
+        after: Option<u32>,
+        /// How many days back to search for episodes
+        #[clap(long, default_value_t = 30)]
+        days: i64,
+        /// Download the next episode if it is available
+        #[clap(long)]
+        download: bool,
+    },
+    /// List the day's news bulletins of journalism programs (JN, GloboNews editions, ...)
+    News {
+        /// Program IDs or slugs; defaults to the main Globo and GloboNews bulletins
+        programs: Vec<String>,
+        /// Day to list: YYYY-MM-DD, DD/MM/YYYY, today, yesterday, last-week, last-month or N-days-ago [default: today]
+        #[clap(long)]
+        date: Option<Day>,
+        /// Download the latest bulletin of each program
+        #[clap(long)]
+        download_latest: bool,
+    },
+    /// List the most recently published full episodes of a title
+    Latest {
+        title_id: TitleId,
+        /// Number of episodes to list
+        #[clap(long, default_value_t = 1)]
+        count: usize,
+        /// Download the listed episodes
+        #[clap(long)]
+        download: bool,
+    },
+}
+
+/// Actions of the config command
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Write a commented default config file
+    Init {
+        /// Overwrite an existing config file
+        #[clap(long)]
+        force: bool,
+    },
+    /// Print the effective settings (flags, environment and config file merged), or one of them
+    Get {
+        /// Setting to print, e.g. default_quality or query_hashes.getTitleVideosByDateView
+        key: Option<String>,
+    },
+    /// Set a key in the config file, or in the --profile profile; webhooks takes a comma-separated list
+    Set {
+        /// Setting to change, e.g. default_quality, query_hashes.getTitleVideosByDateView or commands.video
+        key: String,
+        /// New value; for commands.<COMMAND> the arguments, e.g. "--per-page 50 --kind episode"
+        #[clap(allow_hyphen_values = true)]
+        value: String,
     },
+    /// Print the location of the config file
+    Path,
+    /// Open the config file in $VISUAL or $EDITOR, creating it first if needed
+    Edit,
+    /// Check the config file for unknown keys, invalid values, missing cookie files and unwritable directories
+    Validate,
 }
 
 // Functions to handle commands will go here or in main.rs
@@ -0,0 +1,419 @@
+// src/client.rs
+use crate::constants;
+use crate::cookies::SessionCookies;
+use crate::diagnostics::ExchangeLog;
+use crate::error::{Error, Result};
+use crate::net::{HostRule, HttpClient, PoolOptions, RateLimiter, ResponseCache, RetryPolicy, Transport};
+use crate::protocol::{ApiSettings, MetadataClient, VideoSessionRequest};
+use crate::quality::Quality;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Client for the Globo Play APIs: owns the HTTP client, the session cookies and the API base URLs
+///
+/// The API calls are methods of this type (see `api`). Build one with `GloboClient::builder()`:
+///
+/// ```no_run
+/// # async fn example() -> globo_play::Result<()> {
+/// let client = globo_play::client::GloboClient::builder()
+///     .cookie_file("cookies.txt")
+///     .quality(globo_play::quality::Quality::Height(1080))
+///     .build()?;
+/// let video_id: globo_play::ids::VideoId = "https://globoplay.globo.com/v/12345678/".parse()?;
+/// let session = client.fetch_video_session(&video_id).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct GloboClient {
+    pub(crate) api: MetadataClient<HttpClient>,
+    pub(crate) has_session: bool,
+    cookies: Arc<SessionCookies>,
+    cookie_file: Option<PathBuf>,
+}
+
+impl GloboClient {
+    /// Starts a builder with the defaults of the command-line tool
+    pub fn builder() -> GloboClientBuilder {
+        GloboClientBuilder::default()
+    }
+
+    /// The underlying HTTP client, with the session cookies and host rules applied
+    pub fn http(&self) -> &HttpClient {
+        self.api.fetcher()
+    }
+
+    /// True when session cookies were given, so requests are made as a logged-in account
+    pub fn has_session(&self) -> bool {
+        self.has_session
+    }
+
+    /// True when the server has set, refreshed or deleted session cookies since they were loaded
+    /// or last exported
+    pub fn cookies_changed(&self) -> bool {
+        self.cookies.changed()
+    }
+
+    /// The session cookies, including the ones refreshed by the server, in Netscape cookie-file format
+    pub fn export_cookies(&self) -> String {
+        self.cookies.to_netscape()
+    }
+
+    /// Writes refreshed session cookies back to the cookie file the client was built with
+    ///
+    /// Nothing is written when the cookies are unchanged or the client has no cookie file, or the
+    /// file has been removed since.
+    ///
+    /// # Returns
+    /// True when the cookie file was rewritten
+    pub fn save_cookies(&self) -> Result<bool> {
+        let Some(path) = self.cookie_file.as_ref().filter(|path| path.exists()) else {
+            return Ok(false);
+        };
+        if !self.cookies_changed() {
+            return Ok(false);
+        }
+        fs::write(path, self.export_cookies())
+            .map_err(|e| Error::io(format!("Failed to write cookie file: {}", path.display()), e))?;
+        Ok(true)
+    }
+
+    /// Quality requested when creating video sessions
+    pub fn quality(&self) -> Quality {
+        self.api.settings().quality
+    }
+
+    /// Affiliate code sent with GraphQL requests
+    pub fn region(&self) -> Option<&str> {
+        self.api.settings().region.as_deref()
+    }
+
+    /// Persisted-query hash overrides by operation name
+    pub fn query_hashes(&self) -> &HashMap<String, String> {
+        &self.api.settings().query_hashes
+    }
+
+    /// Persisted-query hash for `operation`, honoring user overrides of the built-in `default`
+    pub fn query_hash<'a>(&'a self, operation: &str, default: &'a str) -> &'a str {
+        self.api.query_hash(operation, default)
+    }
+}
+
+/// Builder for `GloboClient`; every setting has the same default as the command-line tool
+#[derive(Debug, Clone)]
+pub struct GloboClientBuilder {
+    cookie_file: Option<PathBuf>,
+    cookies: Option<String>,
+    quality: Quality,
+    session_request: VideoSessionRequest,
+    region: Option<String>,
+    query_hashes: HashMap<String, String>,
+    playback_base_url: String,
+    graphql_url: String,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    ca_cert: Option<PathBuf>,
+    insecure: bool,
+    hosts: Vec<HostRule>,
+    retry: RetryPolicy,
+    pool: PoolOptions,
+    cache_ttl: Option<Duration>,
+    api_rate_limit: Option<f64>,
+    exchange_log: Option<Arc<ExchangeLog>>,
+    transport: Option<Arc<dyn Transport>>,
+}
+
+impl Default for GloboClientBuilder {
+    fn default() -> Self {
+        GloboClientBuilder {
+            cookie_file: None,
+            cookies: None,
+            quality: Quality::default(),
+            session_request: VideoSessionRequest::default(),
+            region: None,
+            query_hashes: HashMap::new(),
+            playback_base_url: constants::PLAYBACK_API_BASE_URL.to_string(),
+            graphql_url: constants::GRAPHQL_API_BASE_URL.to_string(),
+            connect_timeout: Some(Duration::from_secs(constants::DEFAULT_CONNECT_TIMEOUT_SECS)),
+            timeout: Some(Duration::from_secs(constants::DEFAULT_TIMEOUT_SECS)),
+            ca_cert: None,
+            insecure: false,
+            hosts: Vec::new(),
+            retry: RetryPolicy::default(),
+            pool: PoolOptions::default(),
+            cache_ttl: None,
+            api_rate_limit: Some(constants::DEFAULT_API_RATE_LIMIT),
+            exchange_log: None,
+            transport: None,
+        }
+    }
+}
+
+impl GloboClientBuilder {
+    /// Netscape-format cookie file with the session cookies; a missing file is treated as an empty session
+    pub fn cookie_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cookie_file = Some(path.into());
+        self
+    }
+
+    /// Session cookies in Netscape cookie-file format, e.g. the ones stored by `login`
+    pub fn cookies(mut self, content: impl Into<String>) -> Self {
+        self.cookies = Some(content.into());
+        self
+    }
+
+    /// Quality requested when creating video sessions and picked among their streams
+    pub fn quality(mut self, quality: Quality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    /// Player fields sent when creating video sessions (player type, timezone, capabilities, consumption)
+    pub fn session_request(mut self, request: VideoSessionRequest) -> Self {
+        self.session_request = request;
+        self
+    }
+
+    /// Affiliate code sent with GraphQL requests, for regional programming
+    pub fn region(mut self, region: Option<String>) -> Self {
+        self.region = region;
+        self
+    }
+
+    /// Overrides the built-in persisted-query hash of a GraphQL operation
+    pub fn query_hash(mut self, operation: impl Into<String>, hash: impl Into<String>) -> Self {
+        self.query_hashes.insert(operation.into(), hash.into());
+        self
+    }
+
+    /// Overrides several persisted-query hashes at once
+    pub fn query_hashes(mut self, hashes: HashMap<String, String>) -> Self {
+        self.query_hashes.extend(hashes);
+        self
+    }
+
+    /// Base URL of the playback API that creates video sessions
+    pub fn playback_base_url(mut self, url: impl Into<String>) -> Self {
+        self.playback_base_url = url.into();
+        self
+    }
+
+    /// URL of the GraphQL (Jarvis) API
+    pub fn graphql_url(mut self, url: impl Into<String>) -> Self {
+        self.graphql_url = url.into();
+        self
+    }
+
+    /// Time allowed to establish a connection; `None` disables the limit
+    pub fn connect_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Time allowed for a whole request; `None` disables the limit
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// PEM bundle of extra root certificates to trust
+    pub fn ca_cert(mut self, path: Option<PathBuf>) -> Self {
+        self.ca_cert = path;
+        self
+    }
+
+    /// Accepts invalid TLS certificates
+    pub fn insecure(mut self, insecure: bool) -> Self {
+        self.insecure = insecure;
+        self
+    }
+
+    /// Per-host proxy, header and rate-limit rules
+    pub fn hosts(mut self, hosts: Vec<HostRule>) -> Self {
+        self.hosts = hosts;
+        self
+    }
+
+    /// Retry policy for failed requests
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Connection pool, keep-alive and HTTP/2 settings
+    pub fn pool(mut self, pool: PoolOptions) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    /// Keeps metadata responses in the cache directory and reuses them for this long (None = no cache)
+    pub fn cache_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// Maximum number of API requests per second, in bursts of up to `constants::API_RATE_BURST`
+    /// (None = unlimited); downloads aren't limited
+    pub fn api_rate_limit(mut self, rate: Option<f64>) -> Self {
+        self.api_rate_limit = rate;
+        self
+    }
+
+    /// Records the API requests and responses in `log`, for a diagnostics bundle (`diagnostics`)
+    pub fn exchange_log(mut self, log: Arc<ExchangeLog>) -> Self {
+        self.exchange_log = Some(log);
+        self
+    }
+
+    /// Sends the requests through `transport` instead of the network (see `net::Transport`)
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Loads the cookies and certificates and creates the client
+    pub fn build(self) -> Result<GloboClient> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            reqwest::header::USER_AGENT,
+            reqwest::header::HeaderValue::from_static("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/136.0.0.0 Safari/537.36"),
+        );
+
+        // Add additional headers found in marine-traffic scripts
+        headers.insert(
+            reqwest::header::ORIGIN,
+            reqwest::header::HeaderValue::from_static("https://globoplay.globo.com"),
+        );
+        headers.insert(
+            reqwest::header::REFERER,
+            reqwest::header::HeaderValue::from_static("https://globoplay.globo.com/"),
+        );
+        headers.insert(
+            "x-platform-id",
+            reqwest::header::HeaderValue::from_static("web"),
+        );
+        headers.insert(
+            "x-device-id",
+            reqwest::header::HeaderValue::from_static("desktop"),
+        );
+
+        let cookie_store = Arc::new(SessionCookies::default());
+        let mut has_session = false;
+        if let Some(path) = &self.cookie_file {
+            if path.exists() {
+                let content = fs::read_to_string(path)
+                    .map_err(|e| Error::io(format!("Failed to read cookie file: {}", path.display()), e))?;
+                cookie_store.add_netscape(&content)?;
+            }
+            has_session = true;
+        }
+        if let Some(cookies) = &self.cookies {
+            has_session |= cookie_store.add_netscape(cookies)? > 0;
+        }
+
+        let mut client = self.pool.apply(
+            reqwest::Client::builder()
+                .default_headers(headers)
+                .cookie_provider(cookie_store.clone()),
+        );
+        if let Some(connect_timeout) = self.connect_timeout {
+            client = client.connect_timeout(connect_timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            client = client.timeout(timeout);
+        }
+        if let Some(path) = &self.ca_cert {
+            for certificate in load_ca_bundle(path)? {
+                client = client.add_root_certificate(certificate);
+            }
+        }
+        if self.insecure {
+            client = client.danger_accept_invalid_certs(true);
+        }
+
+        let mut http = HttpClient::new(client, &self.hosts, self.retry)?;
+        if let Some(transport) = self.transport {
+            http = http.with_transport(transport);
+        }
+        if let Some(ttl) = self.cache_ttl {
+            http = http.with_cache(ResponseCache::new(ttl));
+        }
+        if let Some(rate) = self.api_rate_limit {
+            http = http.with_rate_limit(RateLimiter::new(rate, constants::API_RATE_BURST)?);
+        }
+        if let Some(log) = self.exchange_log {
+            http = http.with_exchange_log(log);
+        }
+
+        let settings = ApiSettings {
+            playback_base_url: self.playback_base_url,
+            graphql_url: self.graphql_url,
+            quality: self.quality,
+            session_request: self.session_request,
+            region: self.region,
+            query_hashes: self.query_hashes,
+        };
+        Ok(GloboClient {
+            api: MetadataClient::new(http, settings),
+            has_session,
+            cookies: cookie_store,
+            cookie_file: self.cookie_file,
+        })
+    }
+
+    /// Creates a blocking client that waits for each API call (see `blocking`)
+    #[cfg(feature = "blocking")]
+    pub fn build_blocking(self) -> Result<crate::blocking::GloboClient> {
+        crate::blocking::GloboClient::new(self.build()?)
+    }
+}
+
+/// Adds the cookies of a Netscape-format cookie file to `jar`
+///
+/// # Returns
+/// The number of cookies added
+pub fn add_netscape_cookies(jar: &reqwest::cookie::Jar, content: &str) -> Result<usize> {
+    let mut count = 0;
+    for line in content.lines() {
+        if line.starts_with("#") || line.trim().is_empty() {
+            continue;
+        }
+        let parts: Vec<&str> = line.split('\t').collect();
+        if parts.len() >= 7 {
+            // Basic Netscape cookie format parsing
+            // This is a simplified parser. A more robust one might be needed.
+            let domain = parts[0];
+            // let _flag = parts[1]; // TRUE/FALSE - path accessible from all paths
+            let _path_str = parts[2];
+            // let _secure = parts[3]; // TRUE/FALSE
+            // let _expiration = parts[4];
+            let name = parts[5];
+            let value = parts[6];
+
+            let cookie_str = format!("{}={}", name, value);
+            // The reqwest::cookie::Jar needs a URL to associate the cookie with.
+            // We'll use a placeholder Globo.com URL.
+            // This might need adjustment based on actual cookie requirements.
+            let url = format!("https://{}/", domain.trim_start_matches('.'))
+                .parse::<reqwest::Url>()
+                .map_err(|e| Error::parse(format!("Invalid cookie domain '{}'", domain), e))?;
+            jar.add_cookie_str(&cookie_str, &url);
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Reads the root certificates of a PEM bundle (--ca-cert)
+fn load_ca_bundle(path: &Path) -> Result<Vec<reqwest::Certificate>> {
+    let pem = fs::read(path).map_err(|e| Error::io(format!("Failed to read CA certificate bundle: {}", path.display()), e))?;
+    let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+        .map_err(|e| Error::parse(format!("Failed to parse CA certificate bundle: {}", path.display()), e))?;
+    if certificates.is_empty() {
+        return Err(Error::Invalid(format!("No PEM certificates found in {}", path.display())));
+    }
+    Ok(certificates)
+}
@@ -0,0 +1,1897 @@
+// src/commands.rs
+use crate::api;
+use crate::cli::{Cli, ConfigAction, VideoKind};
+use crate::client;
+use crate::config::{self, AppConfig};
+use crate::config_validate;
+use crate::constants;
+use crate::dates::{self, Day};
+use crate::download::{self, download_batch, ensure_description_sidecar, select_best_stream, source_resolution, BatchItem};
+use crate::error::Error;
+use crate::filename;
+use crate::hls;
+use crate::i18n::Msg;
+use crate::ids::{TitleId, VideoId};
+use crate::layout;
+use crate::library::{self, HistoryEntry, Library, SyncState};
+use crate::models;
+use crate::output;
+use crate::quality::Quality;
+use crate::paths;
+use crate::picker;
+use crate::report::{ItemReport, ItemStatus};
+use crate::secrets;
+use crate::target::{self, Target};
+use crate::tui;
+use crate::utils;
+use crate::webhook;
+use anyhow::{Context, Result};
+use clap::CommandFactory;
+use futures_util::{StreamExt, TryStreamExt};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// One stream of a video session, as listed by `formats`
+#[derive(Debug, serde::Serialize)]
+struct FormatRow {
+    label: Option<String>,
+    resolution: Option<u32>,
+    codecs: Option<String>,
+    cdn: Option<String>,
+    #[serde(rename = "type")]
+    kind: models::SourceKind,
+    expires_at: Option<String>,
+    audio_languages: Vec<String>,
+    url: String,
+}
+
+/// Formats a source's expiration time as local time, marking it if it has passed
+fn format_expiration(source: &models::Source) -> Option<String> {
+    let expires = source.expiration_time?.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M");
+    Some(if source.is_expired() { format!("{} (expired)", expires) } else { expires.to_string() })
+}
+
+/// Handles the formats command (and `--list-formats`), listing every stream of a video
+///
+/// # Arguments
+/// * `video_id` - The ID of the video
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_formats_command(video_id: VideoId, config: &AppConfig) -> Result<()> {
+    output::info!("Fetching streams for {}", video_id);
+    let session = config.client.fetch_video_session(&video_id).await?;
+    let playlists = hls::fetch_playlists(&session.sources, config).await;
+
+    let rows: Vec<FormatRow> = session
+        .sources
+        .iter()
+        .zip(&playlists)
+        .map(|(source, playlist)| {
+            let best_variant = playlist.as_ref().and_then(|p| p.select_variant(Quality::Max));
+            FormatRow {
+                label: source.label.clone().filter(|l| !l.is_empty()),
+                resolution: playlist
+                    .as_ref()
+                    .and_then(|p| p.max_height())
+                    .or_else(|| source_resolution(source, &HashMap::new())),
+                codecs: best_variant.and_then(|v| v.codecs.clone()),
+                cdn: source.cdn.clone(),
+                kind: source.kind.clone(),
+                expires_at: format_expiration(source),
+                audio_languages: playlist.as_ref().map(|p| p.audio_languages()).unwrap_or_default(),
+                url: source.url.clone(),
+            }
+        })
+        .collect();
+
+    if let Some(output) = config.format_output(&rows)? {
+        println!("{}", output);
+    } else {
+        let na = || "-".to_string();
+        let table: Vec<Vec<String>> = rows
+            .iter()
+            .enumerate()
+            .map(|(index, row)| {
+                vec![
+                    (index + 1).to_string(),
+                    row.label.clone().unwrap_or_else(na),
+                    row.resolution.map_or_else(na, |r| format!("{}p", r)),
+                    row.codecs.clone().unwrap_or_else(na),
+                    row.cdn.clone().unwrap_or_else(na),
+                    Some(row.kind.to_string()).filter(|kind| !kind.is_empty()).unwrap_or_else(na),
+                    row.expires_at.clone().unwrap_or_else(na),
+                    if row.audio_languages.is_empty() { na() } else { row.audio_languages.join(",") },
+                ]
+            })
+            .collect();
+        println!(
+            "{}",
+            utils::format_table(&["#", "LABEL", "RES", "CODECS", "CDN", "TYPE", "EXPIRES", "AUDIO"], &table)
+        );
+    }
+    Ok(())
+}
+
+/// Handles the get command, showing or downloading whatever a URL or ID points at
+///
+/// # Arguments
+/// * `input` - Video or program page URL, video ID or title ID
+/// * `download` - Download the video, or the latest episode of a program
+/// * `quality_override` - Optional quality setting that overrides the global config
+/// * `output_dir_override` - Optional output directory that overrides the global config
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_get_command(
+    input: String,
+    download: bool,
+    quality_override: Option<Quality>,
+    output_dir_override: Option<String>,
+    config: &AppConfig,
+) -> Result<()> {
+    match target::resolve(&input, config).await? {
+        Target::Video(video_id) => {
+            Ok(download::process_single_video(video_id, download, None, quality_override, output_dir_override, config, false).await?)
+        }
+        Target::Title(id_or_slug) if download => {
+            // Slugs are resolved to the title ID that `latest` needs
+            let title = config.client.fetch_title(&id_or_slug).await?;
+            handle_latest_command(title.title_id, 1, true, config).await
+        }
+        Target::Title(id_or_slug) => handle_title_command(id_or_slug, config).await,
+    }
+}
+
+/// Handles fetching videos by date and optionally downloading all videos in the result
+///
+/// # Arguments
+/// * `title_id` - The ID of the title/program to fetch videos for
+/// * `from_date_opt` - Optional start date, defaults to today
+/// * `to_date_opt` - Optional end date, defaults to the start date
+/// * `download_all` - Whether to download all videos in the result
+/// * `kind` - Which kind of videos (excerpts, full episodes or both) to keep
+/// * `page` - Page number of results; the first page fetched when `all_pages` is set
+/// * `per_page` - Results per page
+/// * `all_pages` - Keep fetching pages until the API reports no next page
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_videos_by_date_command(
+    title_id: TitleId,
+    from_date_opt: Option<Day>,
+    to_date_opt: Option<Day>,
+    download_all: bool,
+    kind: VideoKind,
+    page: u32,
+    per_page: u32,
+    all_pages: bool,
+    config: &AppConfig,
+) -> Result<()> {
+    let from = from_date_opt.unwrap_or_else(Day::today);
+    let to = to_date_opt.unwrap_or(from);
+    Day::check_range(from, to)?;
+    let (from_date, to_date) = (from.to_string(), to.to_string());
+
+    output::info!(
+        "Fetching videos for title ID: {} from {} to {} (page {}{}, per_page {})",
+        title_id,
+        from_date,
+        to_date,
+        page,
+        if all_pages { " onwards" } else { "" },
+        per_page
+    );
+
+    let wanted = |item: &models::DatedVideoItem| {
+        let kind_matches = match kind {
+            VideoKind::Excerpt => item.is_excerpt(),
+            VideoKind::Episode => !item.is_excerpt(),
+            VideoKind::All => true,
+        };
+        kind_matches && config.passes_kids_filter(item.content_rating.as_deref())
+    };
+    // With --all-pages a long range is fetched a month at a time; a single page is of the whole range
+    let windows = if all_pages {
+        Day::windows(from, to, constants::DATE_WINDOW_MAX_DAYS)
+    } else {
+        vec![(from, to)]
+    };
+    if windows.len() > 1 {
+        output::info!("Fetching the range in {} monthly windows", windows.len());
+    }
+    // Only the items are printed; the API's counts include the videos filtered out here
+    let mut response = models::DatedVideosResponse { items: Vec::new(), count: None, next: None };
+    let mut seen = HashSet::new();
+    let mut truncated = false;
+    for (index, (window_from, window_to)) in windows.into_iter().enumerate() {
+        // --page is where the range starts, so later months are read from their first page
+        let start_page = if index == 0 { page } else { 1 };
+        // Pages are filtered as they arrive, so only the wanted items of a long range are kept
+        let pages = config
+            .client
+            .videos_by_date_pages(&title_id, &window_from.to_string(), &window_to.to_string(), start_page, per_page)
+            .take(if all_pages { constants::PAGE_FOLLOW_MAX_PAGES as usize } else { 1 });
+        futures_util::pin_mut!(pages);
+        loop {
+            match pages.try_next().await {
+                Ok(Some(next_page)) => {
+                    response
+                        .items
+                        .extend(next_page.items.into_iter().filter(|item| wanted(item) && seen.insert(item.id.clone())));
+                    response.next = next_page.next;
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    output::error!("Error fetching videos by date for {}: {}", title_id, e);
+                    return Err(e.into());
+                }
+            }
+        }
+        truncated |= all_pages && response.next.is_some();
+    }
+    if truncated {
+        output::warning!(
+            "stopped after {} pages of a month; narrow the date range to see the rest",
+            constants::PAGE_FOLLOW_MAX_PAGES
+        );
+    }
+
+    if let Some(output) = config.format_output(&response.items)? {
+        println!("{}", output);
+    } else {
+        // Compact output
+        println!("Found {} videos:", response.items.len());
+        for video_item in &response.items {
+            println!(
+                "  ID: {}, Title: {}, Date: {}",
+                video_item.id,
+                video_item.headline.as_deref().unwrap_or("N/A"),
+                video_item.date_formated.as_deref().unwrap_or("N/A")
+            );
+        }
+        if !all_pages && response.next.is_some() {
+            output::info!("More videos available: use --page {} or --all-pages", page + 1);
+        }
+    }
+
+    if download_all {
+        let batch_items = response
+            .items
+            .iter()
+            .map(|item| BatchItem {
+                video_id: item.resource_id.clone().unwrap_or_else(|| item.id.clone()),
+                headline: item.headline.clone(),
+                summary: item.summary.clone(),
+            })
+            .collect();
+        download_batch(batch_items, config).await?.ensure_complete()?;
+    }
+    Ok(())
+}
+
+/// Handles the search command, printing matching titles, videos and channels
+///
+/// # Arguments
+/// * `query` - Free-text search terms
+/// * `page` - Page number of results
+/// * `per_page` - Results per page for each result type
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_search_command(query: String, page: u32, per_page: u32, config: &AppConfig) -> Result<()> {
+    output::info!("Searching for \"{}\" (page {}, per_page {})", query, page, per_page);
+    let mut results = config.client.search(&query, page, per_page).await?;
+    if let Some(titles) = results.title_result.as_mut() {
+        titles.resources.retain(|t| config.passes_kids_filter(t.content_rating.as_deref()));
+    }
+    if let Some(videos) = results.video_result.as_mut() {
+        videos.resources.retain(|v| config.passes_kids_filter(v.content_rating.as_deref()));
+    }
+
+    // CSV and --print get one table with a result_type column instead of three nested pages
+    let formatted = if config.wants_rows() {
+        let groups = vec![
+            ("title".to_string(), serde_json::to_value(results.title_result.as_ref().map(|r| &r.resources))?),
+            ("video".to_string(), serde_json::to_value(results.video_result.as_ref().map(|r| &r.resources))?),
+            ("channel".to_string(), serde_json::to_value(results.channel_result.as_ref().map(|r| &r.resources))?),
+        ];
+        config.format_output(&utils::tagged_rows("result_type", groups))?
+    } else {
+        config.format_output(&results)?
+    };
+    if let Some(output) = formatted {
+        println!("{}", output);
+    } else {
+        // Compact output
+        if let Some(titles) = &results.title_result {
+            println!("Titles ({}):", titles.resources.len());
+            for title in &titles.resources {
+                println!(
+                    "  ID: {}, Title: {}, Type: {}",
+                    title.id,
+                    title.headline.as_deref().unwrap_or("N/A"),
+                    title.title_type.as_deref().unwrap_or("N/A")
+                );
+            }
+        }
+        if let Some(videos) = &results.video_result {
+            println!("Videos ({}):", videos.resources.len());
+            for video in &videos.resources {
+                println!(
+                    "  ID: {}, Title: {}, Program: {}, Date: {}",
+                    video.id,
+                    video.headline.as_deref().unwrap_or("N/A"),
+                    video.title.as_ref().and_then(|t| t.headline.as_deref()).unwrap_or("N/A"),
+                    video.exhibited_at.map_or_else(|| "N/A".to_string(), |t| t.format("%Y-%m-%d %H:%M").to_string())
+                );
+            }
+        }
+        if let Some(channels) = &results.channel_result {
+            println!("Channels ({}):", channels.resources.len());
+            for channel in &channels.resources {
+                println!("  ID: {}, Name: {}", channel.id, channel.name.as_deref().unwrap_or("N/A"));
+            }
+        }
+        let has_more = results.title_result.as_ref().is_some_and(|r| r.has_next_page)
+            || results.video_result.as_ref().is_some_and(|r| r.has_next_page)
+            || results.channel_result.as_ref().is_some_and(|r| r.has_next_page);
+        if has_more {
+            output::info!("More results available: use --page {}", page + 1);
+        }
+    }
+    Ok(())
+}
+
+/// Handles the title command, printing a program's metadata
+///
+/// # Arguments
+/// * `title_id` - The title ID or slug
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_title_command(title_id: String, config: &AppConfig) -> Result<()> {
+    output::info!("Fetching title: {}", title_id);
+    let title = config.client.fetch_title(&title_id).await?;
+
+    if let Some(output) = config.format_output(&title)? {
+        println!("{}", output);
+    } else {
+        // Compact output
+        println!("Title: {}", title.headline.as_deref().unwrap_or("N/A"));
+        println!("ID: {}", title.title_id);
+        if let Some(slug) = &title.slug {
+            println!("Slug: {}", slug);
+        }
+        println!("Type: {}", title.title_type.as_deref().unwrap_or("N/A"));
+        if let Some(year) = title.release_year {
+            println!("Year: {}", year);
+        }
+        if !title.genres_names.is_empty() {
+            println!("Genres: {}", title.genres_names.join(", "));
+        }
+        if let Some(rating) = &title.content_rating {
+            println!("Rating: {}", rating);
+        }
+        if let Some(description) = &title.description {
+            println!("Description: {}", description);
+        }
+        if let Some(seasons) = title.structure.as_ref().and_then(|s| s.seasons.as_ref()) {
+            println!("Seasons:");
+            for season in &seasons.resources {
+                println!(
+                    "  - Season {} (ID: {}): {} episodes",
+                    season.number.map_or_else(|| "?".to_string(), |n| n.to_string()),
+                    season.id,
+                    season.total_episodes.map_or_else(|| "?".to_string(), |n| n.to_string())
+                );
+            }
+        }
+        if let Some(count) = title.episode_count() {
+            println!("Episodes: {}", count);
+        }
+        if let Some(poster) = &title.poster {
+            if let Some(url) = poster.web.as_ref().or(poster.tv.as_ref()).or(poster.mobile.as_ref()) {
+                println!("Poster: {}", url);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fetches every episode of a title's season, following pagination
+///
+/// # Arguments
+/// * `title_id` - The ID of the title/program
+/// * `season` - Season number; `None` for the default season
+/// * `config` - The application configuration
+///
+/// # Returns
+/// The episodes in listing order
+async fn fetch_all_episodes(title_id: &TitleId, season: Option<u32>, config: &AppConfig) -> Result<Vec<models::Episode>> {
+    let season_id = match season {
+        Some(number) => {
+            let title = config.client.fetch_title(title_id.as_str()).await?;
+            let seasons = title
+                .structure
+                .and_then(|s| s.seasons)
+                .map(|s| s.resources)
+                .unwrap_or_default();
+            let season = seasons
+                .into_iter()
+                .find(|s| s.number == Some(number))
+                .ok_or_else(|| anyhow::anyhow!("Title {} has no season {}", title_id, number))?;
+            Some(season.id)
+        }
+        None => None,
+    };
+
+    let per_page = 50;
+    let max_pages = 40; // Safety cap for very long-running titles
+    let mut episodes = Vec::new();
+    let mut page = 1;
+    loop {
+        let result = config.client.fetch_episodes(title_id, season_id.as_deref(), page, per_page).await?;
+        episodes.extend(result.resources);
+        match result.next_page {
+            Some(next) if result.has_next_page && page < max_pages => page = next,
+            _ => break,
+        }
+    }
+    Ok(episodes)
+}
+
+/// Handles the episodes command, listing a title's episodes and optionally downloading them
+///
+/// # Arguments
+/// * `title_id` - The ID of the title/program
+/// * `season` - Season number; `None` for the default season
+/// * `download_all` - Whether to download all listed episodes
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_episodes_command(
+    title_id: TitleId,
+    season: Option<u32>,
+    download_all: bool,
+    config: &AppConfig,
+) -> Result<()> {
+    output::info!(
+        "Fetching episodes for title ID: {}{}",
+        title_id,
+        season.map(|s| format!(" (season {})", s)).unwrap_or_default()
+    );
+    let episodes = fetch_all_episodes(&title_id, season, config).await?;
+
+    if let Some(output) = config.format_output(&episodes)? {
+        println!("{}", output);
+    } else {
+        // Compact output
+        println!("Found {} episodes:", episodes.len());
+        for episode in &episodes {
+            println!(
+                "  {} ID: {}, Title: {}, Date: {}, Duration: {}",
+                match (episode.season_number, episode.number) {
+                    (Some(s), Some(e)) => format!("S{:02}E{:02}", s, e),
+                    (None, Some(e)) => format!("E{:02}", e),
+                    _ => "   ".to_string(),
+                },
+                episode.video_id(),
+                episode.headline.as_deref().unwrap_or("N/A"),
+                episode.exhibited_at.map_or_else(|| "N/A".to_string(), |t| t.format("%Y-%m-%d %H:%M").to_string()),
+                episode.duration.map_or_else(|| "N/A".to_string(), dates::format_clock)
+            );
+        }
+    }
+
+    if download_all {
+        let batch_items = episodes
+            .iter()
+            .map(|episode| BatchItem {
+                video_id: episode.video_id().clone(),
+                headline: episode.headline.clone(),
+                summary: episode.description.clone(),
+            })
+            .collect();
+        download_batch(batch_items, config).await?.ensure_complete()?;
+    }
+    Ok(())
+}
+
+/// Handles the chapters command, listing a novela's chapters in order
+///
+/// Chapter numbers come from the episode metadata, or are detected from headlines such as
+/// "Capítulo 152" when missing.
+///
+/// # Arguments
+/// * `title_id` - The ID of the novela
+/// * `from_chapter` - Optional first chapter of the range
+/// * `to_chapter` - Optional last chapter of the range
+/// * `download` - Whether to download the chapters in the range
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_chapters_command(
+    title_id: TitleId,
+    from_chapter: Option<u32>,
+    to_chapter: Option<u32>,
+    download: bool,
+    config: &AppConfig,
+) -> Result<()> {
+    output::info!("Fetching chapters of {}", title_id);
+    let episodes = fetch_all_episodes(&title_id, None, config).await?;
+
+    // Chapter numbers detected from headlines are filled into `number` so every chapter has one
+    let mut chapters: Vec<models::Episode> = episodes
+        .into_iter()
+        .filter_map(|mut episode| {
+            let detected = episode
+                .headline
+                .as_deref()
+                .and_then(|h| layout::detect_episode_numbers(h).1);
+            episode.number = episode.number.or(detected);
+            episode.number.map(|_| episode)
+        })
+        .filter(|episode| from_chapter.is_none_or(|from| episode.number >= Some(from)))
+        .filter(|episode| to_chapter.is_none_or(|to| episode.number <= Some(to)))
+        .collect();
+    // Re-runs of a chapter share its number; the first airing comes first
+    chapters.sort_by_key(|episode| (episode.number, episode.exhibited_at));
+
+    if let Some(output) = config.format_output(&chapters)? {
+        println!("{}", output);
+    } else {
+        // Compact output
+        println!("Found {} chapters:", chapters.len());
+        for episode in &chapters {
+            println!(
+                "  Capítulo {}: {}, Date: {}, ID: {}",
+                episode.number.unwrap_or_default(),
+                episode.headline.as_deref().unwrap_or("N/A"),
+                episode.exhibited_at.map_or_else(|| "N/A".to_string(), |t| t.format("%Y-%m-%d").to_string()),
+                episode.video_id()
+            );
+        }
+    }
+
+    if download {
+        let batch_items = chapters
+            .iter()
+            .map(|episode| BatchItem {
+                video_id: episode.video_id().clone(),
+                headline: episode.headline.clone(),
+                summary: episode.description.clone(),
+            })
+            .collect();
+        download_batch(batch_items, config).await?.ensure_complete()?;
+    }
+    Ok(())
+}
+
+/// Handles the continue-watching command, listing the account's in-progress videos
+///
+/// # Arguments
+/// * `download` - Whether to download the listed videos
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_continue_watching_command(download: bool, config: &AppConfig) -> Result<()> {
+    config.require_auth("continue-watching")?;
+    output::info!("Fetching continue-watching list");
+
+    let per_page = 50;
+    let max_pages = 20; // Safety cap
+    let mut items = Vec::new();
+    let mut page = 1;
+    loop {
+        let result = config.client.fetch_continue_watching(page, per_page).await?;
+        items.extend(result.resources);
+        match result.next_page {
+            Some(next) if result.has_next_page && page < max_pages => page = next,
+            _ => break,
+        }
+    }
+
+    if let Some(output) = config.format_output(&items)? {
+        println!("{}", output);
+    } else {
+        // Compact output
+        println!("Found {} in-progress videos:", items.len());
+        for item in &items {
+            let position = match (item.resume_point, item.duration) {
+                (Some(position), Some(duration)) => format!(
+                    "{} / {} ({}%)",
+                    dates::format_clock(position),
+                    dates::format_clock(duration),
+                    item.progress_percent().unwrap_or(0)
+                ),
+                (Some(position), None) => dates::format_clock(position),
+                _ => "N/A".to_string(),
+            };
+            println!(
+                "  ID: {}, Program: {}, Title: {}, Resume at: {}",
+                item.id,
+                item.title.as_ref().and_then(|t| t.headline.as_deref()).unwrap_or("N/A"),
+                item.headline.as_deref().unwrap_or("N/A"),
+                position
+            );
+        }
+    }
+
+    if download {
+        let batch_items = items
+            .iter()
+            .map(|item| BatchItem {
+                video_id: item.id.clone(),
+                headline: item.headline.clone(),
+                summary: item.description.clone(),
+            })
+            .collect();
+        download_batch(batch_items, config).await?.ensure_complete()?;
+    }
+    Ok(())
+}
+
+/// Lists a title's full episodes published since its last sync check that aren't in the library yet
+///
+/// # Arguments
+/// * `title_id` - The title to check
+/// * `name` - Display name for progress messages
+/// * `sync_state` - Last check dates per title
+/// * `library` - Already downloaded videos
+/// * `config` - The application configuration
+///
+/// # Returns
+/// `None` on the title's first check, when only a baseline should be recorded
+async fn new_episodes_since_last_check(
+    title_id: &TitleId,
+    name: Option<&str>,
+    sync_state: &SyncState,
+    library: &Library,
+    config: &AppConfig,
+) -> Result<Option<Vec<BatchItem>>> {
+    let Some(since) = sync_state.last_checked_date(title_id) else {
+        output::info!(
+            "First check of {}: recording a baseline, new episodes will be downloaded from the next run",
+            name.unwrap_or(title_id.as_str())
+        );
+        return Ok(None);
+    };
+    let from_date = since.format("%Y-%m-%d").to_string();
+    let to_date = chrono::Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let response = config.client.fetch_all_videos_by_date(title_id, &from_date, &to_date, 1, 100).await?;
+    let mut items: Vec<BatchItem> = Vec::new();
+    for item in response.items.iter().filter(|item| !item.is_excerpt()) {
+        let video_id = item.resource_id.clone().unwrap_or_else(|| item.id.clone());
+        if library.items.contains_key(video_id.as_str()) || items.iter().any(|i| i.video_id == video_id) {
+            continue;
+        }
+        items.push(BatchItem {
+            video_id,
+            headline: item.headline.clone(),
+            summary: item.summary.clone(),
+        });
+    }
+    Ok(Some(items))
+}
+
+/// Handles the my-list command, listing the account's saved titles and videos
+///
+/// With `download_new`, every saved title is checked for full episodes published since the
+/// previous run (tracked in the download directory) that aren't already in the library.
+/// The first run of a title only records a baseline.
+///
+/// # Arguments
+/// * `download_new` - Whether to download new episodes of the saved titles
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_my_list_command(download_new: bool, config: &AppConfig) -> Result<()> {
+    config.require_auth("my-list")?;
+    output::info!("Fetching My List");
+
+    let per_page = 50;
+    let max_pages = 20; // Safety cap
+    let mut titles = Vec::new();
+    let mut videos = Vec::new();
+    let mut page = 1;
+    loop {
+        let list = config.client.fetch_my_list(page, per_page).await?;
+        let mut has_next_page = false;
+        if let Some(result) = list.titles {
+            has_next_page |= result.has_next_page;
+            titles.extend(result.resources);
+        }
+        if let Some(result) = list.videos {
+            has_next_page |= result.has_next_page;
+            videos.extend(result.resources);
+        }
+        if !has_next_page || page >= max_pages {
+            break;
+        }
+        page += 1;
+    }
+
+    let formatted = if config.wants_rows() {
+        let groups = vec![
+            ("title".to_string(), serde_json::to_value(&titles)?),
+            ("video".to_string(), serde_json::to_value(&videos)?),
+        ];
+        config.format_output(&utils::tagged_rows("result_type", groups))?
+    } else {
+        config.format_output(&serde_json::json!({ "titles": titles, "videos": videos }))?
+    };
+    if let Some(output) = formatted {
+        println!("{}", output);
+    } else {
+        // Compact output
+        println!("Titles ({}):", titles.len());
+        for title in &titles {
+            println!(
+                "  ID: {}, Title: {}, Type: {}",
+                title.id,
+                title.headline.as_deref().unwrap_or("N/A"),
+                title.title_type.as_deref().unwrap_or("N/A")
+            );
+        }
+        println!("Videos ({}):", videos.len());
+        for video in &videos {
+            println!(
+                "  ID: {}, Title: {}, Program: {}",
+                video.id,
+                video.headline.as_deref().unwrap_or("N/A"),
+                video.title.as_ref().and_then(|t| t.headline.as_deref()).unwrap_or("N/A")
+            );
+        }
+    }
+
+    if !download_new {
+        return Ok(());
+    }
+
+    let mut sync_state = SyncState::load(&config.state_dir)?;
+    let library = Library::load(&config.download_dir)?;
+    let mut batch_items = Vec::new();
+    let mut title_of_video: HashMap<VideoId, TitleId> = HashMap::new();
+    let mut checked_titles = Vec::new();
+
+    for title in &titles {
+        match new_episodes_since_last_check(&title.id, title.headline.as_deref(), &sync_state, &library, config).await {
+            Ok(items) => {
+                for item in items.into_iter().flatten() {
+                    if title_of_video.contains_key(&item.video_id) {
+                        continue;
+                    }
+                    title_of_video.insert(item.video_id.clone(), title.id.clone());
+                    batch_items.push(item);
+                }
+                checked_titles.push(title.id.clone());
+            }
+            Err(e) => output::error!("Failed to check {} for new episodes: {}", title.id, e),
+        }
+    }
+
+    let report = download_batch(batch_items, config).await?;
+
+    // Titles with failed downloads keep their previous check date so the episodes are retried
+    for item in &report.items {
+        if matches!(item.status, ItemStatus::Failed { .. }) {
+            if let Some(title_id) = title_of_video.get(item.video_id.as_str()) {
+                checked_titles.retain(|t| t != title_id);
+            }
+        }
+    }
+    for title_id in &checked_titles {
+        sync_state.mark_checked(title_id);
+    }
+    sync_state.save()?;
+    Ok(report.ensure_complete()?)
+}
+
+/// Handles the watch command, polling a title and downloading new episodes as they are published
+///
+/// Shares the sync state and library with `my-list --download-new`: the first check only records a
+/// baseline, and a round with failed downloads keeps the previous check date so they are retried.
+///
+/// # Arguments
+/// * `title_id` - The title to watch
+/// * `interval` - Time between checks, e.g. 30m or 1h
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Ok when interrupted with Ctrl-C, or an error if the interval is invalid
+pub async fn handle_watch_command(title_id: TitleId, interval: String, config: &AppConfig) -> Result<()> {
+    let interval_duration = utils::parse_duration(&interval)?;
+    output::info!("Watching {} for new episodes every {} (Ctrl-C to stop)", title_id, interval);
+    loop {
+        if let Err(e) = check_watched_title(&title_id, config).await {
+            output::error!("Failed to check {} for new episodes: {:#}", title_id, e);
+        }
+        output::info!("Next check in {}", interval);
+        tokio::select! {
+            _ = tokio::time::sleep(interval_duration) => {}
+            _ = tokio::signal::ctrl_c() => {
+                output::info!("Stopped watching {}", title_id);
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// One round of the watch command: downloads the title's new episodes and records the check
+async fn check_watched_title(title_id: &TitleId, config: &AppConfig) -> Result<()> {
+    let mut sync_state = SyncState::load(&config.state_dir)?;
+    let library = Library::load(&config.download_dir)?;
+    output::info!("Checking {} for new episodes", title_id);
+    let items = new_episodes_since_last_check(title_id, None, &sync_state, &library, config).await?;
+    for item in items.iter().flatten() {
+        let event = webhook::WebhookEvent::new(webhook::EventKind::NewEpisodeFound, &item.video_id, item.headline.as_deref())
+            .with_title_id(title_id);
+        webhook::send(&event, config).await;
+    }
+    if let Some(items) = items {
+        if items.is_empty() {
+            output::info!("No new episodes");
+        } else if download_batch(items, config).await?.failed > 0 {
+            // Keep the previous check date so the failed episodes are retried next round
+            return Ok(());
+        }
+    }
+    sync_state.mark_checked(title_id);
+    Ok(sync_state.save()?)
+}
+
+/// Prints a timestamped daemon log line
+fn daemon_log(message: &str) {
+    let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+    output::print_human(format_args!("{} {}", output::paint(format!("[{}]", timestamp), output::Color::Dim), message));
+}
+
+/// Resolves when the process is asked to stop (Ctrl-C, or SIGTERM from a service manager)
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(signal) => signal,
+            Err(_) => return std::future::pending().await,
+        };
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Handles the daemon command, running the config file's jobs on their cron schedules
+///
+/// Each job runs in the background when due; a run is skipped if the job's previous run
+/// (e.g. a long recording) is still going.
+///
+/// # Arguments
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Ok when stopped by a signal or when no job has an upcoming run, or an error if the jobs can't be loaded
+pub async fn handle_daemon_command(config: &AppConfig) -> Result<()> {
+    let path = &config.config_path;
+    if config.jobs.is_empty() {
+        anyhow::bail!("No [[jobs]] configured in {}", path.display());
+    }
+    let jobs = config
+        .jobs
+        .iter()
+        .map(|job| Ok((job.parse_schedule()?, Arc::new(job.clone()))))
+        .collect::<Result<Vec<_>>>()?;
+
+    let config = Arc::new(config.clone());
+    let mut next_runs: Vec<_> = jobs.iter().map(|(schedule, _)| schedule.upcoming(chrono::Local).next()).collect();
+    let mut running: Vec<Option<tokio::task::JoinHandle<()>>> = jobs.iter().map(|_| None).collect();
+    daemon_log(&format!("Daemon started with {} jobs from {}", jobs.len(), path.display()));
+    for ((_, job), next_run) in jobs.iter().zip(&next_runs) {
+        match next_run {
+            Some(time) => daemon_log(&format!("Job {}: next run at {}", job.display_name(), time.format("%Y-%m-%d %H:%M:%S"))),
+            None => daemon_log(&format!("Job {}: no upcoming runs", job.display_name())),
+        }
+    }
+
+    loop {
+        let Some(wake_at) = next_runs.iter().flatten().min().copied() else {
+            daemon_log("No upcoming job runs; exiting");
+            futures_util::future::join_all(running.into_iter().flatten()).await;
+            return Ok(());
+        };
+        let delay = (wake_at - chrono::Local::now()).to_std().unwrap_or_default();
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = shutdown_signal() => {
+                daemon_log("Stopping daemon");
+                stop_daemon_jobs(running, &config).await;
+                return Ok(());
+            }
+        }
+
+        let now = chrono::Local::now();
+        for (index, (schedule, job)) in jobs.iter().enumerate() {
+            if next_runs[index].is_none_or(|time| time > now) {
+                continue;
+            }
+            next_runs[index] = schedule.after(&now).next();
+            if running[index].as_ref().is_some_and(|handle| !handle.is_finished()) {
+                daemon_log(&format!("Job {}: previous run still in progress, skipping", job.display_name()));
+                continue;
+            }
+            let (job, config) = (Arc::clone(job), Arc::clone(&config));
+            running[index] = Some(tokio::spawn(async move { run_daemon_job(&job, &config).await }));
+        }
+    }
+}
+
+/// Cancels the running daemon jobs and waits for them to stop, so recordings are finalized and
+/// no ffmpeg is left behind
+async fn stop_daemon_jobs(running: Vec<Option<tokio::task::JoinHandle<()>>>, config: &AppConfig) {
+    let handles: Vec<_> = running.into_iter().flatten().filter(|handle| !handle.is_finished()).collect();
+    if handles.is_empty() {
+        return;
+    }
+    config.cancel.cancel();
+    daemon_log(&format!("Waiting for {} running jobs to stop", handles.len()));
+    let timeout = std::time::Duration::from_secs(constants::DAEMON_SHUTDOWN_TIMEOUT_SECS);
+    if tokio::time::timeout(timeout, futures_util::future::join_all(handles)).await.is_err() {
+        daemon_log(&format!("Jobs still running after {}s; exiting anyway", timeout.as_secs()));
+    }
+}
+
+/// Runs one daemon job and logs its outcome
+async fn run_daemon_job(job: &config::JobConfig, config: &AppConfig) {
+    daemon_log(&format!("Job {}: started", job.display_name()));
+    let result = match &job.action {
+        config::JobAction::Sync { title_id } => check_watched_title(title_id, config).await,
+        config::JobAction::Record { channel, duration, filename } => {
+            handle_live_command(channel.clone(), true, Some(duration.clone()), filename.clone(), config).await
+        }
+    };
+    match result {
+        Ok(()) => daemon_log(&format!("Job {}: finished", job.display_name())),
+        Err(e) => {
+            daemon_log(&format!("Job {}: failed", job.display_name()));
+            output::error!("Job {} failed: {:#}", job.display_name(), e);
+        }
+    }
+}
+
+/// Handles the history command, listing recorded downloads
+///
+/// # Arguments
+/// * `search` - Only entries whose video ID or title contains this text (case-insensitive)
+/// * `status` - Only entries with this result (downloaded, skipped or failed)
+/// * `since` - Only entries recorded on this day or later, in the time zone they were recorded in
+/// * `limit` - Show at most this many of the most recent entries
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_history_command(
+    search: Option<String>,
+    status: Option<String>,
+    since: Option<Day>,
+    limit: usize,
+    config: &AppConfig,
+) -> Result<()> {
+    let search = search.map(|s| s.to_lowercase());
+    let mut entries: Vec<HistoryEntry> = library::load_history(&config.state_dir)?
+        .into_iter()
+        .filter(|entry| {
+            search.as_deref().is_none_or(|text| {
+                entry.video_id.to_lowercase().contains(text)
+                    || entry.title.as_deref().is_some_and(|t| t.to_lowercase().contains(text))
+            })
+        })
+        .filter(|entry| {
+            status.as_deref().is_none_or(|wanted| match &entry.status {
+                ItemStatus::Downloaded => wanted == "downloaded",
+                ItemStatus::Skipped { .. } => wanted == "skipped",
+                ItemStatus::Failed { .. } => wanted == "failed",
+                ItemStatus::Fetched => false,
+            })
+        })
+        .filter(|entry| {
+            since.is_none_or(|day| {
+                dates::parse_timestamp(&entry.timestamp).is_some_and(|timestamp| timestamp.date_naive() >= day.0)
+            })
+        })
+        .collect();
+    if entries.len() > limit {
+        entries.drain(..entries.len() - limit);
+    }
+
+    if let Some(output) = config.format_output(&entries)? {
+        println!("{}", output);
+    } else {
+        // Compact output
+        if entries.is_empty() {
+            println!("No downloads recorded in {}", config.state_dir.join(library::HISTORY_FILE_NAME).display());
+        }
+        for entry in &entries {
+            let outcome = match &entry.status {
+                ItemStatus::Downloaded => "downloaded".to_string(),
+                ItemStatus::Skipped { reason } => format!("skipped ({})", reason),
+                ItemStatus::Failed { error } => format!("failed ({})", error.lines().next().unwrap_or_default()),
+                ItemStatus::Fetched => "fetched".to_string(),
+            };
+            println!(
+                "{}  {}  {}  {}",
+                entry.timestamp.get(..19).unwrap_or(&entry.timestamp).replace('T', " "),
+                entry.video_id,
+                entry.title.as_deref().unwrap_or("N/A"),
+                outcome
+            );
+            if let Some(path) = &entry.path {
+                println!("    {}", path.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Formats an EPG slot as "Name (start-end)" for compact output
+fn format_slot(slot: &models::EpgSlot) -> String {
+    // Keep only the HH:MM part of timestamps like "2024-01-05T20:30:00-03:00"
+    let time = |t: &Option<String>| {
+        t.as_deref()
+            .and_then(|t| t.get(11..16))
+            .unwrap_or("??:??")
+            .to_string()
+    };
+    format!(
+        "{} ({}-{})",
+        slot.name
+            .as_deref()
+            .or(slot.title.as_ref().and_then(|t| t.headline.as_deref()))
+            .unwrap_or("N/A"),
+        time(&slot.start_time),
+        time(&slot.end_time)
+    )
+}
+
+/// Handles the channels command, listing live channels and what they are airing
+///
+/// # Arguments
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_channels_command(config: &AppConfig) -> Result<()> {
+    output::info!("Fetching live channels");
+    let broadcasts = config.client.fetch_broadcasts().await?;
+
+    if let Some(output) = config.format_output(&broadcasts)? {
+        println!("{}", output);
+    } else {
+        // Compact output
+        println!("Found {} channels:", broadcasts.len());
+        for broadcast in &broadcasts {
+            println!(
+                "  ID: {}, Name: {}, Media ID: {}{}",
+                broadcast.channel.slug.as_deref().unwrap_or(&broadcast.channel.id),
+                broadcast.channel.name.as_deref().unwrap_or("N/A"),
+                broadcast.media_id.as_deref().unwrap_or("N/A"),
+                broadcast
+                    .affiliate_code
+                    .as_deref()
+                    .map(|code| format!(", Affiliate: {}", code))
+                    .unwrap_or_default()
+            );
+            let mut slots = broadcast.epg_current_slots.iter();
+            if let Some(now) = slots.next() {
+                println!("      Now:  {}", format_slot(now));
+            }
+            if let Some(next) = slots.next() {
+                println!("      Next: {}", format_slot(next));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Resolves a channel ID/slug (or live media ID) to its live media ID and broadcast details
+///
+/// # Returns
+/// The media ID, plus the broadcast when the channel is listed by the API
+async fn resolve_broadcast(channel_id: &str, config: &AppConfig) -> Result<(VideoId, Option<models::Broadcast>)> {
+    let broadcasts = config.client.fetch_broadcasts().await?;
+    let mut candidates: Vec<models::Broadcast> = broadcasts.into_iter().filter(|b| b.matches(channel_id)).collect();
+    // Channels with regional feeds list one broadcast per affiliate; prefer the --region one
+    let regional = config
+        .client
+        .region()
+        .and_then(|region| candidates.iter().position(|b| b.is_affiliate(region)));
+    if let (Some(region), None, true) = (config.client.region(), regional, candidates.len() > 1) {
+        output::warning!("no {} feed for {}; using the default affiliate", region, channel_id);
+    }
+    let broadcast = match regional {
+        Some(index) => Some(candidates.swap_remove(index)),
+        None if candidates.is_empty() => None,
+        None if config.interactive && candidates.len() > 1 => {
+            let labels: Vec<String> = candidates
+                .iter()
+                .map(|b| {
+                    format!(
+                        "{} - affiliate {} (media {})",
+                        b.channel.name.as_deref().unwrap_or(&b.channel.id),
+                        b.affiliate_code.as_deref().unwrap_or("N/A"),
+                        b.media_id.as_deref().unwrap_or("N/A")
+                    )
+                })
+                .collect();
+            let index = picker::pick(&format!("{} ({})", Msg::ChooseFeed.text(), channel_id), &labels)?;
+            Some(candidates.swap_remove(index))
+        }
+        None => Some(candidates.swap_remove(0)),
+    };
+    match broadcast.as_ref().and_then(|b| b.media_id.clone()) {
+        Some(media_id) => Ok((media_id, broadcast)),
+        // Unknown channels are assumed to be live media IDs
+        None => match channel_id.parse() {
+            Ok(media_id) => Ok((media_id, broadcast)),
+            Err(_) => Err(anyhow::anyhow!("Unknown live channel: {} (see the channels command)", channel_id)),
+        },
+    }
+}
+
+/// Handles the schedule command, listing a channel's program guide for a day
+///
+/// # Arguments
+/// * `channel_id` - Channel ID/slug from `channels`, or a live media ID
+/// * `date` - Optional day, defaults to today
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_schedule_command(channel_id: String, date: Option<Day>, config: &AppConfig) -> Result<()> {
+    let date = date.unwrap_or_else(Day::today).to_string();
+    let (media_id, broadcast) = resolve_broadcast(&channel_id, config).await?;
+    let channel_name = broadcast
+        .and_then(|b| b.channel.name)
+        .unwrap_or_else(|| channel_id.clone());
+
+    output::info!("Fetching schedule for {} on {}", channel_name, date);
+    let slots = config.client.fetch_epg(&media_id, &date).await?;
+
+    if let Some(output) = config.format_output(&slots)? {
+        println!("{}", output);
+    } else {
+        // Compact output
+        println!("{} programs:", slots.len());
+        for slot in &slots {
+            match slot.title.as_ref().and_then(|t| t.title_id.as_deref()) {
+                Some(title_id) => println!("  {} [title ID: {}]", format_slot(slot), title_id),
+                None => println!("  {}", format_slot(slot)),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Handles the live command, showing a channel's live streams or recording them
+///
+/// # Arguments
+/// * `channel_id` - Channel ID/slug from `channels`, or a live media ID
+/// * `record` - Whether to record the live stream
+/// * `duration` - Recording length (e.g. "1h30m"), required with `record`
+/// * `custom_filename` - Optional custom filename for the recording
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_live_command(
+    channel_id: String,
+    record: bool,
+    duration: Option<String>,
+    custom_filename: Option<String>,
+    config: &AppConfig,
+) -> Result<()> {
+    let (media_id, broadcast) = resolve_broadcast(&channel_id, config).await?;
+    let broadcast = broadcast.as_ref();
+    let channel_name = broadcast
+        .and_then(|b| b.channel.name.clone())
+        .unwrap_or_else(|| channel_id.clone());
+
+    output::info!("Opening live session for {} (media ID {})", channel_name, media_id);
+    let session = config.client.fetch_video_session(&media_id).await?;
+
+    if !record {
+        if let Some(output) = config.format_output(&session)? {
+            println!("{}", output);
+        } else {
+            println!("Live: {}", channel_name);
+            if let Some(now) = broadcast.and_then(|b| b.epg_current_slots.first()) {
+                println!("Now: {}", format_slot(now));
+            }
+            println!("Available Streams:");
+            for source in &session.sources {
+                println!("  - Label: {}, URL: {}", source.label.as_deref().unwrap_or("N/A"), source.url);
+            }
+        }
+        return Ok(());
+    }
+
+    let duration = utils::parse_duration(duration.as_deref().unwrap_or_default())?;
+    let stream_source = select_best_stream(&session.sources, config.client.quality(), &HashMap::new())
+        .ok_or_else(|| anyhow::anyhow!("No live stream available for {}", channel_name))?;
+    let filename = custom_filename.unwrap_or_else(|| {
+        let name = format!("{}_{}", channel_name, chrono::Local::now().format("%Y%m%d_%H%M"));
+        filename::sanitize_filename(&name, Some("mp4"), config.filename_spaces)
+    });
+    let recording_path = filename::fit_path(&config.download_dir.join(filename), constants::MAX_PATH_LEN);
+
+    let mut report = ItemReport::new(&media_id, false);
+    let recorded = download::record_live(&media_id, stream_source, &recording_path, duration, config, &mut report).await;
+    for warning in &report.warnings {
+        output::warning!("{}", warning);
+    }
+    recorded?;
+    output::info!("Recording complete: {}", recording_path.display());
+    Ok(())
+}
+
+/// Handles the trending command, listing the home-page highlight rails
+///
+/// # Arguments
+/// * `rail_filter` - Optional case-insensitive text the rail name must contain
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_trending_command(rail_filter: Option<String>, config: &AppConfig) -> Result<()> {
+    output::info!("Fetching home-page highlights");
+    let mut rails = config.client.fetch_highlights().await?;
+    if let Some(filter) = rail_filter {
+        let filter = filter.to_lowercase();
+        rails.retain(|rail| {
+            rail.name
+                .as_deref()
+                .is_some_and(|name| name.to_lowercase().contains(&filter))
+        });
+    }
+
+    if let Some(output) = config.format_output(&rails)? {
+        println!("{}", output);
+    } else {
+        // Compact output
+        for rail in &rails {
+            println!("{} ({} items):", rail.name.as_deref().unwrap_or(&rail.id), rail.items.len());
+            for item in &rail.items {
+                println!(
+                    "  ID: {}, Title: {}, Type: {}",
+                    item.id,
+                    item.headline.as_deref().unwrap_or("N/A"),
+                    item.item_type.as_deref().unwrap_or("N/A")
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Handles the graphql command, sending an arbitrary operation and printing the raw response
+///
+/// # Arguments
+/// * `operation` - The GraphQL operation name
+/// * `variables` - Variables as inline JSON or `@file`
+/// * `hash` - Persisted-query hash, when sending a persisted query
+/// * `query` - Full query text or `@file`, when sending a document
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_graphql_command(
+    operation: String,
+    variables: String,
+    hash: Option<String>,
+    query: Option<String>,
+    config: &AppConfig,
+) -> Result<()> {
+    let variables: serde_json::Value =
+        serde_json::from_str(&utils::read_inline_or_file(&variables)?).context("Variables are not valid JSON")?;
+    let document = query.as_deref().map(utils::read_inline_or_file).transpose()?;
+    let query = match (&hash, &document) {
+        (Some(hash), _) => api::GraphQlQuery::Persisted(hash),
+        (None, Some(document)) => api::GraphQlQuery::Document(document),
+        (None, None) => anyhow::bail!("Either --hash or --query is required"),
+    };
+
+    let response = config.client.graphql_raw_request(&operation, query, variables).await?;
+    if config.output_format == "pretty" {
+        println!("{}", serde_json::to_string_pretty(&response)?);
+    } else {
+        println!("{}", serde_json::to_string(&response)?);
+    }
+    Ok(())
+}
+
+/// Handles the browse command, listing categories or the titles of one category
+///
+/// # Arguments
+/// * `category` - Optional category slug; lists all categories when `None`
+/// * `page` - Page number of titles
+/// * `per_page` - Titles per page
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_browse_command(category: Option<String>, page: u32, per_page: u32, config: &AppConfig) -> Result<()> {
+    let Some(category) = category else {
+        output::info!("Fetching categories");
+        let categories = config.client.fetch_categories().await?;
+        if let Some(output) = config.format_output(&categories)? {
+            println!("{}", output);
+        } else {
+            println!("Found {} categories:", categories.len());
+            for category in &categories {
+                println!(
+                    "  {} ({})",
+                    category.slug.as_deref().unwrap_or(&category.id),
+                    category.name.as_deref().unwrap_or("N/A")
+                );
+            }
+        }
+        return Ok(());
+    };
+
+    output::info!("Fetching titles in category {} (page {}, per_page {})", category, page, per_page);
+    let mut titles = config.client.fetch_category_titles(&category, page, per_page).await?;
+    titles.resources.retain(|t| config.passes_kids_filter(t.content_rating.as_deref()));
+    if let Some(output) = config.format_output(&titles)? {
+        println!("{}", output);
+    } else {
+        // Compact output
+        println!("Found {} titles:", titles.resources.len());
+        for title in &titles.resources {
+            println!(
+                "  ID: {}, Title: {}, Type: {}",
+                title.id,
+                title.headline.as_deref().unwrap_or("N/A"),
+                title.title_type.as_deref().unwrap_or("N/A")
+            );
+        }
+        if titles.has_next_page {
+            output::info!("More titles available: use --page {}", titles.next_page.unwrap_or(page + 1));
+        }
+    }
+    Ok(())
+}
+
+/// Handles the snapshot command, writing each title's video metadata to a dated JSON file
+///
+/// Files are written to `<snapshot_dir>/<title_id>/<YYYY-MM-DD>.json`, so running the command
+/// nightly builds a record of what was published when.
+///
+/// # Arguments
+/// * `title_ids` - The titles/programs to snapshot; when empty, the config file's `snapshot` list or else
+///   the titles of its sync jobs
+/// * `from_date_opt` - Optional start date, defaults to January 1 of each title's release year (or of
+///   `constants::CATALOG_START_YEAR` when it is unknown), so the whole catalog is recorded
+/// * `to_date_opt` - Optional end date, defaults to today
+/// * `snapshot_dir_opt` - Optional directory for snapshot files
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_snapshot_command(
+    title_ids: Vec<TitleId>,
+    from_date_opt: Option<Day>,
+    to_date_opt: Option<Day>,
+    snapshot_dir_opt: Option<String>,
+    config: &AppConfig,
+) -> Result<()> {
+    let title_ids = if !title_ids.is_empty() {
+        title_ids
+    } else if !config.snapshot_titles.is_empty() {
+        config.snapshot_titles.clone()
+    } else {
+        let mut synced: Vec<TitleId> = Vec::new();
+        for job in &config.jobs {
+            if let config::JobAction::Sync { title_id } = &job.action {
+                if !synced.contains(title_id) {
+                    synced.push(title_id.clone());
+                }
+            }
+        }
+        synced
+    };
+    if title_ids.is_empty() {
+        return Err(Error::Invalid(
+            "No titles to snapshot: give title IDs, or list them under `snapshot` (or as sync jobs) in the config file".to_string(),
+        )
+        .into());
+    }
+    let today = Day::today();
+    let to = to_date_opt.unwrap_or(today);
+    if let Some(from) = from_date_opt {
+        Day::check_range(from, to)?;
+    }
+    let to_date = to.to_string();
+    let snapshot_dir = snapshot_dir_opt
+        .map(|d| PathBuf::from(shellexpand::tilde(&d).into_owned()))
+        .unwrap_or_else(|| config.download_dir.join("snapshots"));
+
+    let per_page = 100;
+    let mut failures = 0;
+
+    for title_id in &title_ids {
+        let from = match from_date_opt {
+            Some(from) => from,
+            None => {
+                let year = match config.client.fetch_title(title_id.as_str()).await {
+                    Ok(title) => title.release_year.map_or(constants::CATALOG_START_YEAR, |year| year as i32),
+                    Err(e) => {
+                        output::warning!("Release year of {} unknown ({}), starting at {}", title_id, e, constants::CATALOG_START_YEAR);
+                        constants::CATALOG_START_YEAR
+                    }
+                };
+                // A release year after the end date leaves just the end date
+                chrono::NaiveDate::from_ymd_opt(year, 1, 1).map_or(to, Day).min(to)
+            }
+        };
+        let from_date = from.to_string();
+        output::info!("Snapshotting title {} from {} to {}", title_id, from_date, to_date);
+        let items = match config.client.fetch_all_videos_by_date(title_id, &from_date, &to_date, 1, per_page).await {
+            Ok(response) => {
+                if response.next.is_some() {
+                    output::warning!(
+                        "snapshot of {} truncated after {} pages",
+                        title_id,
+                        constants::PAGE_FOLLOW_MAX_PAGES
+                    );
+                }
+                response.items
+            }
+            Err(e) => {
+                output::error!("Failed to snapshot title {}: {}", title_id, e);
+                failures += 1;
+                continue;
+            }
+        };
+
+        let snapshot = models::CatalogSnapshot {
+            title_id: title_id.clone(),
+            taken_at: chrono::Local::now().to_rfc3339(),
+            from_date: from_date.clone(),
+            to_date: to_date.clone(),
+            items,
+        };
+        let title_dir = snapshot_dir.join(filename::sanitize_filename(title_id, None, config.filename_spaces));
+        std::fs::create_dir_all(&title_dir)
+            .context(format!("Failed to create snapshot directory: {}", title_dir.display()))?;
+        let snapshot_path = title_dir.join(format!("{}.json", today));
+        std::fs::write(&snapshot_path, serde_json::to_string_pretty(&snapshot)?)
+            .context(format!("Failed to write snapshot: {}", snapshot_path.display()))?;
+        output::info!("Wrote {} items to {}", snapshot.items.len(), snapshot_path.display());
+    }
+
+    if failures > 0 {
+        return Err(Error::Partial { failed: failures, total: title_ids.len(), what: "title snapshots" }.into());
+    }
+    Ok(())
+}
+
+/// Handles the next-episode command, finding the episode that follows a given episode number
+///
+/// # Arguments
+/// * `title_id` - The ID of the title/program to search
+/// * `after` - Episode number to look past; defaults to the latest detected episode
+/// * `days` - How many days back from today to search
+/// * `download` - Whether to download the next episode when found
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_next_episode_command(
+    title_id: TitleId,
+    after: Option<u32>,
+    days: i64,
+    download: bool,
+    config: &AppConfig,
+) -> Result<()> {
+    let today = chrono::Local::now().date_naive();
+    let from_date = (today - chrono::Duration::days(days)).format("%Y-%m-%d").to_string();
+    let to_date = today.format("%Y-%m-%d").to_string();
+
+    output::info!("Searching episodes of {} from {} to {}", title_id, from_date, to_date);
+    let response = config.client.fetch_all_videos_by_date(&title_id, &from_date, &to_date, 1, 100).await?;
+
+    // Pair every item with its episode number, from the GraphQL fields or the headline text
+    let mut numbered: Vec<(u32, &models::DatedVideoItem)> = response
+        .items
+        .iter()
+        .filter_map(|item| {
+            let text = item.headline.as_deref().unwrap_or(&item.title);
+            let (_, detected) = layout::detect_episode_numbers(text);
+            item.episode.or(detected).map(|episode| (episode, item))
+        })
+        .collect();
+    numbered.sort_by_key(|(episode, _)| *episode);
+
+    let Some((latest, _)) = numbered.last() else {
+        output::info!("No numbered episodes found for {} in the last {} days.", title_id, days);
+        return Ok(());
+    };
+    let after = after.unwrap_or(*latest);
+
+    match numbered.iter().map(|(episode, _)| *episode).find(|episode| *episode > after) {
+        Some(episode) => {
+            // Full episodes and their clips can carry the same number
+            let candidates: Vec<&models::DatedVideoItem> = numbered
+                .iter()
+                .filter(|(number, _)| *number == episode)
+                .map(|(_, item)| *item)
+                .collect();
+            let item = if config.interactive && candidates.len() > 1 {
+                let labels: Vec<String> = candidates
+                    .iter()
+                    .map(|item| {
+                        format!(
+                            "{} ({}, {})",
+                            item.headline.as_deref().unwrap_or(&item.title),
+                            item.duration_formatted.as_deref().unwrap_or("N/A"),
+                            item.date_formated.as_deref().unwrap_or("N/A")
+                        )
+                    })
+                    .collect();
+                candidates[picker::pick(&format!("{} ({})", Msg::ChooseEpisode.text(), episode), &labels)?]
+            } else {
+                candidates[0]
+            };
+            let video_id = item.resource_id.as_ref().unwrap_or(&item.id);
+            if let Some(output) = config.format_output(item)? {
+                println!("{}", output);
+            } else {
+                println!(
+                    "Next episode after {}: episode {} - {} (ID: {}, Date: {})",
+                    after,
+                    episode,
+                    item.headline.as_deref().unwrap_or(&item.title),
+                    video_id,
+                    item.date_formated.as_deref().unwrap_or("N/A")
+                );
+            }
+            if download {
+                let mut report = ItemReport::new(video_id, config.timings);
+                download::process_video(video_id.clone(), true, None, None, None, config, false, &mut report).await?;
+                ensure_description_sidecar(&mut report, item.summary.as_deref(), config);
+                report.print(&config.output_format)?;
+            }
+        }
+        None => output::info!(
+            "Episode {} is not published yet (latest found: episode {}).",
+            after.saturating_add(1),
+            latest
+        ),
+    }
+    Ok(())
+}
+
+/// Finds the most recently published full episodes of a title
+///
+/// Searches backwards over widening date windows (a week, a month, three months, a year)
+/// until enough episodes are found, so callers don't have to pick a date range.
+///
+/// # Returns
+/// Up to `count` episodes, newest first
+async fn fetch_latest_episodes(title_id: &TitleId, count: usize, config: &AppConfig) -> Result<Vec<models::DatedVideoItem>> {
+    let today = chrono::Local::now().date_naive();
+    let to_date = today.format("%Y-%m-%d").to_string();
+    let mut episodes = Vec::new();
+    for days in [7, 30, 90, 365] {
+        let from_date = (today - chrono::Duration::days(days)).format("%Y-%m-%d").to_string();
+        let response = config.client.fetch_all_videos_by_date(title_id, &from_date, &to_date, 1, 100).await?;
+        episodes = response.items.into_iter().filter(|item| !item.is_excerpt()).collect();
+        if episodes.len() >= count {
+            break;
+        }
+    }
+    // Newest first; items without a parseable date keep their API order at the end
+    episodes.sort_by_key(|item| std::cmp::Reverse(item.published_date()));
+    episodes.truncate(count);
+    Ok(episodes)
+}
+
+/// Handles the latest command, listing a title's most recent full episodes
+///
+/// # Arguments
+/// * `title_id` - The ID of the title/program
+/// * `count` - Number of episodes to list
+/// * `download` - Whether to download the listed episodes
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_latest_command(title_id: TitleId, count: usize, download: bool, config: &AppConfig) -> Result<()> {
+    output::info!("Fetching the latest {} episode(s) of {}", count, title_id);
+    let episodes = fetch_latest_episodes(&title_id, count, config).await?;
+
+    if let Some(output) = config.format_output(&episodes)? {
+        println!("{}", output);
+    } else {
+        // Compact output
+        if episodes.is_empty() {
+            println!("No episodes of {} published in the last year.", title_id);
+        }
+        for item in &episodes {
+            println!(
+                "  ID: {}, Title: {}, Date: {}",
+                item.resource_id.as_ref().unwrap_or(&item.id),
+                item.headline.as_deref().unwrap_or(&item.title),
+                item.date_formated.as_deref().unwrap_or("N/A")
+            );
+        }
+    }
+
+    if download {
+        let batch_items = episodes
+            .iter()
+            .map(|item| BatchItem {
+                video_id: item.resource_id.clone().unwrap_or_else(|| item.id.clone()),
+                headline: item.headline.clone(),
+                summary: item.summary.clone(),
+            })
+            .collect();
+        download_batch(batch_items, config).await?.ensure_complete()?;
+    }
+    Ok(())
+}
+
+/// Handles the news command, listing each journalism program's bulletins for a day
+///
+/// # Arguments
+/// * `programs` - Program IDs or slugs; the default bulletins from `constants::NEWS_PROGRAMS` when empty
+/// * `date` - Optional day, defaults to today
+/// * `download_latest` - Whether to download the latest bulletin of each program
+/// * `config` - The application configuration
+///
+/// # Returns
+/// Result indicating success or error
+pub async fn handle_news_command(
+    programs: Vec<String>,
+    date: Option<Day>,
+    download_latest: bool,
+    config: &AppConfig,
+) -> Result<()> {
+    let date = date.unwrap_or_else(Day::today).to_string();
+    let programs = if programs.is_empty() {
+        constants::NEWS_PROGRAMS.iter().map(|p| p.to_string()).collect()
+    } else {
+        programs
+    };
+
+    output::info!("Fetching news bulletins for {}", date);
+    let mut bulletins = Vec::new();
+    let mut batch_items = Vec::new();
+    let mut failures = 0;
+    for program in &programs {
+        // Slugs are resolved to title IDs, which the videos-by-date operation needs
+        let parsed = if program.contains('-') { None } else { program.parse::<TitleId>().ok() };
+        let (title_id, name) = match parsed {
+            Some(title_id) => (title_id, program.clone()),
+            None => match config.client.fetch_title(program).await {
+                Ok(title) => (title.title_id, title.headline.unwrap_or_else(|| program.clone())),
+                Err(e) => {
+                    output::error!("Failed to resolve program {}: {}", program, e);
+                    failures += 1;
+                    continue;
+                }
+            },
+        };
+
+        let items: Vec<models::DatedVideoItem> =
+            match config.client.fetch_all_videos_by_date(&title_id, &date, &date, 1, 100).await {
+                Ok(response) => response.items.into_iter().filter(|item| !item.is_excerpt()).collect(),
+                Err(e) => {
+                    output::error!("Failed to fetch bulletins of {}: {}", name, e);
+                    failures += 1;
+                    continue;
+                }
+            };
+
+        // Listings are newest first
+        if let (true, Some(latest)) = (download_latest, items.first()) {
+            batch_items.push(BatchItem {
+                video_id: latest.resource_id.clone().unwrap_or_else(|| latest.id.clone()),
+                headline: latest.headline.clone(),
+                summary: latest.summary.clone(),
+            });
+        }
+        bulletins.push((name, title_id, items));
+    }
+
+    let as_json = || -> Vec<serde_json::Value> {
+        bulletins
+            .iter()
+            .map(|(name, title_id, items)| serde_json::json!({ "program": name, "title_id": title_id, "items": items }))
+            .collect()
+    };
+    let formatted = if config.wants_rows() {
+        // One row per bulletin, tagged with its program
+        let groups = bulletins
+            .iter()
+            .map(|(name, _, items)| Ok((name.clone(), serde_json::to_value(items)?)))
+            .collect::<Result<Vec<_>>>()?;
+        config.format_output(&utils::tagged_rows("program", groups))?
+    } else {
+        config.format_output(&as_json())?
+    };
+    if let Some(output) = formatted {
+        println!("{}", output);
+    } else {
+        // Compact output
+        for (name, _, items) in &bulletins {
+            println!("{} ({} bulletins):", name, items.len());
+            for item in items {
+                println!(
+                    "  ID: {}, Title: {}",
+                    item.resource_id.as_ref().unwrap_or(&item.id),
+                    item.headline.as_deref().unwrap_or(&item.title)
+                );
+            }
+        }
+    }
+
+    let downloads = if download_latest { Some(download_batch(batch_items, config).await?) } else { None };
+    if failures > 0 {
+        return Err(Error::Partial { failed: failures, total: programs.len(), what: "news programs" }.into());
+    }
+    if let Some(report) = downloads {
+        report.ensure_complete()?;
+    }
+    Ok(())
+}
+
+/// Runs the interactive browser, then downloads the episodes queued in it
+///
+/// # Arguments
+/// * `config` - The application configuration
+pub async fn handle_tui_command(config: &AppConfig) -> Result<()> {
+    let queue = tui::run(config).await?;
+    if queue.is_empty() {
+        return Ok(());
+    }
+    Ok(download_batch(queue, config).await?.ensure_complete()?)
+}
+
+/// Handles the config command: scaffolds, prints, changes or opens the config file
+///
+/// # Arguments
+/// * `action` - What to do with the config file
+/// * `cli` - The parsed command line, for `--config` and the flags merged into `config get`
+pub async fn handle_config_command(action: &ConfigAction, cli: &Cli) -> Result<()> {
+    let path = config::config_path(cli);
+    match action {
+        ConfigAction::Init { force } => {
+            config::write_default_config(&path, *force)?;
+            println!("Wrote {}", path.display());
+        }
+        ConfigAction::Get { key } => {
+            let settings = AppConfig::from_cli(cli).await?.effective_settings();
+            match key {
+                None => print!("{}", toml::to_string(&settings).context("Failed to serialize settings")?),
+                Some(key) => {
+                    let table = toml::Value::try_from(&settings).context("Failed to serialize settings")?;
+                    let value = key
+                        .split('.')
+                        .try_fold(&table, |value, part| value.get(part))
+                        .ok_or_else(|| anyhow::anyhow!("{} is not set", key))?;
+                    match value {
+                        toml::Value::String(text) => println!("{}", text),
+                        other => println!("{}", other),
+                    }
+                }
+            }
+        }
+        ConfigAction::Set { key, value } => {
+            config::set_config_value(&path, cli.profile.as_deref(), key, value)?;
+            match &cli.profile {
+                Some(profile) => output::info!("Set {} of profile {} in {}", key, profile, path.display()),
+                None => output::info!("Set {} in {}", key, path.display()),
+            }
+        }
+        ConfigAction::Path => println!("{}", path.display()),
+        ConfigAction::Edit => {
+            if !path.exists() {
+                config::write_default_config(&path, false)?;
+            }
+            let editor = ["VISUAL", "EDITOR"]
+                .iter()
+                .filter_map(|name| std::env::var(name).ok())
+                .find(|value| !value.trim().is_empty())
+                .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+            // Editors are often configured with arguments, e.g. "code --wait"
+            let mut words = editor.split_whitespace();
+            let program = words.next().unwrap_or("vi");
+            let status = std::process::Command::new(program)
+                .args(words)
+                .arg(&path)
+                .status()
+                .context(format!("Failed to start editor '{}'", editor))?;
+            if !status.success() {
+                anyhow::bail!("Editor '{}' exited with {}", editor, status);
+            }
+            config::load_config_from_file(&path)?;
+            for diagnostic in config_validate::validate_file(&path)? {
+                output::warning!("{}: {}", path.display(), diagnostic);
+            }
+        }
+        ConfigAction::Validate => {
+            if !path.exists() {
+                anyhow::bail!("Config file not found: {}", path.display());
+            }
+            let diagnostics = config_validate::validate_file(&path)?;
+            if diagnostics.is_empty() {
+                println!("{} is valid", path.display());
+            } else {
+                for diagnostic in &diagnostics {
+                    output::error!("{}: {}", path.display(), diagnostic);
+                }
+                anyhow::bail!("{} problem(s) found in {}", diagnostics.len(), path.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Stores the cookies of a browser-exported cookie file in the secrets file
+///
+/// # Arguments
+/// * `cookie_file` - Netscape-format cookie file to import
+pub fn handle_login_command(cookie_file: &str) -> Result<()> {
+    let source = PathBuf::from(shellexpand::tilde(cookie_file).into_owned());
+    let cookies = std::fs::read_to_string(&source).context(format!("Failed to read cookie file: {}", source.display()))?;
+    let count = client::add_netscape_cookies(&reqwest::cookie::Jar::default(), &cookies)?;
+    if count == 0 {
+        anyhow::bail!("No cookies found in {}; export them in Netscape cookie-file format", source.display());
+    }
+    let path = paths::secrets_file();
+    let mut stored = secrets::load(&path)?.unwrap_or_default();
+    stored.cookies = Some(cookies);
+    secrets::save(&path, &stored)?;
+    output::info!("Stored {} cookie(s) in {}; {} can be deleted now", count, path.display(), source.display());
+    Ok(())
+}
+
+/// Deletes the cookies stored by `login`
+pub fn handle_logout_command() -> Result<()> {
+    let path = paths::secrets_file();
+    if secrets::remove(&path)? {
+        output::info!("Deleted {}", path.display());
+    } else {
+        output::info!("Not logged in: {} does not exist", path.display());
+    }
+    Ok(())
+}
+
+/// Writes a completion script for `shell` to stdout
+///
+/// # Arguments
+/// * `shell` - Shell to generate the script for
+/// * `profiles` - Profile names from the config file, offered as values of `--profile`
+pub fn handle_completions_command(shell: clap_complete::Shell, profiles: Vec<String>) {
+    let mut command = Cli::command();
+    if !profiles.is_empty() {
+        // The generated scripts can't call back into the program, so the names are written into them
+        command = command.mut_arg("profile", |arg| arg.value_parser(clap::builder::PossibleValuesParser::new(profiles)));
+    }
+    let bin_name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, bin_name, &mut std::io::stdout());
+}
@@ -1,126 +1,997 @@
 // src/config.rs
+#[cfg(feature = "cli")]
 use crate::cli::Cli;
-use anyhow::Result;
-use serde::Deserialize;
-use shellexpand;
+use crate::client::GloboClient;
+use crate::config_migrate;
+#[cfg(feature = "cli")]
+use crate::config_validate;
+use crate::constants;
+use crate::error::{Error, Result};
+use crate::hls::VariantCache;
+use crate::ids::TitleId;
+use crate::downloader::{CancellationToken, DownloaderKind};
+use crate::filename::SpaceStyle;
+use crate::layout::LibraryLayout;
+#[cfg(feature = "cli")]
+use crate::library;
+use crate::net::{HostRule, PoolOptions, RetryPolicy};
+#[cfg(feature = "cli")]
+use crate::protocol::VideoSessionRequest;
+#[cfg(feature = "cli")]
+use crate::output;
+use crate::paths;
+#[cfg(feature = "cli")]
+use crate::progress;
+use crate::progress::ProgressSink;
+#[cfg(feature = "cli")]
+use crate::quality::Quality;
+use crate::secrets;
+use crate::utils;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "cli")]
+use clap::CommandFactory;
+use std::collections::HashMap;
+#[cfg(feature = "cli")]
+use std::ffi::OsString;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
-#[derive(Debug, Deserialize, Clone)]
-#[allow(dead_code)]
+/// Contents of the TOML config file; command-line flags and environment variables take precedence
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct ConfigFile {
+    /// Format version (`constants::CONFIG_VERSION`); missing in files written before it existed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    #[serde(flatten)]
+    pub settings: Settings,
+    /// Profile used when no --profile is given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    /// Named sets of settings (`[profiles.NAME]`) that override the top-level ones when selected
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub profiles: HashMap<String, Settings>,
+    /// Scheduled jobs run by the daemon command
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub jobs: Vec<JobConfig>,
+}
+
+/// Settings that can be given at the top level of the config file or in a profile
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Settings {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cookie_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub default_quality: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub default_output_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub default_download_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// URLs that receive webhook events when no --webhook is given
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<String>,
+    /// Titles `snapshot` records when none are given
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub snapshot: Vec<TitleId>,
+    /// Persisted-query hash overrides by operation name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub query_hashes: HashMap<String, String>,
+    /// Filename template for downloads (--output-template)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_template: Option<String>,
+    /// Media-server folder structure for downloads (--library-layout)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub library_layout: Option<LibraryLayout>,
+    /// How spaces in titles are written in file names (--filename-spaces)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename_spaces: Option<SpaceStyle>,
+    /// Download backend (--downloader)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub downloader: Option<DownloaderKind>,
+    /// Seconds to wait for a connection (--connect-timeout)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connect_timeout: Option<u64>,
+    /// Seconds a download may go without receiving data (--read-timeout)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_timeout: Option<u64>,
+    /// Seconds an API request may take in total (--timeout)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Seconds a download may take in total (--download-timeout)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_timeout: Option<u64>,
+    /// PEM bundle of extra root certificates (--ca-cert)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<String>,
+    /// File that receives the debug log of every run (--log-file)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<String>,
+    /// Times a failed request is retried (--retries)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    /// Milliseconds before the first retry (--retry-backoff)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_backoff: Option<u64>,
+    /// Longest wait in seconds between retries (--retry-max-backoff)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_max_backoff: Option<u64>,
+    /// HTTP statuses that are retried (--retry-on)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retry_on: Vec<u16>,
+    /// Idle connections kept open per host (--pool-max-idle)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool_max_idle: Option<usize>,
+    /// Seconds an idle connection is kept open (--pool-idle-timeout)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool_idle_timeout: Option<u64>,
+    /// Seconds between TCP keep-alive probes (--tcp-keepalive)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tcp_keepalive: Option<u64>,
+    /// Seconds between HTTP/2 keep-alive pings (--http2-keep-alive)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http2_keep_alive: Option<u64>,
+    /// Grow HTTP/2 flow-control windows with the bandwidth (--http2-adaptive-window)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http2_adaptive_window: Option<bool>,
+    /// Proxy, header and rate-limit rules per host pattern (`[[hosts]]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hosts: Vec<HostRule>,
+    /// Default arguments per subcommand, inserted before the ones on the command line
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub commands: HashMap<String, Vec<String>>,
+}
+
+impl Settings {
+    /// Overrides these settings with the ones `other` sets; query hashes are merged
+    fn merge(&mut self, other: &Settings) {
+        let other = other.clone();
+        self.cookie_file = other.cookie_file.or(self.cookie_file.take());
+        self.default_quality = other.default_quality.or(self.default_quality.take());
+        self.default_output_format = other.default_output_format.or(self.default_output_format.take());
+        self.default_download_dir = other.default_download_dir.or(self.default_download_dir.take());
+        self.region = other.region.or(self.region.take());
+        self.output_template = other.output_template.or(self.output_template.take());
+        self.library_layout = other.library_layout.or(self.library_layout);
+        self.filename_spaces = other.filename_spaces.or(self.filename_spaces);
+        self.downloader = other.downloader.or(self.downloader);
+        self.connect_timeout = other.connect_timeout.or(self.connect_timeout);
+        self.read_timeout = other.read_timeout.or(self.read_timeout);
+        self.timeout = other.timeout.or(self.timeout);
+        self.download_timeout = other.download_timeout.or(self.download_timeout);
+        self.ca_cert = other.ca_cert.or(self.ca_cert.take());
+        self.log_file = other.log_file.or(self.log_file.take());
+        self.retries = other.retries.or(self.retries);
+        self.retry_backoff = other.retry_backoff.or(self.retry_backoff);
+        self.retry_max_backoff = other.retry_max_backoff.or(self.retry_max_backoff);
+        if !other.retry_on.is_empty() {
+            self.retry_on = other.retry_on;
+        }
+        self.pool_max_idle = other.pool_max_idle.or(self.pool_max_idle);
+        self.pool_idle_timeout = other.pool_idle_timeout.or(self.pool_idle_timeout);
+        self.tcp_keepalive = other.tcp_keepalive.or(self.tcp_keepalive);
+        self.http2_keep_alive = other.http2_keep_alive.or(self.http2_keep_alive);
+        self.http2_adaptive_window = other.http2_adaptive_window.or(self.http2_adaptive_window);
+        if !other.webhooks.is_empty() {
+            self.webhooks = other.webhooks;
+        }
+        if !other.snapshot.is_empty() {
+            self.snapshot = other.snapshot;
+        }
+        if !other.hosts.is_empty() {
+            self.hosts = other.hosts;
+        }
+        self.query_hashes.extend(other.query_hashes);
+        self.commands.extend(other.commands);
+    }
+}
+
+impl ConfigFile {
+    /// The top-level settings with those of `profile` applied on top
+    pub fn settings_for(&self, profile: Option<&str>) -> Result<Settings> {
+        let mut settings = self.settings.clone();
+        if let Some(name) = profile {
+            let overrides = self.profiles.get(name).ok_or_else(|| {
+                let mut names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+                names.sort_unstable();
+                Error::Invalid(format!("Unknown profile '{}'; the config file defines: {}", name, names.join(", ")))
+            })?;
+            settings.merge(overrides);
+        }
+        Ok(settings)
+    }
+}
+
+/// Keys that `config set` can change; `query_hashes.<OPERATION>` sets a hash override
+pub const SETTABLE_KEYS: &[&str] = &[
+    "cookie_file",
+    "default_quality",
+    "default_output_format",
+    "default_download_dir",
+    "region",
+    "output_template",
+    "library_layout",
+    "filename_spaces",
+    "downloader",
+    "webhooks",
+    "snapshot",
+    "connect_timeout",
+    "read_timeout",
+    "timeout",
+    "download_timeout",
+    "retries",
+    "retry_backoff",
+    "retry_max_backoff",
+    "retry_on",
+    "pool_max_idle",
+    "pool_idle_timeout",
+    "tcp_keepalive",
+    "http2_keep_alive",
+    "http2_adaptive_window",
+    "ca_cert",
+    "log_file",
+];
+
+/// Keys whose values are whole numbers
+const INTEGER_KEYS: &[&str] = &[
+    "connect_timeout",
+    "read_timeout",
+    "timeout",
+    "download_timeout",
+    "retries",
+    "retry_backoff",
+    "retry_max_backoff",
+    "pool_max_idle",
+    "pool_idle_timeout",
+    "tcp_keepalive",
+    "http2_keep_alive",
+];
+
+/// A daemon job: what to run and when
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JobConfig {
+    /// Name used in the daemon log; defaults to the title or channel
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Cron expression, either `min hour day month weekday` or with a leading seconds field
+    pub schedule: String,
+    #[serde(flatten)]
+    pub action: JobAction,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum JobAction {
+    /// Download a title's new episodes, like one round of `watch`
+    Sync { title_id: TitleId },
+    /// Record a live channel for a fixed duration
+    Record {
+        channel: String,
+        duration: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        filename: Option<String>,
+    },
+}
+
+impl JobConfig {
+    /// Name shown in the daemon log
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(match &self.action {
+            JobAction::Sync { title_id } => title_id.as_str(),
+            JobAction::Record { channel, .. } => channel,
+        })
+    }
+
+    /// Parses the cron schedule, accepting the common five-field form without seconds
+    #[cfg(feature = "cli")]
+    pub fn parse_schedule(&self) -> Result<cron::Schedule> {
+        let expression = if self.schedule.split_whitespace().count() == 5 {
+            format!("0 {}", self.schedule)
+        } else {
+            self.schedule.clone()
+        };
+        expression
+            .parse()
+            .map_err(|e| Error::Invalid(format!("Invalid schedule '{}' for job {}: {}", self.schedule, self.display_name(), e)))
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct AppConfig {
-    #[allow(dead_code)]
     pub cookie_file_path: Option<PathBuf>,
-    pub video_quality: String,
+    pub save_cookies: bool,         // Write refreshed session cookies back at exit
+    pub cookies_from_secrets: bool, // The session came from `login` rather than a cookie file
     pub output_format: String,
-    pub debug_mode: bool,
+    pub print_template: Option<String>,
     pub download_dir: PathBuf,
-    pub http_client: reqwest::Client,
+    pub redownload_if_changed: bool,
+    pub library_layout: Option<LibraryLayout>,
+    pub filename_spaces: SpaceStyle,
+    pub downloader: DownloaderKind,
+    pub precheck: bool, // Check that a stream URL answers before downloading it
+    pub cancel: CancellationToken, // Cancelling it stops the running download and the rest of a batch
+    pub progress: Option<ProgressSink>, // Receives the download progress; the CLI's progress bar by default
+    pub manifest_cache: Arc<VariantCache>,
+    pub output_template: Option<String>,
+    pub normalize_audio: bool,
+    pub loudness_target: f64,
+    pub loudnorm_two_pass: bool,
+    pub timings: bool,
+    pub audio_langs: Vec<String>,
+    pub contact_sheet: Option<(u32, u32)>, // Grid columns and rows when enabled
+    pub contact_sheet_format: String,
+    pub write_description: bool,
+    pub kids_only: bool,
+    pub exclude_kids: bool,
+    pub interactive: bool,
+    pub notify: bool,
+    pub webhooks: Vec<String>,
+    pub webhook_template: Option<String>,
+    pub snapshot_titles: Vec<TitleId>,
+    pub config_path: PathBuf, // Config file the settings below were read from, if it exists
+    pub state_dir: PathBuf,   // Download history and sync state
+    pub profile: Option<String>,
+    pub command_defaults: HashMap<String, Vec<String>>,
+    pub jobs: Vec<JobConfig>,
+    pub connect_timeout: Option<Duration>, // None when disabled with 0
+    pub read_timeout: Option<Duration>,
+    pub ca_cert: Option<PathBuf>,
+    pub log_file: Option<PathBuf>,
+    pub insecure: bool,
+    pub hosts: Vec<HostRule>,
+    pub retry: RetryPolicy,
+    pub pool: PoolOptions,
+    pub timeout: Option<Duration>,
+    pub download_timeout: Option<Duration>,
+    pub client: GloboClient, // HTTP client, session and API settings
 }
 
 impl AppConfig {
+    /// Builds the configuration with the precedence: command line > environment > config file > defaults
+    #[cfg(feature = "cli")]
     pub async fn from_cli(cli: &Cli) -> Result<Self> {
-        // Attempt to load config from a file (e.g., ~/.config/globo-play-rust/config.toml)
-        // For simplicity, we'll skip the config file loading for now and use CLI args or defaults.
+        let config_path = config_path(cli);
+        let file = match load_config_from_file(&config_path)? {
+            Some(file) => {
+                // Typos and unusable values would otherwise be silently ignored
+                for diagnostic in config_validate::validate_file(&config_path)? {
+                    output::warning!("{}: {}", config_path.display(), diagnostic);
+                }
+                file
+            }
+            None if cli.config.is_some() => return Err(Error::NotFound(format!("Config file not found: {}", config_path.display()))),
+            None => ConfigFile::default(),
+        };
+        let profile = cli.profile.clone().or(file.default_profile.clone());
+        let settings = file.settings_for(profile.as_deref())?;
 
         let cookie_file_path = cli
             .cookie
             .as_ref()
+            .or(settings.cookie_file.as_ref())
             .map(|p| PathBuf::from(shellexpand::tilde(p).into_owned()));
+        let log_file = log_file(cli, &settings);
+
+        let video_quality = match cli.quality {
+            Some(quality) => quality,
+            None => settings
+                .default_quality
+                .as_deref()
+                .map(str::parse::<Quality>)
+                .transpose()
+                .map_err(|e| e.context("Invalid default_quality in the config file"))?
+                .unwrap_or_default(),
+        };
+
+        let output_format = cli
+            .output
+            .clone()
+            .or(settings.default_output_format)
+            .unwrap_or_else(|| constants::DEFAULT_OUTPUT_FORMAT.to_string());
+        if !constants::OUTPUT_FORMATS.contains(&output_format.as_str()) {
+            return Err(Error::Invalid(format!(
+                "Invalid default_output_format '{}' in {}, expected one of: {}",
+                output_format,
+                config_path.display(),
+                constants::OUTPUT_FORMATS.join(", ")
+            )));
+        }
 
-        let download_dir = PathBuf::from(shellexpand::tilde(&cli.output_dir).into_owned());
+        let download_dir = cli
+            .output_dir
+            .as_deref()
+            .or(settings.default_download_dir.as_deref())
+            .unwrap_or(constants::DEFAULT_DOWNLOAD_DIR);
+        let download_dir = PathBuf::from(shellexpand::tilde(download_dir).into_owned());
         if !download_dir.exists() {
-            fs::create_dir_all(&download_dir)?;
-        }
-
-        // Initialize HTTP client with cookie store
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert(
-            reqwest::header::USER_AGENT,
-            reqwest::header::HeaderValue::from_static("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/136.0.0.0 Safari/537.36"),
-        );
-        
-        // Add additional headers found in marine-traffic scripts
-        headers.insert(
-            reqwest::header::ORIGIN,
-            reqwest::header::HeaderValue::from_static("https://globoplay.globo.com"),
-        );
-        headers.insert(
-            reqwest::header::REFERER,
-            reqwest::header::HeaderValue::from_static("https://globoplay.globo.com/"),
-        );
-        headers.insert(
-            "x-platform-id",
-            reqwest::header::HeaderValue::from_static("web"),
-        );
-        headers.insert(
-            "x-device-id",
-            reqwest::header::HeaderValue::from_static("desktop"),
-        );
-
-        let cookie_store = reqwest::cookie::Jar::default();
-        if let Some(ref path) = cookie_file_path {
-            if path.exists() {
-                let content = fs::read_to_string(path)?;
-                for line in content.lines() {
-                    if line.starts_with("#") || line.trim().is_empty() {
-                        continue;
-                    }
-                    let parts: Vec<&str> = line.split('\t').collect();
-                    if parts.len() >= 7 {
-                        // Basic Netscape cookie format parsing
-                        // This is a simplified parser. A more robust one might be needed.
-                        let domain = parts[0];
-                        // let _flag = parts[1]; // TRUE/FALSE - path accessible from all paths
-                        let _path_str = parts[2];
-                        // let _secure = parts[3]; // TRUE/FALSE
-                        // let _expiration = parts[4];
-                        let name = parts[5];
-                        let value = parts[6];
-
-                        let cookie_str = format!("{}={}", name, value);
-                        // The reqwest::cookie::Jar needs a URL to associate the cookie with.
-                        // We'll use a placeholder Globo.com URL.
-                        // This might need adjustment based on actual cookie requirements.
-                        let url = format!("https://{}/", domain.trim_start_matches('.'))
-                            .parse::<reqwest::Url>()?;
-                        cookie_store.add_cookie_str(&cookie_str, &url);
-                    }
-                }
-            }
+            fs::create_dir_all(&download_dir)
+                .map_err(|e| Error::io(format!("Failed to create {}", download_dir.display()), e))?;
+        }
+        let state_dir = paths::state_dir();
+        library::migrate_legacy_state(&download_dir, &state_dir)?;
+
+        let contact_sheet = if cli.contact_sheet {
+            let (columns, rows) = cli
+                .contact_sheet_grid
+                .split_once('x')
+                .and_then(|(c, r)| Some((c.parse::<u32>().ok()?, r.parse::<u32>().ok()?)))
+                .filter(|(c, r)| *c > 0 && *r > 0)
+                .ok_or_else(|| Error::Invalid(format!("Invalid --contact-sheet-grid '{}', expected COLUMNSxROWS", cli.contact_sheet_grid)))?;
+            Some((columns, rows))
+        } else {
+            None
+        };
+
+        // Hash overrides: --query-hash wins over the environment variable, which wins over the config file
+        let mut query_hashes = settings.query_hashes;
+        if let Ok(list) = std::env::var(constants::QUERY_HASHES_ENV_VAR) {
+            let entries: Vec<String> = list.split(',').map(str::to_string).collect();
+            query_hashes.extend(parse_query_hashes(&entries, constants::QUERY_HASHES_ENV_VAR)?);
         }
+        query_hashes.extend(parse_query_hashes(&cli.query_hash, "--query-hash")?);
+
+        // A template wins over a layout, so naming flags replace the config file's naming scheme as a whole
+        let (output_template, library_layout) = if cli.output_template.is_some() || cli.library_layout.is_some() {
+            (cli.output_template.clone(), cli.library_layout)
+        } else {
+            (settings.output_template.clone(), settings.library_layout)
+        };
 
-        let client = reqwest::Client::builder()
-            .default_headers(headers)
-            .cookie_provider(std::sync::Arc::new(cookie_store))
-            .build()?;
+        let seconds = |flag: Option<u64>, setting: Option<u64>, default: u64| {
+            Some(Duration::from_secs(flag.or(setting).unwrap_or(default))).filter(|timeout| !timeout.is_zero())
+        };
+        let connect_timeout = seconds(cli.connect_timeout, settings.connect_timeout, constants::DEFAULT_CONNECT_TIMEOUT_SECS);
+        let read_timeout = seconds(cli.read_timeout, settings.read_timeout, constants::DEFAULT_READ_TIMEOUT_SECS);
+        let timeout = seconds(cli.timeout, settings.timeout, constants::DEFAULT_TIMEOUT_SECS);
+        let download_timeout = seconds(cli.download_timeout, settings.download_timeout, 0);
+
+        let ca_cert = cli
+            .ca_cert
+            .as_ref()
+            .or(settings.ca_cert.as_ref())
+            .map(|p| PathBuf::from(shellexpand::tilde(p).into_owned()));
+        if cli.insecure {
+            output::warning!("--insecure: TLS certificates are NOT verified; anyone on the network path can read and alter traffic");
+        }
+        let retry = RetryPolicy {
+            retries: cli.retries.or(settings.retries).unwrap_or(constants::DEFAULT_RETRIES),
+            backoff: Duration::from_millis(
+                cli.retry_backoff.or(settings.retry_backoff).unwrap_or(constants::DEFAULT_RETRY_BACKOFF_MS),
+            ),
+            max_backoff: Duration::from_secs(
+                cli.retry_max_backoff
+                    .or(settings.retry_max_backoff)
+                    .unwrap_or(constants::DEFAULT_RETRY_MAX_BACKOFF_SECS),
+            ),
+            statuses: [&cli.retry_on, &settings.retry_on]
+                .into_iter()
+                .find(|statuses| !statuses.is_empty())
+                .cloned()
+                .unwrap_or_else(|| constants::DEFAULT_RETRY_STATUSES.to_vec()),
+        };
+        let pool = PoolOptions {
+            max_idle_per_host: cli.pool_max_idle.or(settings.pool_max_idle),
+            idle_timeout: seconds(cli.pool_idle_timeout, settings.pool_idle_timeout, constants::DEFAULT_POOL_IDLE_TIMEOUT_SECS),
+            tcp_keepalive: seconds(cli.tcp_keepalive, settings.tcp_keepalive, 0),
+            http2_keep_alive: seconds(cli.http2_keep_alive, settings.http2_keep_alive, 0),
+            http2_adaptive_window: cli.http2_adaptive_window || settings.http2_adaptive_window.unwrap_or(false),
+        };
+
+        let mut session_request = VideoSessionRequest::new();
+        if let Some(player_type) = &cli.player_type {
+            session_request = session_request.player_type(player_type);
+        }
+        if let Some(offset) = &cli.session_timezone {
+            offset
+                .parse::<chrono::FixedOffset>()
+                .map_err(|_| Error::Invalid(format!("Invalid --session-timezone '{}', expected a UTC offset such as -03:00", offset)))?;
+            session_request = session_request.timezone(offset);
+        }
+        if let Some(consumption) = &cli.consumption {
+            session_request = session_request.consumption(consumption);
+        }
+        for (name, enabled) in parse_capabilities(&cli.session_capability)? {
+            session_request = session_request.capability(name, enabled);
+        }
+
+        let mut client = GloboClient::builder()
+            .quality(video_quality)
+            .session_request(session_request)
+            .region(cli.region.clone().or(settings.region))
+            .query_hashes(query_hashes)
+            .connect_timeout(connect_timeout)
+            .timeout(timeout)
+            .ca_cert(ca_cert.clone())
+            .insecure(cli.insecure)
+            .hosts(settings.hosts.clone())
+            .retry(retry.clone())
+            .pool(pool.clone())
+            .cache_ttl((cli.cache_ttl > 0 && !cli.no_cache).then(|| Duration::from_secs(cli.cache_ttl)))
+            .api_rate_limit((cli.api_rate_limit != 0.0).then_some(cli.api_rate_limit));
+        if cli.debug_dump.is_some() {
+            client = client.exchange_log(Arc::default());
+        }
+        // A cookie file from the command line or config file wins over the cookies stored by `login`
+        let mut cookies_from_secrets = false;
+        if let Some(path) = &cookie_file_path {
+            client = client.cookie_file(path);
+        } else if let Some(cookies) = secrets::load(&paths::secrets_file())?.and_then(|secrets| secrets.cookies) {
+            client = client.cookies(cookies);
+            cookies_from_secrets = true;
+        }
+        let client = client.build()?;
 
         Ok(AppConfig {
             cookie_file_path,
-            video_quality: cli.quality.clone(),
-            output_format: cli.output.clone(),
-            debug_mode: cli.debug,
+            save_cookies: !cli.no_save_cookies,
+            cookies_from_secrets,
+            output_format,
+            print_template: cli.print.clone(),
             download_dir,
-            http_client: client,
+            redownload_if_changed: cli.redownload_if_changed,
+            library_layout,
+            filename_spaces: cli.filename_spaces.or(settings.filename_spaces).unwrap_or_default(),
+            downloader: cli.downloader.or(settings.downloader).unwrap_or_default(),
+            precheck: !cli.no_precheck,
+            cancel: CancellationToken::new(),
+            progress: Some(progress::terminal_bar()),
+            manifest_cache: Arc::new(VariantCache::new(cli.manifest_cache_ttl)),
+            output_template,
+            normalize_audio: cli.normalize_audio,
+            loudness_target: cli.loudness_target,
+            loudnorm_two_pass: !cli.loudnorm_single_pass,
+            timings: cli.timings,
+            audio_langs: cli.audio_lang.clone(),
+            contact_sheet,
+            contact_sheet_format: cli.contact_sheet_format.clone(),
+            write_description: cli.write_description,
+            kids_only: cli.kids_only,
+            exclude_kids: cli.exclude_kids,
+            interactive: cli.interactive,
+            notify: cli.notify,
+            webhooks: if cli.webhook.is_empty() { settings.webhooks } else { cli.webhook.clone() },
+            webhook_template: cli.webhook_template.as_deref().map(utils::read_inline_or_file).transpose()?,
+            snapshot_titles: settings.snapshot,
+            config_path,
+            state_dir,
+            profile,
+            command_defaults: settings.commands,
+            connect_timeout,
+            read_timeout,
+            timeout,
+            download_timeout,
+            ca_cert,
+            log_file,
+            insecure: cli.insecure,
+            hosts: settings.hosts,
+            retry,
+            pool,
+            jobs: file.jobs,
+            client,
+        })
+    }
+
+    /// The settings in effect after merging flags, environment and config file, in config file form
+    pub fn effective_settings(&self) -> ConfigFile {
+        ConfigFile {
+            version: Some(constants::CONFIG_VERSION),
+            settings: Settings {
+                cookie_file: self.cookie_file_path.as_ref().map(|path| path.display().to_string()),
+                default_quality: Some(self.client.quality().to_string()),
+                default_output_format: Some(self.output_format.clone()),
+                default_download_dir: Some(self.download_dir.display().to_string()),
+                region: self.client.region().map(str::to_string),
+                output_template: self.output_template.clone(),
+                library_layout: self.library_layout,
+                filename_spaces: Some(self.filename_spaces),
+                downloader: Some(self.downloader),
+                webhooks: self.webhooks.clone(),
+                snapshot: self.snapshot_titles.clone(),
+                connect_timeout: Some(self.connect_timeout.map_or(0, |timeout| timeout.as_secs())),
+                read_timeout: Some(self.read_timeout.map_or(0, |timeout| timeout.as_secs())),
+                timeout: Some(self.timeout.map_or(0, |timeout| timeout.as_secs())),
+                download_timeout: Some(self.download_timeout.map_or(0, |timeout| timeout.as_secs())),
+                ca_cert: self.ca_cert.as_ref().map(|path| path.display().to_string()),
+                log_file: self.log_file.as_ref().map(|path| path.display().to_string()),
+                hosts: self.hosts.clone(),
+                retries: Some(self.retry.retries),
+                retry_backoff: Some(self.retry.backoff.as_millis() as u64),
+                retry_max_backoff: Some(self.retry.max_backoff.as_secs()),
+                retry_on: self.retry.statuses.clone(),
+                pool_max_idle: self.pool.max_idle_per_host,
+                pool_idle_timeout: Some(self.pool.idle_timeout.map_or(0, |timeout| timeout.as_secs())),
+                tcp_keepalive: Some(self.pool.tcp_keepalive.map_or(0, |interval| interval.as_secs())),
+                http2_keep_alive: Some(self.pool.http2_keep_alive.map_or(0, |interval| interval.as_secs())),
+                http2_adaptive_window: Some(self.pool.http2_adaptive_window),
+                query_hashes: self.client.query_hashes().clone(),
+                commands: self.command_defaults.clone(),
+            },
+            default_profile: self.profile.clone(),
+            profiles: HashMap::new(),
+            jobs: self.jobs.clone(),
+        }
+    }
+
+    /// Options that go before ffmpeg's `-i url`: the read timeout, the TLS settings and retry policy of the
+    /// HTTP client and the proxy and headers of the host rule matching `url`
+    pub fn ffmpeg_input_options(&self, url: &str) -> Vec<String> {
+        let mut options = Vec::new();
+        // -rw_timeout is in microseconds
+        if let Some(timeout) = self.read_timeout {
+            options.extend(["-rw_timeout".to_string(), timeout.as_micros().to_string()]);
+        }
+        if self.insecure {
+            options.extend(["-tls_verify".to_string(), "0".to_string()]);
+        } else if let Some(path) = &self.ca_cert {
+            options.extend(["-ca_file".to_string(), path.display().to_string()]);
+            options.extend(["-tls_verify".to_string(), "1".to_string()]);
+        }
+        options.extend(self.retry.ffmpeg_options());
+        if let Some(rule) = self.client.http().rule_for(url) {
+            // ffmpeg only speaks HTTP proxies
+            if let Some(proxy) = rule.proxy.as_ref().filter(|proxy| proxy.starts_with("http://")) {
+                options.extend(["-http_proxy".to_string(), proxy.clone()]);
+            }
+            if !rule.headers.is_empty() {
+                let headers: String = rule.headers.iter().map(|(name, value)| format!("{}: {}\r\n", name, value)).collect();
+                options.extend(["-headers".to_string(), headers]);
+            }
+        }
+        options
+    }
+
+    /// Formats command output honoring `--print` and `--output`
+    ///
+    /// Returns `None` for the compact format, which each command prints in its own layout.
+    pub fn format_output<T: serde::Serialize>(&self, data: &T) -> Result<Option<String>> {
+        match &self.print_template {
+            Some(template) => utils::render_print_template(template, data).map(Some),
+            None => utils::format_output(data, &self.output_format),
+        }
+    }
+
+    /// True when output is one line per item (CSV or `--print`), so grouped results are flattened into one list
+    pub fn wants_rows(&self) -> bool {
+        self.print_template.is_some() || matches!(self.output_format.as_str(), "csv" | "ndjson")
+    }
+
+    /// Applies `--kids-only`/`--exclude-kids` to an item's content rating
+    ///
+    /// Unrated items are treated as not suitable for children.
+    pub fn passes_kids_filter(&self, content_rating: Option<&str>) -> bool {
+        let is_kids = content_rating.is_some_and(crate::models::is_kids_rating);
+        if self.kids_only {
+            is_kids
+        } else if self.exclude_kids {
+            !is_kids
+        } else {
+            true
+        }
+    }
+
+    /// Writes the session cookies refreshed by the server back to where they were loaded from:
+    /// the cookie file, or the secrets file for a session stored by `login`
+    ///
+    /// # Returns
+    /// True when cookies were written
+    pub fn save_cookies(&self) -> Result<bool> {
+        if !self.save_cookies || !self.client.cookies_changed() {
+            return Ok(false);
+        }
+        if !self.cookies_from_secrets {
+            return self.client.save_cookies();
+        }
+        let path = paths::secrets_file();
+        let mut stored = secrets::load(&path)?.unwrap_or_default();
+        // A `logout` during the run ends the session for good
+        if stored.cookies.is_none() {
+            return Ok(false);
+        }
+        stored.cookies = Some(self.client.export_cookies());
+        secrets::save(&path, &stored)?;
+        tracing::debug!(path = %path.display(), "saved refreshed session cookies");
+        Ok(true)
+    }
+
+    /// Fails with a helpful message when a command needs an authenticated session but has no cookies
+    pub fn require_auth(&self, command: &str) -> Result<()> {
+        if !self.client.has_session() {
+            return Err(Error::Auth(format!(
+                "{} requires an authenticated session; pass --cookie <FILE> or run `login <FILE>`",
+                command
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Parses `OPERATION=HASH` entries into a map; `source` names the flag or variable in errors
+#[cfg(feature = "cli")]
+fn parse_query_hashes(entries: &[String], source: &str) -> Result<HashMap<String, String>> {
+    entries
+        .iter()
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((operation, hash)) if !operation.trim().is_empty() && !hash.trim().is_empty() => {
+                Ok((operation.trim().to_string(), hash.trim().to_string()))
+            }
+            _ => Err(Error::Invalid(format!("Invalid {} entry '{}', expected OPERATION=HASH", source, entry))),
+        })
+        .collect()
+}
+
+/// Parses `--session-capability` entries (`NAME=BOOL`)
+#[cfg(feature = "cli")]
+fn parse_capabilities(entries: &[String]) -> Result<Vec<(String, bool)>> {
+    entries
+        .iter()
+        .map(|entry| match entry.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => match value.trim().parse::<bool>() {
+                Ok(enabled) => Ok((name.trim().to_string(), enabled)),
+                Err(_) => Err(Error::Invalid(format!("Invalid --session-capability entry '{}', expected NAME=true or NAME=false", entry))),
+            },
+            _ => Err(Error::Invalid(format!("Invalid --session-capability entry '{}', expected NAME=BOOL", entry))),
         })
+        .collect()
+}
+
+/// Location of the config file in the platform config directory, e.g. `~/.config/globo-play-rust/config.toml`
+pub fn default_config_path() -> PathBuf {
+    paths::config_file()
+}
+
+/// Config file in use: `--config` if given, otherwise the default location
+#[cfg(feature = "cli")]
+pub fn config_path(cli: &Cli) -> PathBuf {
+    match &cli.config {
+        Some(path) => PathBuf::from(shellexpand::tilde(path).into_owned()),
+        None => default_config_path(),
     }
 }
 
-// Placeholder for loading from a config file, not used in this iteration
-// pub fn load_config_from_file(path: &PathBuf) -> Result<Option<ConfigFile>> {
-//     if path.exists() {
-//         let content = fs::read_to_string(path)?;
-//         let config: ConfigFile = toml::from_str(&content)?;
-//         Ok(Some(config))
-//     } else {
-//         Ok(None)
-//     }
-// }
+/// Writes the commented default config file, creating its directory
+///
+/// Fails if the file exists, unless `force` is set.
+pub fn write_default_config(path: &Path, force: bool) -> Result<()> {
+    if path.exists() && !force {
+        return Err(Error::Invalid(format!("{} already exists; pass --force to overwrite it", path.display())));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| Error::io(format!("Failed to create {}", parent.display()), e))?;
+    }
+    fs::write(path, constants::CONFIG_TEMPLATE).map_err(|e| Error::io(format!("Failed to write config file: {}", path.display()), e))
+}
+
+/// Sets one key in the config file, or in one of its profiles, keeping its comments and layout
+///
+/// `webhooks` takes a comma-separated list and `query_hashes.<OPERATION>` sets a hash override.
+/// The file is created if missing and only written if the result is still a valid config.
+pub fn set_config_value(path: &Path, profile: Option<&str>, key: &str, value: &str) -> Result<()> {
+    config_migrate::migrate_file(path)?;
+    let content = if path.exists() {
+        fs::read_to_string(path).map_err(|e| Error::io(format!("Failed to read config file: {}", path.display()), e))?
+    } else {
+        String::new()
+    };
+    let mut document: toml_edit::DocumentMut =
+        content.parse().map_err(|e| Error::parse(format!("Failed to parse config file: {}", path.display()), e))?;
+    let target = match profile {
+        None => document.as_table_mut(),
+        Some(name) => {
+            let profiles = document
+                .entry("profiles")
+                .or_insert(toml_edit::table())
+                .as_table_mut()
+                .ok_or_else(|| Error::Invalid(format!("profiles in {} is not a table", path.display())))?;
+            // Tables holding only subtables get no header of their own, e.g. no empty [profiles]
+            profiles.set_implicit(true);
+            let table = profiles
+                .entry(name)
+                .or_insert(toml_edit::table())
+                .as_table_mut()
+                .ok_or_else(|| Error::Invalid(format!("profiles.{} in {} is not a table", name, path.display())))?;
+            table.set_implicit(true);
+            table
+        }
+    };
+    match key.split_once('.') {
+        Some(("commands", command)) => {
+            // Aliases are stored under the command's own name, which is what lookups use
+            #[cfg(feature = "cli")]
+            let command = Cli::command()
+                .find_subcommand(command)
+                .map(|subcommand| subcommand.get_name().to_string())
+                .ok_or_else(|| Error::Invalid(format!("Unknown command '{}' in {}", command, key)))?;
+            #[cfg(not(feature = "cli"))]
+            let command = command.to_string();
+            let table = target
+                .entry("commands")
+                .or_insert(toml_edit::table())
+                .as_table_mut()
+                .ok_or_else(|| Error::Invalid(format!("commands in {} is not a table", path.display())))?;
+            let args: toml_edit::Array = value.split_whitespace().collect();
+            table[&command] = toml_edit::value(args);
+        }
+        Some(("query_hashes", operation)) if !operation.is_empty() => {
+            let table = target
+                .entry("query_hashes")
+                .or_insert(toml_edit::table())
+                .as_table_mut()
+                .ok_or_else(|| Error::Invalid(format!("query_hashes in {} is not a table", path.display())))?;
+            table[operation] = toml_edit::value(value);
+        }
+        None if key == "webhooks" => {
+            let urls: toml_edit::Array = value.split(',').map(str::trim).filter(|url| !url.is_empty()).collect();
+            target[key] = toml_edit::value(urls);
+        }
+        None if key == "snapshot" => {
+            let title_ids = value
+                .split(',')
+                .map(str::trim)
+                .filter(|title_id| !title_id.is_empty())
+                .map(|title_id| title_id.parse::<TitleId>().map(|title_id| title_id.to_string()))
+                .collect::<Result<toml_edit::Array>>()?;
+            target[key] = toml_edit::value(title_ids);
+        }
+        None if key == "retry_on" => {
+            let statuses = value
+                .split(',')
+                .map(str::trim)
+                .filter(|status| !status.is_empty())
+                .map(|status| status.parse::<i64>())
+                .collect::<Result<toml_edit::Array, _>>()
+                .map_err(|_| Error::Invalid(format!("Invalid value '{}' for {}, expected comma-separated HTTP statuses", value, key)))?;
+            target[key] = toml_edit::value(statuses);
+        }
+        None if key == "http2_adaptive_window" => {
+            let enabled: bool = value
+                .parse()
+                .map_err(|_| Error::Invalid(format!("Invalid value '{}' for {}, expected true or false", value, key)))?;
+            target[key] = toml_edit::value(enabled);
+        }
+        None if INTEGER_KEYS.contains(&key) => {
+            let number: i64 = value
+                .parse()
+                .map_err(|_| Error::Invalid(format!("Invalid value '{}' for {}, expected a whole number", value, key)))?;
+            target[key] = toml_edit::value(number);
+        }
+        None if SETTABLE_KEYS.contains(&key) => target[key] = toml_edit::value(value),
+        _ => {
+            return Err(Error::Invalid(format!(
+                "Unknown config key '{}', expected one of: {}, query_hashes.<OPERATION>, commands.<COMMAND>",
+                key,
+                SETTABLE_KEYS.join(", ")
+            )))
+        }
+    }
+
+    let updated = document.to_string();
+    let parsed: ConfigFile = toml::from_str(&updated).map_err(|e| Error::parse(format!("Invalid value for {}", key), e))?;
+    let format = parsed.settings_for(profile)?.default_output_format;
+    if let Some(format) = format.filter(|format| !constants::OUTPUT_FORMATS.contains(&format.as_str())) {
+        return Err(Error::Invalid(format!(
+            "Invalid default_output_format '{}', expected one of: {}",
+            format,
+            constants::OUTPUT_FORMATS.join(", ")
+        )));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| Error::io(format!("Failed to create {}", parent.display()), e))?;
+    }
+    fs::write(path, updated).map_err(|e| Error::io(format!("Failed to write config file: {}", path.display()), e))
+}
+
+/// Inserts the config file's default arguments for the invoked subcommand (`[commands]`)
+///
+/// The defaults go right after the subcommand name, so options given on the command line come
+/// later and override them (the parser lets a repeated option replace its earlier value).
+///
+/// # Arguments
+/// * `cli` - The command line parsed without defaults, for `--config`, `--profile` and the subcommand
+/// * `args` - The raw command-line arguments, including the program name
+///
+/// # Returns
+/// The arguments with defaults inserted, or `None` when there are none for this subcommand
+#[cfg(feature = "cli")]
+pub fn command_defaults(cli: &Cli, args: &[OsString]) -> Result<Option<Vec<OsString>>> {
+    let command = Cli::command();
+    let matches = command
+        .clone()
+        .try_get_matches_from(args)
+        .map_err(|e| Error::parse("Failed to parse the command line", e))?;
+    let Some(name) = matches.subcommand_name().map(str::to_string) else {
+        return Ok(None);
+    };
+    // These must keep working when the config file is broken
+    if name == "config" || name == "completions" {
+        return Ok(None);
+    }
+    let Some(file) = load_config_from_file(&config_path(cli))? else {
+        return Ok(None);
+    };
+    let profile = cli.profile.clone().or(file.default_profile.clone());
+    let settings = file.settings_for(profile.as_deref())?;
+    let Some(defaults) = settings.commands.get(&name).filter(|defaults| !defaults.is_empty()) else {
+        return Ok(None);
+    };
+    let Some(subcommand) = command.find_subcommand(&name) else {
+        return Ok(None);
+    };
+    // The first argument naming the subcommand (or one of its aliases)
+    let position = args.iter().skip(1).position(|arg| {
+        arg.to_str()
+            .is_some_and(|arg| arg == name || subcommand.get_all_aliases().any(|alias| alias == arg))
+    });
+    let Some(position) = position else {
+        return Ok(None);
+    };
+    let mut merged = args.to_vec();
+    let insert_at = position + 2;
+    merged.splice(insert_at..insert_at, defaults.iter().map(OsString::from));
+    Ok(Some(merged))
+}
+
+/// The config file's settings, for the setup done before the configuration is loaded: logging
+/// (`log_file`) and where messages go (`machine_output`)
+///
+/// A config file that can't be read is skipped here; loading the configuration reports it.
+#[cfg(feature = "cli")]
+pub fn early_settings(cli: &Cli) -> Settings {
+    load_config_from_file(&config_path(cli))
+        .ok()
+        .flatten()
+        .and_then(|file| file.settings_for(cli.profile.as_deref().or(file.default_profile.as_deref())).ok())
+        .unwrap_or_default()
+}
+
+/// The profile names the config file defines, sorted, for shell completion of `--profile`
+///
+/// A config file that can't be read has none.
+#[cfg(feature = "cli")]
+pub fn profile_names(cli: &Cli) -> Vec<String> {
+    let mut names: Vec<String> = load_config_from_file(&config_path(cli))
+        .ok()
+        .flatten()
+        .map(|file| file.profiles.into_keys().collect())
+        .unwrap_or_default();
+    names.sort_unstable();
+    names
+}
+
+/// True when stdout carries serialized results, i.e. with `--print` or any output format but
+/// `compact`, so progress messages have to go to stderr (see `output::set_machine_output`)
+#[cfg(feature = "cli")]
+pub fn machine_output(cli: &Cli, settings: &Settings) -> bool {
+    let format = cli
+        .output
+        .as_deref()
+        .or(settings.default_output_format.as_deref())
+        .unwrap_or(constants::DEFAULT_OUTPUT_FORMAT);
+    cli.print.is_some() || format != "compact"
+}
+
+/// The log file of `--log-file` or the `log_file` setting
+#[cfg(feature = "cli")]
+pub fn log_file(cli: &Cli, settings: &Settings) -> Option<PathBuf> {
+    cli.log_file
+        .as_ref()
+        .or(settings.log_file.as_ref())
+        .map(|p| PathBuf::from(shellexpand::tilde(p).into_owned()))
+}
+
+/// Reads a TOML config file, returning `None` if it doesn't exist
+pub fn load_config_from_file(path: &Path) -> Result<Option<ConfigFile>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    config_migrate::migrate_file(path)?;
+    let content = fs::read_to_string(path).map_err(|e| Error::io(format!("Failed to read config file: {}", path.display()), e))?;
+    let config: ConfigFile =
+        toml::from_str(&content).map_err(|e| {
+            Error::parse(format!("Failed to parse config file: {} (run `config validate` for details)", path.display()), e)
+        })?;
+    Ok(Some(config))
+}
@@ -0,0 +1,103 @@
+// src/config_migrate.rs
+use crate::constants::CONFIG_VERSION;
+use crate::error::{Error, Result};
+use crate::output;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::DocumentMut;
+
+/// Upgrades a config document from version `from` to `from + 1`
+struct Migration {
+    from: u32,
+    /// Changes the document in place and describes each change made, e.g. "renamed x to y"
+    apply: fn(&mut DocumentMut) -> Vec<String>,
+}
+
+/// Upgrade steps in version order; a renamed or retyped option adds a step here and bumps `CONFIG_VERSION`
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    apply: from_unversioned,
+}];
+
+/// Files written before the `version` field existed already use the version 1 format
+fn from_unversioned(_document: &mut DocumentMut) -> Vec<String> {
+    Vec::new()
+}
+
+/// Version a config file declares; files without a `version` field are version 0
+fn version_of(document: &DocumentMut) -> Result<u32> {
+    match document.get("version") {
+        None => Ok(0),
+        Some(item) => item
+            .as_integer()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| Error::Invalid("version must be a positive integer".to_string())),
+    }
+}
+
+/// Upgrades config file contents to the current version without touching the file
+///
+/// # Returns
+/// The upgraded contents, or `None` if nothing had to change, and the changes made
+pub fn migrate(content: &str) -> Result<(Option<String>, Vec<String>)> {
+    // Syntax errors are left for the config parser and `config validate` to report
+    let Ok(mut document) = content.parse::<DocumentMut>() else {
+        return Ok((None, Vec::new()));
+    };
+    let version = version_of(&document)?;
+    if version > CONFIG_VERSION {
+        return Err(Error::Invalid(format!(
+            "config version {} is newer than the {} this program supports; upgrade globo_play_rust",
+            version, CONFIG_VERSION
+        )));
+    }
+    let mut changes = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|migration| migration.from >= version) {
+        changes.extend((migration.apply)(&mut document));
+    }
+    if changes.is_empty() {
+        return Ok((None, changes));
+    }
+    document["version"] = toml_edit::value(i64::from(CONFIG_VERSION));
+    Ok((Some(document.to_string()), changes))
+}
+
+/// Upgrades the config file at `path` in place, keeping a copy of the old file next to it
+///
+/// Files that are already current, or only lack the `version` field, are left untouched.
+pub fn migrate_file(path: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let content = fs::read_to_string(path).map_err(|e| Error::io(format!("Failed to read config file: {}", path.display()), e))?;
+    let (Some(upgraded), changes) =
+        migrate(&content).map_err(|e| e.context(format!("Failed to upgrade config file: {}", path.display())))?
+    else {
+        return Ok(());
+    };
+    let backup = backup_path(path, &content);
+    fs::copy(path, &backup).map_err(|e| Error::io(format!("Failed to back up config file to {}", backup.display()), e))?;
+    fs::write(path, upgraded).map_err(|e| Error::io(format!("Failed to write config file: {}", path.display()), e))?;
+    output::info!(
+        "Upgraded {} to config version {} (previous file saved as {})",
+        path.display(),
+        CONFIG_VERSION,
+        backup.display()
+    );
+    for change in changes {
+        output::info!("  {}", change);
+    }
+    Ok(())
+}
+
+/// Backup name recording the version it was upgraded from, e.g. `config.toml.v1.bak`,
+/// with a timestamp added if that name is taken
+fn backup_path(path: &Path, content: &str) -> PathBuf {
+    let version = content.parse::<DocumentMut>().ok().and_then(|document| version_of(&document).ok()).unwrap_or(0);
+    let name = path.file_name().map_or_else(|| "config.toml".into(), |name| name.to_string_lossy().into_owned());
+    let backup = path.with_file_name(format!("{}.v{}.bak", name, version));
+    if !backup.exists() {
+        return backup;
+    }
+    path.with_file_name(format!("{}.v{}.{}.bak", name, version, chrono::Local::now().format("%Y%m%d%H%M%S")))
+}
@@ -0,0 +1,383 @@
+// src/config_validate.rs
+#[cfg(feature = "cli")]
+use crate::cli::Cli;
+use crate::config::{ConfigFile, JobAction, Settings};
+use crate::config_migrate;
+use crate::constants;
+use crate::error::{Error, Result};
+use crate::ids::TitleId;
+use crate::quality::Quality;
+use crate::utils;
+#[cfg(feature = "cli")]
+use clap::CommandFactory;
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use toml_edit::{ImDocument, Item, Table};
+
+/// Keys accepted at the top level of the config file and in `[profiles.NAME]`
+const SETTINGS_KEYS: &[&str] = &[
+    "cookie_file",
+    "default_quality",
+    "default_output_format",
+    "default_download_dir",
+    "region",
+    "output_template",
+    "library_layout",
+    "filename_spaces",
+    "downloader",
+    "webhooks",
+    "snapshot",
+    "query_hashes",
+    "connect_timeout",
+    "read_timeout",
+    "timeout",
+    "download_timeout",
+    "retries",
+    "retry_backoff",
+    "retry_max_backoff",
+    "retry_on",
+    "pool_max_idle",
+    "pool_idle_timeout",
+    "tcp_keepalive",
+    "http2_keep_alive",
+    "http2_adaptive_window",
+    "ca_cert",
+    "log_file",
+    "hosts",
+    "commands",
+];
+
+/// Keys accepted only at the top level
+const TOP_LEVEL_KEYS: &[&str] = &["version", "default_profile", "profiles", "jobs"];
+
+/// Keys accepted in a `[[jobs]]` entry
+const JOB_KEYS: &[&str] = &["name", "schedule", "kind", "title_id", "channel", "duration", "filename"];
+
+/// Keys accepted in a `[[hosts]]` entry
+const HOST_KEYS: &[&str] = &["pattern", "proxy", "headers", "rate_limit"];
+
+/// A problem found in the config file
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    /// 1-based line the problem is on, when it can be located
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Checks a config file for syntax and type errors, unknown keys and unusable values
+///
+/// # Arguments
+/// * `path` - The config file
+///
+/// # Returns
+/// The problems found, in file order; fails only if the file can't be read
+pub fn validate_file(path: &Path) -> Result<Vec<Diagnostic>> {
+    let content = fs::read_to_string(path).map_err(|e| Error::io(format!("Failed to read config file: {}", path.display()), e))?;
+    Ok(validate(&content))
+}
+
+fn validate(content: &str) -> Vec<Diagnostic> {
+    let line_of = |span: Option<Range<usize>>| span.map(|span| content[..span.start].matches('\n').count() + 1);
+    let document = match ImDocument::parse(content) {
+        Ok(document) => document,
+        Err(e) => {
+            return vec![Diagnostic {
+                line: line_of(e.span()),
+                message: one_line(e.message()),
+            }]
+        }
+    };
+    let mut checker = Checker {
+        line_of: &line_of,
+        diagnostics: Vec::new(),
+    };
+    // Checks apply to the current format, so an outdated file may also show renamed keys as unknown
+    match config_migrate::migrate(content) {
+        Ok((None, _)) => {}
+        Ok((Some(_), _)) => checker.report(
+            checker.key_line(document.as_table(), &["version"]),
+            format!(
+                "config version is outdated; the file is upgraded to version {} the next time it is loaded",
+                constants::CONFIG_VERSION
+            ),
+        ),
+        Err(e) => checker.report(checker.key_line(document.as_table(), &["version"]), e.to_string()),
+    }
+    checker.check_structure(document.as_table());
+
+    // Unknown keys are reported above; wrong types and values only show up when deserializing
+    match toml::from_str::<ConfigFile>(content) {
+        Ok(config) => checker.check_values(&config, document.as_table()),
+        Err(e) => checker.diagnostics.push(Diagnostic {
+            line: line_of(e.span()),
+            message: one_line(e.message()),
+        }),
+    }
+    checker.diagnostics.sort_by_key(|diagnostic| diagnostic.line);
+    checker.diagnostics
+}
+
+struct Checker<'a> {
+    line_of: &'a dyn Fn(Option<Range<usize>>) -> Option<usize>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn report(&mut self, line: Option<usize>, message: String) {
+        self.diagnostics.push(Diagnostic { line, message });
+    }
+
+    /// Line of the key at `path` (e.g. `["profiles", "nas", "default_quality"]`), if present
+    fn key_line(&self, root: &Table, path: &[&str]) -> Option<usize> {
+        let (last, parents) = path.split_last()?;
+        let mut table = root;
+        for part in parents {
+            table = table.get(part)?.as_table()?;
+        }
+        (self.line_of)(table.key(last)?.span())
+    }
+
+    fn check_structure(&mut self, root: &Table) {
+        for (key, item) in root.iter() {
+            match key {
+                "profiles" => {
+                    let Some(profiles) = item.as_table() else { continue };
+                    for (name, profile) in profiles.iter() {
+                        if let Some(profile) = profile.as_table() {
+                            self.check_settings_keys(profile, &format!("profiles.{}.", name));
+                        }
+                    }
+                }
+                "jobs" => {
+                    let Some(jobs) = item.as_array_of_tables() else { continue };
+                    for job in jobs.iter() {
+                        self.check_keys(job, JOB_KEYS, "jobs.");
+                    }
+                }
+                _ => {}
+            }
+        }
+        let allowed: Vec<&str> = SETTINGS_KEYS.iter().chain(TOP_LEVEL_KEYS).copied().collect();
+        self.check_keys(root, &allowed, "");
+        self.check_settings_tables(root, "");
+    }
+
+    fn check_settings_keys(&mut self, table: &Table, prefix: &str) {
+        self.check_keys(table, SETTINGS_KEYS, prefix);
+        self.check_settings_tables(table, prefix);
+    }
+
+    /// Checks the keys of `[[hosts]]` entries, and that command names under `commands` are subcommands
+    fn check_settings_tables(&mut self, table: &Table, prefix: &str) {
+        if let Some(hosts) = table.get("hosts").and_then(Item::as_array_of_tables) {
+            for host in hosts.iter() {
+                self.check_keys(host, HOST_KEYS, &format!("{}hosts.", prefix));
+            }
+        }
+        #[cfg(feature = "cli")]
+        self.check_command_names(table, prefix);
+    }
+
+    /// Checks that the names under `commands` are subcommands rather than aliases or typos
+    #[cfg(feature = "cli")]
+    fn check_command_names(&mut self, table: &Table, prefix: &str) {
+        let Some(commands) = table.get("commands").and_then(Item::as_table) else {
+            return;
+        };
+        let cli = Cli::command();
+        for (name, _) in commands.iter() {
+            match cli.find_subcommand(name) {
+                None => {
+                    let line = (self.line_of)(commands.key(name).and_then(|key| key.span()));
+                    let names: Vec<&str> = cli.get_subcommands().map(|subcommand| subcommand.get_name()).collect();
+                    let message = match closest(name, &names) {
+                        Some(suggestion) => format!("unknown command '{}' in {}commands (did you mean '{}'?)", name, prefix, suggestion),
+                        None => format!("unknown command '{}' in {}commands", name, prefix),
+                    };
+                    self.report(line, message);
+                }
+                Some(subcommand) if subcommand.get_name() != name => {
+                    let line = (self.line_of)(commands.key(name).and_then(|key| key.span()));
+                    self.report(
+                        line,
+                        format!("'{}' is an alias; use the command name '{}' in {}commands", name, subcommand.get_name(), prefix),
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn check_keys(&mut self, table: &Table, allowed: &[&str], prefix: &str) {
+        for (key, _) in table.iter() {
+            if allowed.contains(&key) {
+                continue;
+            }
+            let line = (self.line_of)(table.key(key).and_then(|key| key.span()));
+            let message = match closest(key, allowed) {
+                Some(suggestion) => format!("unknown key '{}{}' (did you mean '{}'?)", prefix, key, suggestion),
+                None => format!("unknown key '{}{}'", prefix, key),
+            };
+            self.report(line, message);
+        }
+    }
+
+    fn check_values(&mut self, config: &ConfigFile, root: &Table) {
+        self.check_settings(&config.settings, root, &[]);
+        let mut names: Vec<&String> = config.profiles.keys().collect();
+        names.sort();
+        for name in names {
+            self.check_settings(&config.profiles[name], root, &["profiles", name]);
+        }
+        if let Some(profile) = &config.default_profile {
+            if !config.profiles.contains_key(profile) {
+                let line = self.key_line(root, &["default_profile"]);
+                self.report(line, format!("default_profile '{}' is not defined under [profiles]", profile));
+            }
+        }
+
+        let job_tables = root.get("jobs").and_then(Item::as_array_of_tables);
+        for (index, job) in config.jobs.iter().enumerate() {
+            let line = job_tables
+                .and_then(|tables| tables.get(index))
+                .and_then(|table| (self.line_of)(table.span()));
+            #[cfg(feature = "cli")]
+            if let Err(e) = job.parse_schedule() {
+                self.report(line, e.to_string());
+            }
+            if let JobAction::Record { duration, .. } = &job.action {
+                if let Err(e) = utils::parse_duration(duration) {
+                    self.report(line, format!("job {}: {}", job.display_name(), e));
+                }
+            }
+        }
+    }
+
+    /// Checks the values of one settings table; `path` locates it (empty for the top level)
+    fn check_settings(&mut self, settings: &Settings, root: &Table, path: &[&str]) {
+        let key_line = |key: &str| {
+            let mut full = path.to_vec();
+            full.push(key);
+            self.key_line(root, &full)
+        };
+        let mut found = Vec::new();
+
+        if let Some(quality) = &settings.default_quality {
+            if let Err(e) = quality.parse::<Quality>() {
+                found.push((key_line("default_quality"), format!("invalid default_quality: {}", e)));
+            }
+        }
+        if let Some(format) = &settings.default_output_format {
+            if !constants::OUTPUT_FORMATS.contains(&format.as_str()) {
+                found.push((
+                    key_line("default_output_format"),
+                    format!(
+                        "invalid default_output_format '{}', expected one of {}",
+                        format,
+                        constants::OUTPUT_FORMATS.join(", ")
+                    ),
+                ));
+            }
+        }
+        if let Some(cookie_file) = &settings.cookie_file {
+            let path = PathBuf::from(shellexpand::tilde(cookie_file).into_owned());
+            if !path.is_file() {
+                found.push((key_line("cookie_file"), format!("cookie file {} does not exist", path.display())));
+            }
+        }
+        if let Some(title_id) = settings.snapshot.iter().find(|title_id| title_id.as_str().parse::<TitleId>().is_err()) {
+            found.push((key_line("snapshot"), format!("invalid title ID '{}' in snapshot", title_id)));
+        }
+        if let Some(status) = settings.retry_on.iter().find(|status| !(100..=599).contains(*status)) {
+            found.push((key_line("retry_on"), format!("invalid HTTP status {} in retry_on", status)));
+        }
+        if let Some(ca_cert) = &settings.ca_cert {
+            let path = PathBuf::from(shellexpand::tilde(ca_cert).into_owned());
+            if !path.is_file() {
+                found.push((key_line("ca_cert"), format!("CA certificate bundle {} does not exist", path.display())));
+            }
+        }
+        if let Some(dir) = &settings.default_download_dir {
+            let path = PathBuf::from(shellexpand::tilde(dir).into_owned());
+            if let Some(problem) = unwritable_dir_problem(&path) {
+                found.push((key_line("default_download_dir"), problem));
+            }
+        }
+        let host_tables = {
+            let mut table = Some(root);
+            for part in path {
+                table = table.and_then(|table| table.get(part)).and_then(Item::as_table);
+            }
+            table.and_then(|table| table.get("hosts")).and_then(Item::as_array_of_tables)
+        };
+        for (index, rule) in settings.hosts.iter().enumerate() {
+            if let Err(e) = rule.validate() {
+                let line = host_tables
+                    .and_then(|tables| tables.get(index))
+                    .and_then(|table| (self.line_of)(table.span()));
+                found.push((line, e.to_string()));
+            }
+        }
+        for (line, message) in found {
+            self.report(line, message);
+        }
+    }
+}
+
+/// Describes why files can't be written to `dir` (or to the nearest existing parent it would be created in)
+fn unwritable_dir_problem(dir: &Path) -> Option<String> {
+    let existing = dir.ancestors().find(|ancestor| ancestor.exists())?;
+    if !existing.is_dir() {
+        return Some(format!("download directory {}: {} is not a directory", dir.display(), existing.display()));
+    }
+    // Permission bits don't tell the whole story (ACLs, read-only mounts), so try writing
+    let probe = existing.join(format!(".globo_play_write_test_{}", std::process::id()));
+    match fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            None
+        }
+        Err(e) => Some(format!("download directory {} is not writable: {}", dir.display(), e)),
+    }
+}
+
+/// Parser messages can span several lines; diagnostics are printed one per line
+fn one_line(message: &str) -> String {
+    message.trim().lines().map(str::trim).collect::<Vec<_>>().join("; ")
+}
+
+/// The allowed key closest to a misspelled one, if it is a plausible typo
+fn closest<'a>(key: &str, allowed: &[&'a str]) -> Option<&'a str> {
+    allowed
+        .iter()
+        .map(|candidate| (edit_distance(key, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= 3)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -3,17 +3,120 @@
 // Base URLs
 pub const PLAYBACK_API_BASE_URL: &str = "https://playback.video.globo.com";
 pub const GRAPHQL_API_BASE_URL: &str = "https://cloud-jarvis.globo.com/graphql";
+/// Cookies set by this domain and its subdomains are part of the session saved at exit
+pub const SESSION_COOKIE_DOMAIN: &str = "globo.com";
 #[allow(dead_code)]
 pub const THUMBNAIL_BASE_URL: &str = "https://s02.video.glbimg.com";
 
 // URL Templates
 pub const VIDEO_SESSION_URL_TEMPLATE: &str = "/v4/video-session";
-#[allow(dead_code)]
 pub const VIDEOS_BY_DATE_OPERATION: &str = "getTitleVideosByDateView";
-#[allow(dead_code)]
+// Built-in persisted-query hashes; Globo rotates them, so they can be overridden with
+// --query-hash OPERATION=HASH or the QUERY_HASHES_ENV_VAR variable without recompiling
 pub const VIDEOS_BY_DATE_HASH: &str = "d4d95fd5770f9672dc1247e3343c13cafff725f339c95eb28c6e61dac9501c5d";
-#[allow(dead_code)]
-pub const VIDEO_DETAILS_URL_TEMPLATE: &str = "/videos/{}";
+/// Query text sent instead of VIDEOS_BY_DATE_HASH when the API no longer knows the hash
+pub const VIDEOS_BY_DATE_QUERY: &str = include_str!("queries/videos_by_date.graphql");
+/// Config file location before the platform config directory was used (see `paths::config_file`)
+pub const LEGACY_CONFIG_FILE_PATH: &str = "~/.config/globo-play-rust/config.toml";
+// Defaults for settings that can come from the command line or the config file
+pub const DEFAULT_OUTPUT_FORMAT: &str = "pretty";
+pub const DEFAULT_DOWNLOAD_DIR: &str = ".";
+/// Format version of the config file; older files are upgraded by `config_migrate`
+pub const CONFIG_VERSION: u32 = 1;
+/// Commented config file written by `config init`
+pub const CONFIG_TEMPLATE: &str = include_str!("config_template.toml");
+/// Events written to the log file (`--log-file`), whatever the console verbosity: the crate's
+/// debug events (requests, downloads, warnings and errors) and other crates' info events
+pub const LOG_FILE_FILTER: &str = "info,globo_play=debug,globo_play_rust=debug";
+// Limits of what a diagnostics bundle (--debug-dump) keeps: the most recent API exchanges, the
+// start of each response body and of the debug log
+pub const DEBUG_DUMP_MAX_EXCHANGES: usize = 200;
+pub const DEBUG_DUMP_MAX_BODY_BYTES: usize = 256 * 1024;
+pub const DEBUG_DUMP_MAX_LOG_BYTES: usize = 16 * 1024 * 1024;
+/// Longest generated file or directory name in bytes: below the 255 most file systems allow, with
+/// room for the suffixes of partial downloads and sidecar files
+pub const MAX_FILENAME_BYTES: usize = 200;
+/// Longest generated download path, in UTF-16 units on Windows and bytes elsewhere: below Windows'
+/// MAX_PATH of 260 (and PATH_MAX of 4096), with room for the suffixes of partial downloads and sidecar files
+#[cfg(windows)]
+pub const MAX_PATH_LEN: usize = 240;
+#[cfg(not(windows))]
+pub const MAX_PATH_LEN: usize = 4000;
+// Default timeouts in seconds (--connect-timeout, --read-timeout, --timeout)
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+pub const DEFAULT_READ_TIMEOUT_SECS: u64 = 30;
+pub const DEFAULT_TIMEOUT_SECS: u64 = 60;
+// Default retry policy (--retries, --retry-backoff, --retry-max-backoff, --retry-on)
+pub const DEFAULT_RETRIES: u32 = 3;
+pub const DEFAULT_RETRY_BACKOFF_MS: u64 = 500;
+pub const DEFAULT_RETRY_MAX_BACKOFF_SECS: u64 = 30;
+pub const DEFAULT_RETRY_STATUSES: [u16; 5] = [429, 500, 502, 503, 504];
+/// Longest `Retry-After` a rate-limited request waits for before giving up, and how many times
+/// in a row it waits; these pauses don't count against --retries
+pub const MAX_RETRY_AFTER_SECS: u64 = 300;
+pub const MAX_RATE_LIMIT_PAUSES: u32 = 5;
+/// Default limit of requests per second to Globo's APIs (--api-rate-limit), and the number of
+/// requests that may be sent at once after a pause
+pub const DEFAULT_API_RATE_LIMIT: f64 = 5.0;
+pub const API_RATE_BURST: u32 = 10;
+/// Segments the native downloader fetches at once, written in order as they complete
+pub const SEGMENT_FETCH_CONCURRENCY: usize = 4;
+/// Seconds an idle pooled connection is kept open (--pool-idle-timeout)
+pub const DEFAULT_POOL_IDLE_TIMEOUT_SECS: u64 = 90;
+/// A stream URL expiring within this many seconds gets a new playback session before its download starts
+pub const SESSION_REFRESH_MARGIN_SECS: i64 = 60;
+/// Times a live recording resumes with a new session after ffmpeg fails, before giving up
+pub const LIVE_RECORD_MAX_RESUMES: u32 = 5;
+/// Seconds the daemon waits on shutdown for cancelled jobs to clean up (e.g. finalize a recording)
+pub const DAEMON_SHUTDOWN_TIMEOUT_SECS: u64 = 60;
+/// Values accepted by `--output` and `default_output_format`
+pub const OUTPUT_FORMATS: [&str; 5] = ["pretty", "json", "ndjson", "compact", "csv"];
+/// Environment variable holding comma-separated `OPERATION=HASH` overrides
+pub const QUERY_HASHES_ENV_VAR: &str = "GLOBO_PLAY_QUERY_HASHES";
+/// Journalism programs (slugs) listed by the news command when none are given
+pub const NEWS_PROGRAMS: &[&str] = &[
+    "bom-dia-brasil",
+    "jornal-hoje",
+    "jornal-nacional",
+    "jornal-da-globo",
+    "edicao-das-18",
+    "jornal-das-10",
+];
+/// Maximum number of pages followed when aggregating a complete result set
+pub const PAGE_FOLLOW_MAX_PAGES: u32 = 50;
+/// Date ranges longer than this many days are fetched one calendar month at a time, since the
+/// videos-by-date query misbehaves over multi-month ranges
+pub const DATE_WINDOW_MAX_DAYS: i64 = 31;
+/// Year Globoplay launched: where `snapshot` starts by default for a title without a release year
+pub const CATALOG_START_YEAR: i32 = 2015;
+/// Delay between consecutive page requests when following `next`, to stay polite with the API
+pub const PAGE_FOLLOW_DELAY_MS: u64 = 250;
+
+// GraphQL operations sent as full query text (bundled from src/queries)
+pub const VIDEO_OPERATION: &str = "getVideo";
+pub const VIDEO_QUERY: &str = include_str!("queries/video.graphql");
+pub const SEARCH_OPERATION: &str = "search";
+pub const SEARCH_QUERY: &str = include_str!("queries/search.graphql");
+pub const TITLE_OPERATION: &str = "getTitle";
+pub const TITLE_QUERY: &str = include_str!("queries/title.graphql");
+pub const EPISODES_OPERATION: &str = "getTitleEpisodes";
+pub const EPISODES_QUERY: &str = include_str!("queries/episodes.graphql");
+pub const BROADCASTS_OPERATION: &str = "getBroadcasts";
+pub const BROADCASTS_QUERY: &str = include_str!("queries/broadcasts.graphql");
+pub const EPG_OPERATION: &str = "getEpg";
+pub const EPG_QUERY: &str = include_str!("queries/epg.graphql");
+pub const CATEGORIES_OPERATION: &str = "getCategories";
+pub const CATEGORIES_QUERY: &str = include_str!("queries/categories.graphql");
+pub const CATEGORY_TITLES_OPERATION: &str = "getCategoryTitles";
+pub const CATEGORY_TITLES_QUERY: &str = include_str!("queries/category_titles.graphql");
+pub const CONTINUE_WATCHING_OPERATION: &str = "getContinueWatching";
+pub const CONTINUE_WATCHING_QUERY: &str = include_str!("queries/continue_watching.graphql");
+pub const MY_LIST_OPERATION: &str = "getMyList";
+pub const MY_LIST_QUERY: &str = include_str!("queries/my_list.graphql");
+pub const HIGHLIGHTS_OPERATION: &str = "getHighlights";
+pub const HIGHLIGHTS_QUERY: &str = include_str!("queries/highlights.graphql");
+/// Operations that read the logged-in account (`user`), whose responses are never cached
+pub const ACCOUNT_OPERATIONS: &[&str] = &[CONTINUE_WATCHING_OPERATION, MY_LIST_OPERATION];
 
 // Thumbnail resolution templates
 #[allow(dead_code)]
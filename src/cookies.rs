@@ -0,0 +1,197 @@
+// src/cookies.rs
+//! The client's cookie store, which keeps a record of the session cookies so cookies the server
+//! refreshes during a run can be written back to the cookie file or the secrets file
+use crate::constants;
+use crate::error::{Error, Result};
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::header::HeaderValue;
+use reqwest::Url;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// One line of a Netscape cookie file
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CookieLine {
+    /// Host the cookie was set for, with a leading dot when it also applies to its subdomains
+    domain: String,
+    path: String,
+    secure: bool,
+    http_only: bool,
+    /// Expiry in seconds since the epoch; 0 for a cookie that ends with the browser session
+    expires: i64,
+    name: String,
+    value: String,
+}
+
+impl CookieLine {
+    /// Reads a line of a Netscape cookie file; curl and browser extensions mark HttpOnly cookies
+    /// with a `#HttpOnly_` prefix on what would otherwise be a comment
+    fn parse(line: &str) -> Option<CookieLine> {
+        let (line, http_only) = match line.strip_prefix("#HttpOnly_") {
+            Some(line) => (line, true),
+            None if line.starts_with('#') => return None,
+            None => (line, false),
+        };
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 7 {
+            return None;
+        }
+        Some(CookieLine {
+            domain: fields[0].to_string(),
+            path: fields[2].to_string(),
+            secure: fields[3].eq_ignore_ascii_case("TRUE"),
+            http_only,
+            expires: fields[4].trim().parse().unwrap_or(0),
+            name: fields[5].to_string(),
+            value: fields[6].trim_end_matches('\r').to_string(),
+        })
+    }
+
+    /// The cookie set by a `Set-Cookie` header of a response to `url`
+    fn from_set_cookie(cookie: &cookie::Cookie<'_>, url: &Url) -> CookieLine {
+        let domain = match cookie.domain() {
+            Some(domain) => format!(".{}", domain.trim_start_matches('.')),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let now = chrono::Utc::now().timestamp();
+        // Max-Age wins over Expires; either at or before now deletes the cookie
+        let expires = match (cookie.max_age(), cookie.expires_datetime()) {
+            (Some(max_age), _) if max_age.whole_seconds() <= 0 => -1,
+            (Some(max_age), _) => now + max_age.whole_seconds(),
+            (None, Some(expires)) if expires.unix_timestamp() <= now => -1,
+            (None, Some(expires)) => expires.unix_timestamp(),
+            (None, None) => 0,
+        };
+        CookieLine {
+            domain,
+            path: cookie.path().unwrap_or("/").to_string(),
+            secure: cookie.secure().unwrap_or(false),
+            http_only: cookie.http_only().unwrap_or(false),
+            expires,
+            name: cookie.name().to_string(),
+            value: cookie.value().to_string(),
+        }
+    }
+
+    fn key(&self) -> (String, String, String) {
+        (self.domain.to_ascii_lowercase(), self.path.clone(), self.name.clone())
+    }
+
+    /// The cookie in `Set-Cookie` form, for adding it to the jar
+    fn set_cookie(&self) -> String {
+        let mut cookie = format!("{}={}; Path={}", self.name, self.value, self.path);
+        if self.domain.starts_with('.') {
+            cookie.push_str(&format!("; Domain={}", self.domain.trim_start_matches('.')));
+        }
+        cookie
+    }
+
+    fn url(&self) -> Result<Url> {
+        format!("https://{}/", self.domain.trim_start_matches('.'))
+            .parse::<Url>()
+            .map_err(|e| Error::parse(format!("Invalid cookie domain '{}'", self.domain), e))
+    }
+
+    fn to_line(&self) -> String {
+        let flag = |value: bool| if value { "TRUE" } else { "FALSE" };
+        format!(
+            "{}{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            if self.http_only { "#HttpOnly_" } else { "" },
+            self.domain,
+            flag(self.domain.starts_with('.')),
+            self.path,
+            flag(self.secure),
+            self.expires,
+            self.name,
+            self.value
+        )
+    }
+}
+
+/// Cookie store of a `GloboClient`
+///
+/// reqwest's `Jar` matches cookies to requests, while a record of the cookies loaded from the
+/// session and of those Globo's servers set later lets the session be exported again in Netscape
+/// format. Cookies set by other hosts (CDNs, analytics) are sent but not recorded.
+#[derive(Default)]
+pub struct SessionCookies {
+    jar: Jar,
+    cookies: Mutex<BTreeMap<(String, String, String), CookieLine>>,
+    changed: AtomicBool,
+}
+
+impl SessionCookies {
+    /// Adds the cookies of a Netscape-format cookie file, returning how many there were
+    pub fn add_netscape(&self, content: &str) -> Result<usize> {
+        let mut cookies = self.cookies.lock().unwrap_or_else(|e| e.into_inner());
+        let mut count = 0;
+        for line in content.lines().filter_map(CookieLine::parse) {
+            self.jar.add_cookie_str(&line.set_cookie(), &line.url()?);
+            cookies.insert(line.key(), line);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// True once a response has added, changed or deleted a recorded cookie
+    pub fn changed(&self) -> bool {
+        self.changed.load(Ordering::Relaxed)
+    }
+
+    /// The recorded cookies in Netscape cookie-file format, and clears `changed`
+    pub fn to_netscape(&self) -> String {
+        let cookies = self.cookies.lock().unwrap_or_else(|e| e.into_inner());
+        self.changed.store(false, Ordering::Relaxed);
+        let mut content = String::from("# Netscape HTTP Cookie File\n");
+        for line in cookies.values() {
+            content.push_str(&line.to_line());
+            content.push('\n');
+        }
+        content
+    }
+}
+
+// Only the cookie names, so the debug log doesn't contain the session
+impl std::fmt::Debug for SessionCookies {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cookies = self.cookies.lock().unwrap_or_else(|e| e.into_inner());
+        f.debug_struct("SessionCookies")
+            .field("names", &cookies.values().map(|line| line.name.as_str()).collect::<Vec<_>>())
+            .field("changed", &self.changed())
+            .finish()
+    }
+}
+
+impl CookieStore for SessionCookies {
+    fn set_cookies(&self, headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let headers: Vec<&HeaderValue> = headers.collect();
+        let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+        if host == constants::SESSION_COOKIE_DOMAIN || host.ends_with(&format!(".{}", constants::SESSION_COOKIE_DOMAIN)) {
+            let mut cookies = self.cookies.lock().unwrap_or_else(|e| e.into_inner());
+            for header in &headers {
+                let Some(cookie) = header.to_str().ok().and_then(|value| cookie::Cookie::parse(value).ok()) else {
+                    continue;
+                };
+                let line = CookieLine::from_set_cookie(&cookie, url);
+                let changed = if line.expires < 0 {
+                    cookies.remove(&line.key()).is_some()
+                } else if cookies.get(&line.key()).is_some_and(|old| old.value == line.value && old.expires == line.expires) {
+                    false
+                } else {
+                    cookies.insert(line.key(), line);
+                    true
+                };
+                if changed {
+                    tracing::debug!(name = cookie.name(), %host, "session cookie updated");
+                    self.changed.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+        self.jar.set_cookies(&mut headers.into_iter(), url);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        self.jar.cookies(url)
+    }
+}
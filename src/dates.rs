@@ -0,0 +1,213 @@
+// src/dates.rs
+//! Dates, times and durations of API responses, and the serde adapters that parse them
+//!
+//! The APIs aren't consistent about formats, so parsing is lenient: a value that can't be read
+//! becomes `None` instead of failing the whole response.
+use crate::error::{Error, Result};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Deserializer, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// A point in time with the offset it was sent with
+pub type Timestamp = DateTime<FixedOffset>;
+
+/// Offset of Brasília time, assumed for timestamps sent without one
+fn brasilia() -> FixedOffset {
+    FixedOffset::west_opt(3 * 3600).expect("valid offset")
+}
+
+/// Parses an RFC 3339 timestamp, one without an offset (taken as Brasília time) or a bare date
+/// (its midnight)
+pub fn parse_timestamp(value: &str) -> Option<Timestamp> {
+    let value = value.trim();
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp);
+    }
+    let naive = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .or_else(|_| NaiveDate::parse_from_str(value, "%d/%m/%Y"))
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })?;
+    naive.and_local_timezone(brasilia()).single()
+}
+
+/// Reads an epoch time in seconds, or in milliseconds as some CDNs send it
+pub fn from_epoch(value: u64) -> Option<DateTime<Utc>> {
+    let seconds = if value > 100_000_000_000 { value / 1000 } else { value };
+    DateTime::from_timestamp(i64::try_from(seconds).ok()?, 0)
+}
+
+/// `Option<Timestamp>` fields, sent as strings (see `parse_timestamp`) and written as RFC 3339
+pub mod timestamp {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &Option<Timestamp>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(timestamp) => serializer.serialize_str(&timestamp.to_rfc3339()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Timestamp>, D::Error> {
+        let value = Option::<String>::deserialize(deserializer)?;
+        Ok(value.as_deref().and_then(parse_timestamp))
+    }
+}
+
+/// `Option<DateTime<Utc>>` fields sent as epoch seconds or milliseconds, written as RFC 3339
+///
+/// RFC 3339 strings are read too, so written values can be read back.
+pub mod epoch {
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(u64),
+        Text(String),
+    }
+
+    pub fn serialize<S: Serializer>(value: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(time) => serializer.serialize_str(&time.to_rfc3339()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+        Ok(match Option::<Raw>::deserialize(deserializer)? {
+            Some(Raw::Number(value)) => from_epoch(value),
+            Some(Raw::Text(text)) => match text.trim().parse::<u64>() {
+                Ok(value) => from_epoch(value),
+                Err(_) => parse_timestamp(&text).map(|t| t.with_timezone(&Utc)),
+            },
+            None => None,
+        })
+    }
+}
+
+/// `Option<Duration>` fields sent and written as a number of seconds
+pub mod seconds {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(duration) => serializer.serialize_i64(duration.num_seconds()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+        let value = Option::<f64>::deserialize(deserializer)?;
+        Ok(value
+            .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+            .and_then(|seconds| Duration::try_milliseconds((seconds * 1000.0) as i64)))
+    }
+}
+
+/// Formats a duration as H:MM:SS, or M:SS under an hour
+pub fn format_clock(duration: Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    let (hours, minutes, secs) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
+    }
+}
+
+/// A calendar day given on the command line, e.g. `--from-date`
+///
+/// Displays in the API's YYYY-MM-DD format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Day(pub NaiveDate);
+
+impl Day {
+    pub fn today() -> Self {
+        Day(chrono::Local::now().date_naive())
+    }
+
+    /// Reads `input` relative to `today`: "today", "yesterday", "last-week" (7 days ago),
+    /// "last-month" (30 days ago), "N-days-ago", or a date as YYYY-MM-DD or DD/MM/YYYY
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use globo_play::dates::Day;
+    /// let today = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    /// assert_eq!(Day::parse_relative("3-days-ago", today).unwrap().to_string(), "2024-06-07");
+    /// assert!(Day::parse_relative("-3-days-ago", today).is_err());
+    /// assert!(Day::parse_relative("+3-days-ago", today).is_err());
+    /// ```
+    pub fn parse_relative(input: &str, today: NaiveDate) -> Result<Self> {
+        let input = input.trim().to_ascii_lowercase();
+        let days_ago = match input.as_str() {
+            "today" => Some(0),
+            "yesterday" => Some(1),
+            "last-week" => Some(7),
+            "last-month" => Some(30),
+            _ => input
+                .strip_suffix("-days-ago")
+                .filter(|days| !days.is_empty() && days.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|days| days.parse::<i64>().ok()),
+        };
+        let day = match days_ago {
+            Some(days) => Duration::try_days(days).and_then(|days| today.checked_sub_signed(days)),
+            None => NaiveDate::parse_from_str(&input, "%Y-%m-%d")
+                .or_else(|_| NaiveDate::parse_from_str(&input, "%d/%m/%Y"))
+                .ok(),
+        };
+        day.map(Day).ok_or_else(|| {
+            Error::Invalid(format!(
+                "'{}' is not a date (YYYY-MM-DD, DD/MM/YYYY, today, yesterday, last-week, last-month or N-days-ago)",
+                input
+            ))
+        })
+    }
+
+    /// Splits a range longer than `max_days` days into calendar months (the first and last one
+    /// partial); shorter ranges are kept whole
+    pub fn windows(from: Day, to: Day, max_days: i64) -> Vec<(Day, Day)> {
+        if (to.0 - from.0).num_days() < max_days {
+            return vec![(from, to)];
+        }
+        let mut windows = Vec::new();
+        let mut start = from.0;
+        while start <= to.0 {
+            let next_month = start
+                .with_day(1)
+                .and_then(|first| first.checked_add_months(chrono::Months::new(1)))
+                .unwrap_or(NaiveDate::MAX);
+            let end = next_month.pred_opt().unwrap_or(next_month).min(to.0);
+            windows.push((Day(start), Day(end)));
+            start = next_month;
+        }
+        windows
+    }
+
+    /// Checks that a range doesn't end before it starts
+    pub fn check_range(from: Day, to: Day) -> Result<()> {
+        if from > to {
+            return Err(Error::Invalid(format!("The start date {} is after the end date {}", from, to)));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Day {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.format("%Y-%m-%d"))
+    }
+}
+
+impl FromStr for Day {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        Day::parse_relative(input, Day::today().0)
+    }
+}
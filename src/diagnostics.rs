@@ -0,0 +1,373 @@
+// src/diagnostics.rs
+//! Diagnostics bundles (`--debug-dump`): a zip of a run's API exchanges, debug log, effective
+//! configuration and versions, redacted so it can be attached to a bug report
+use crate::config::AppConfig;
+use crate::constants;
+use crate::error::{Error, Result};
+use crate::exit_code;
+use crate::protocol::{ApiRequest, ApiResponse, Method};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// An API request and its response or error, as recorded for a diagnostics bundle
+#[derive(Debug, Clone, Serialize)]
+pub struct Exchange {
+    pub time: String, // RFC 3339
+    pub method: &'static str,
+    pub url: String,
+    /// Headers on top of the default ones (see `ApiRequest::headers`)
+    pub request_headers: Vec<(String, String)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Response body, cut at `DEBUG_DUMP_MAX_BODY_BYTES`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_body: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+    /// Answered by the response cache (`--cache-ttl`) without a request
+    pub from_cache: bool,
+}
+
+impl Exchange {
+    pub(crate) fn new(request: &ApiRequest, response: std::result::Result<&ApiResponse, &Error>, elapsed: Duration, from_cache: bool) -> Self {
+        let (status, response_body, error) = match response {
+            Ok(response) => (Some(response.status.as_u16()), Some(truncate(&response.body)), None),
+            Err(e) => (None, None, Some(e.to_string())),
+        };
+        Exchange {
+            time: chrono::Local::now().to_rfc3339(),
+            method: match request.method {
+                Method::Get => "GET",
+                Method::Post => "POST",
+            },
+            url: request.url.clone(),
+            request_headers: request.headers.iter().map(|(name, value)| (name.to_string(), value.clone())).collect(),
+            request_body: request.body.clone(),
+            status,
+            response_body,
+            error,
+            elapsed_ms: elapsed.as_millis() as u64,
+            from_cache,
+        }
+    }
+}
+
+fn truncate(body: &str) -> String {
+    if body.len() <= constants::DEBUG_DUMP_MAX_BODY_BYTES {
+        return body.to_string();
+    }
+    let mut end = constants::DEBUG_DUMP_MAX_BODY_BYTES;
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}... [{} more bytes]", &body[..end], body.len() - end)
+}
+
+/// The most recent `DEBUG_DUMP_MAX_EXCHANGES` API exchanges of a client (`net::HttpClient::with_exchange_log`)
+#[derive(Debug, Default)]
+pub struct ExchangeLog {
+    exchanges: Mutex<VecDeque<Exchange>>,
+}
+
+impl ExchangeLog {
+    pub fn record(&self, exchange: Exchange) {
+        let mut exchanges = self.exchanges.lock().unwrap_or_else(|e| e.into_inner());
+        if exchanges.len() == constants::DEBUG_DUMP_MAX_EXCHANGES {
+            exchanges.pop_front();
+        }
+        exchanges.push_back(exchange);
+    }
+
+    /// The recorded exchanges, oldest first
+    pub fn exchanges(&self) -> Vec<Exchange> {
+        self.exchanges.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
+    }
+}
+
+/// In-memory copy of the debug log for a diagnostics bundle; a `tracing-subscriber` layer writes
+/// to clones of it. Output past `DEBUG_DUMP_MAX_LOG_BYTES` is dropped.
+#[derive(Debug, Clone, Default)]
+pub struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl LogBuffer {
+    pub fn contents(&self) -> Vec<u8> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut log = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let room = constants::DEBUG_DUMP_MAX_LOG_BYTES.saturating_sub(log.len());
+        log.extend_from_slice(&buf[..buf.len().min(room)]);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Replaces credentials in `text` with `[redacted]`: cookie and authorization headers, token and
+/// signature query parameters (e.g. of signed stream URLs) and JSON fields with such names
+pub fn redact(text: &str) -> String {
+    static PATTERNS: OnceLock<Vec<(Regex, &str)>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        [
+            // Header lines
+            (r"(?im)^((?:cookie|set-cookie|authorization)\s*[:=]\s*).+$", "${1}[redacted]"),
+            // Query and form parameters
+            (r#"(?i)\b((?:[a-z_]*token|signature|sig|hdnts|hdntl|hdnea|auth[a-z_]*|glbid|password|secret)=)[^&"'\s<>]+"#, "${1}[redacted]"),
+            // JSON fields, and headers serialized as ["name", "value"] pairs
+            (r#"(?i)("(?:[a-z_]*token|signature|password|secret|glbid|cookies?|authorization)"\s*:\s*)"(?:[^"\\]|\\.)*""#, "${1}\"[redacted]\""),
+            (r#"(?i)("(?:cookie|authorization)",\s*)"(?:[^"\\]|\\.)*""#, "${1}\"[redacted]\""),
+            // Netscape cookie-file lines, whose value is the last field
+            (r"(?m)^((?:#HttpOnly_)?[^\t\n]+\t(?:TRUE|FALSE)\t[^\t\n]*\t(?:TRUE|FALSE)\t-?\d+\t[^\t\n]+\t).*$", "${1}[redacted]"),
+        ]
+        .into_iter()
+        .map(|(pattern, replacement)| (Regex::new(pattern).expect("valid redaction pattern"), replacement))
+        .collect()
+    });
+    let mut redacted = text.to_string();
+    for (pattern, replacement) in patterns {
+        redacted = pattern.replace_all(&redacted, *replacement).into_owned();
+    }
+    redacted
+}
+
+/// Files of a diagnostics bundle, redacted as they are added and written as a zip
+#[derive(Debug, Default)]
+pub struct Bundle {
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl Bundle {
+    pub fn new() -> Self {
+        Bundle::default()
+    }
+
+    /// Adds a text file, redacted with `redact`
+    pub fn add(&mut self, name: &str, content: &str) {
+        self.files.push((name.to_string(), redact(content).into_bytes()));
+    }
+
+    /// Adds `value` as a pretty-printed, redacted JSON file
+    pub fn add_json<T: Serialize>(&mut self, name: &str, value: &T) -> Result<()> {
+        let json = serde_json::to_string_pretty(value).map_err(|e| Error::parse(format!("Failed to serialize {}", name), e))?;
+        self.add(name, &json);
+        Ok(())
+    }
+
+    /// Writes the files as an uncompressed zip archive
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| Error::io(format!("Failed to create directory: {}", parent.display()), e))?;
+        }
+        fs::write(path, zip(&self.files, chrono::Local::now().naive_local()))
+            .map_err(|e| Error::io(format!("Failed to write diagnostics bundle: {}", path.display()), e))
+    }
+}
+
+/// What a diagnostics bundle reports about the run besides the exchanges and the log
+#[derive(Debug, Serialize)]
+struct Summary<'a> {
+    version: &'a str,
+    os: &'a str,
+    arch: &'a str,
+    args: Vec<String>,
+    written_at: String, // RFC 3339
+    exit_code: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    ffmpeg: String,
+}
+
+/// Writes the diagnostics bundle of a run to `path`
+///
+/// # Arguments
+/// * `config` - The loaded configuration, for the effective settings and the API exchanges; `None`
+///   when loading it failed
+/// * `args` - The command-line arguments
+/// * `error` - Why the run failed, if it did
+/// * `log` - The debug log captured during the run
+pub fn write_bundle(path: &Path, config: Option<&AppConfig>, args: &[std::ffi::OsString], error: Option<&(dyn std::error::Error + 'static)>, log: &LogBuffer) -> Result<()> {
+    let mut bundle = Bundle::new();
+    bundle.add_json(
+        "summary.json",
+        &Summary {
+            version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            args: args.iter().map(|arg| arg.to_string_lossy().into_owned()).collect(),
+            written_at: chrono::Local::now().to_rfc3339(),
+            exit_code: error.map_or(exit_code::SUCCESS, exit_code::classify),
+            error: error.map(crate::error::with_causes),
+            ffmpeg: ffmpeg_version(),
+        },
+    )?;
+    if let Some(config) = config {
+        let settings = toml::to_string_pretty(&config.effective_settings())
+            .map_err(|e| Error::parse("Failed to serialize the effective settings", e))?;
+        bundle.add("config.toml", &settings);
+        if let Some(exchanges) = config.client.http().exchange_log() {
+            bundle.add_json("exchanges.json", &exchanges.exchanges())?;
+        }
+    }
+    bundle.add("log.jsonl", &String::from_utf8_lossy(&log.contents()));
+    bundle.write(path)
+}
+
+/// The first line of `ffmpeg -version`, or why it couldn't be run
+fn ffmpeg_version() -> String {
+    match std::process::Command::new("ffmpeg").arg("-version").output() {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).lines().next().unwrap_or_default().to_string(),
+        Err(e) => format!("not available: {}", e),
+    }
+}
+
+/// A zip archive of `files` stored without compression, which every unzip tool reads
+fn zip(files: &[(String, Vec<u8>)], modified: chrono::NaiveDateTime) -> Vec<u8> {
+    use chrono::{Datelike, Timelike};
+    // MS-DOS date and time, with two-second resolution and years from 1980
+    let time = ((modified.hour() << 11) | (modified.minute() << 5) | (modified.second() / 2)) as u16;
+    let date = (((modified.year().max(1980) - 1980) as u32) << 9 | (modified.month() << 5) | modified.day()) as u16;
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+    for (name, content) in files {
+        let offset = archive.len() as u32;
+        let crc = crc32(content);
+        // Fields shared by the local header and the central directory entry: version needed,
+        // flags (bit 11: UTF-8 names), method 0 (stored), time, date, CRC and sizes, name length
+        let mut common = Vec::new();
+        common.extend(20u16.to_le_bytes());
+        common.extend(0x0800u16.to_le_bytes());
+        common.extend(0u16.to_le_bytes());
+        common.extend(time.to_le_bytes());
+        common.extend(date.to_le_bytes());
+        common.extend(crc.to_le_bytes());
+        common.extend((content.len() as u32).to_le_bytes());
+        common.extend((content.len() as u32).to_le_bytes());
+        common.extend((name.len() as u16).to_le_bytes());
+
+        archive.extend(0x04034b50u32.to_le_bytes());
+        archive.extend(&common);
+        archive.extend(0u16.to_le_bytes()); // extra field length
+        archive.extend(name.as_bytes());
+        archive.extend(content);
+
+        directory.extend(0x02014b50u32.to_le_bytes());
+        directory.extend(20u16.to_le_bytes()); // version made by
+        directory.extend(&common);
+        directory.extend([0u8; 12]); // extra and comment lengths, disk, attributes
+        directory.extend(offset.to_le_bytes());
+        directory.extend(name.as_bytes());
+    }
+    let directory_offset = archive.len() as u32;
+    archive.extend(&directory);
+    archive.extend(0x06054b50u32.to_le_bytes());
+    archive.extend([0u8; 4]); // disk numbers
+    archive.extend((files.len() as u16).to_le_bytes());
+    archive.extend((files.len() as u16).to_le_bytes());
+    archive.extend((directory.len() as u32).to_le_bytes());
+    archive.extend(directory_offset.to_le_bytes());
+    archive.extend(0u16.to_le_bytes()); // comment length
+    archive
+}
+
+/// CRC-32 (IEEE) checksum, as zip archives use
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads the stored entries of a zip archive through its central directory, checking each CRC
+    fn unzip(archive: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let u16_at = |at: usize| u16::from_le_bytes([archive[at], archive[at + 1]]) as usize;
+        let u32_at = |at: usize| u32::from_le_bytes(archive[at..at + 4].try_into().unwrap()) as usize;
+        let end = archive.len() - 22;
+        assert_eq!(u32_at(end), 0x06054b50);
+        let mut entry = u32_at(end + 16);
+        let mut files = Vec::new();
+        for _ in 0..u16_at(end + 10) {
+            assert_eq!(u32_at(entry), 0x02014b50);
+            let (crc, size, name_len, offset) = (u32_at(entry + 16), u32_at(entry + 20), u16_at(entry + 28), u32_at(entry + 42));
+            let name = String::from_utf8(archive[entry + 46..entry + 46 + name_len].to_vec()).unwrap();
+            assert_eq!(u32_at(offset), 0x04034b50);
+            let start = offset + 30 + u16_at(offset + 26) + u16_at(offset + 28);
+            let content = archive[start..start + size].to_vec();
+            assert_eq!(crc32(&content) as usize, crc, "CRC of {}", name);
+            files.push((name, content));
+            entry += 46 + name_len;
+        }
+        files
+    }
+
+    #[test]
+    fn crc32_matches_the_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn bundle_unzips_to_its_redacted_files() {
+        let mut bundle = Bundle::new();
+        bundle.add("log.jsonl", "{\"message\":\"ok\"}\n");
+        bundle.add_json("summary.json", &serde_json::json!({ "exit_code": 0, "token": "secret-value" })).unwrap();
+        bundle.add("empty.txt", "");
+        let path = std::env::temp_dir().join(format!("globo-play-bundle-{}.zip", std::process::id()));
+        bundle.write(&path).unwrap();
+        let archive = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let files = unzip(&archive);
+        let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["log.jsonl", "summary.json", "empty.txt"]);
+        assert_eq!(files[0].1, b"{\"message\":\"ok\"}\n");
+        let summary = String::from_utf8(files[1].1.clone()).unwrap();
+        assert!(summary.contains("\"exit_code\": 0"));
+        assert!(summary.contains("\"token\": \"[redacted]\""));
+        assert!(!summary.contains("secret-value"));
+        assert!(files[2].1.is_empty());
+    }
+
+    #[test]
+    fn redacts_headers_query_tokens_json_fields_and_cookie_lines() {
+        let text = "\
+Cookie: GLBID=abc123; other=1
+authorization: Bearer eyJhbGciOi
+https://cdn.example/master.m3u8?hdnts=exp=1~hmac=ff00&token=t0k3n&quality=high
+{\"access_token\": \"at-value\", \"name\": \"Jornal Nacional\"}
+[\"Authorization\", \"Bearer header-value\"]
+.globo.com\tTRUE\t/\tTRUE\t1735689600\tGLBID\tcookie-value
+#HttpOnly_.globo.com\tTRUE\t/\tFALSE\t0\tsession\thttponly-value
+";
+        let redacted = redact(text);
+        for secret in ["abc123", "eyJhbGciOi", "exp=1", "t0k3n", "at-value", "header-value", "cookie-value", "httponly-value"] {
+            assert!(!redacted.contains(secret), "{} left in:\n{}", secret, redacted);
+        }
+        assert!(redacted.contains("Cookie: [redacted]"));
+        assert!(redacted.contains("authorization: [redacted]"));
+        assert!(redacted.contains("hdnts=[redacted]&token=[redacted]&quality=high"));
+        assert!(redacted.contains("\"access_token\": \"[redacted]\", \"name\": \"Jornal Nacional\""));
+        assert!(redacted.contains(".globo.com\tTRUE\t/\tTRUE\t1735689600\tGLBID\t[redacted]"));
+    }
+}
@@ -0,0 +1,911 @@
+// src/download.rs
+use crate::config::AppConfig;
+use crate::constants;
+use crate::dates;
+use crate::downloader::DownloadJob;
+use crate::error::{self, Error, Result};
+use crate::filename;
+use crate::hls;
+use crate::ids::VideoId;
+#[cfg(feature = "cli")]
+use crate::i18n::Msg;
+use crate::layout::{self, EpisodeInfo};
+use crate::library::{self, ContentFingerprint, HistoryEntry, Library, LibraryStatus};
+use crate::models::{self, Source, SourceKind};
+#[cfg(feature = "cli")]
+use crate::notify;
+use crate::output;
+use crate::progress::{self, ProgressEvent};
+use crate::quality::Quality;
+#[cfg(feature = "cli")]
+use crate::picker;
+use crate::report::{BatchReport, ItemReport, ItemStatus, Phase};
+use crate::utils;
+use crate::webhook;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// Selects the best stream source for the specified quality.
+/// 
+/// # Arguments
+/// * `sources` - A slice of available video sources
+/// * `quality` - The quality to pick; sources whose resolution is unknown are only picked as the default
+/// * `resolutions` - Resolutions looked up from HLS manifests, keyed by source URL
+///
+/// # Returns
+/// Option containing the selected source, or None if no sources available
+pub fn select_best_stream(sources: &[Source], quality: Quality, resolutions: &HashMap<String, u32>) -> Option<Source> {
+    if sources.is_empty() {
+        return None;
+    }
+
+    // An expired signed URL would only be refused by the CDN; it is kept only if nothing else is left
+    let unexpired: Vec<Source> = sources.iter().filter(|s| !s.is_expired()).cloned().collect();
+    let sources = if unexpired.is_empty() { sources } else { &unexpired };
+
+    let ranked = match quality {
+        Quality::Max => find_highest_quality_source(sources, true, resolutions),
+        Quality::Min => find_lowest_quality_source(sources, true, resolutions),
+        Quality::Height(height) => find_height_source(sources, height, resolutions),
+        // Sources don't advertise their bitrate; it picks the HLS variant when muxing audio
+        Quality::Bitrate(_) => None,
+    };
+    if ranked.is_some() {
+        return ranked;
+    }
+
+    // Default to primary source or first source
+    let primary_source = sources.iter().find(|s| s.is_primary());
+
+    primary_source.cloned().or_else(|| sources.first().cloned())
+}
+
+/// Determines the resolution of a source from its label, URL, asset key or manifest lookup
+pub fn source_resolution(source: &Source, resolutions: &HashMap<String, u32>) -> Option<u32> {
+    if let Some(label_str) = source.label.as_deref() {
+        if !label_str.is_empty() {
+            if let Some(res) = extract_resolution(label_str) {
+                return Some(res);
+            }
+        }
+    }
+
+    if let Some(res) = extract_resolution_from_url(&source.url) {
+        return Some(res);
+    }
+
+    if let Some(asset_key_str) = source.asset_key.as_deref() {
+        if !asset_key_str.is_empty() {
+            if let Some(res) = extract_resolution(asset_key_str) { // asset_key can also contain resolution info
+                return Some(res);
+            }
+        }
+    }
+
+    // Fall back to the variant table of the source's master playlist
+    resolutions.get(&source.url).copied()
+}
+
+/// Returns true if a source can't be ranked without fetching its manifest
+fn needs_manifest_lookup(source: &Source) -> bool {
+    source_resolution(source, &HashMap::new()).is_none()
+}
+
+/// Finds the highest quality source from a list of sources
+/// 
+/// Attempts to parse resolution values like "1080p", "720p", etc. or uses metadata
+fn find_highest_quality_source(sources: &[Source], prefer_primary: bool, resolutions: &HashMap<String, u32>) -> Option<Source> {
+    let mut best_source: Option<Source> = None;
+    let mut max_resolution: u32 = 0;
+
+    let filtered_sources = sources.iter().filter(|s| {
+        if prefer_primary {
+            s.is_primary()
+        } else {
+            true // Consider all sources if not preferring primary
+        }
+    });
+
+    for source in filtered_sources {
+        if let Some(resolution) = source_resolution(source, resolutions) {
+            if resolution > max_resolution {
+                max_resolution = resolution;
+                best_source = Some(source.clone());
+            }
+        }
+    }
+
+    best_source
+}
+
+/// Finds the lowest quality source from a list of sources
+fn find_lowest_quality_source(sources: &[Source], prefer_primary: bool, resolutions: &HashMap<String, u32>) -> Option<Source> {
+    let mut worst_source: Option<Source> = None;
+    let mut min_resolution: u32 = u32::MAX;
+
+    let filtered_sources = sources.iter().filter(|s| {
+        if prefer_primary {
+            s.is_primary()
+        } else {
+            true // Consider all sources if not preferring primary
+        }
+    });
+
+    for source in filtered_sources {
+        if let Some(resolution) = source_resolution(source, resolutions) {
+            if resolution < min_resolution {
+                min_resolution = resolution;
+                worst_source = Some(source.clone());
+            }
+        }
+    }
+
+    worst_source
+}
+
+/// Finds the source with the given height, else the highest one below it, else the lowest one
+fn find_height_source(sources: &[Source], height: u32, resolutions: &HashMap<String, u32>) -> Option<Source> {
+    let ranked: Vec<(u32, &Source)> = sources
+        .iter()
+        .filter_map(|s| source_resolution(s, resolutions).map(|resolution| (resolution, s)))
+        .collect();
+    ranked
+        .iter()
+        .filter(|(resolution, _)| *resolution <= height)
+        .max_by_key(|(resolution, _)| *resolution)
+        .or_else(|| ranked.iter().min_by_key(|(resolution, _)| *resolution))
+        .map(|(_, source)| (*source).clone())
+}
+
+/// Resolves the inputs for a download honoring `--audio-lang`
+///
+/// When the source's master playlist has matching audio renditions, the download uses the
+/// selected video variant plus one input per chosen audio rendition; otherwise the source URL
+/// is downloaded as-is and the reason is recorded as a warning.
+///
+/// # Returns
+/// The URL to pass to ffmpeg and the extra audio inputs to mux
+async fn resolve_audio_tracks(
+    source: &Source,
+    quality: Quality,
+    config: &AppConfig,
+    report: &mut ItemReport,
+) -> (String, Vec<utils::AudioTrackInput>) {
+    if config.audio_langs.is_empty() {
+        return (source.url.clone(), Vec::new());
+    }
+    if !source.url.contains(".m3u8") {
+        report.warn("--audio-lang only applies to HLS sources; using the default audio");
+        return (source.url.clone(), Vec::new());
+    }
+
+    let manifest_started = Instant::now();
+    let playlist = config.manifest_cache.get_or_fetch(&source.url, config).await;
+    report.record_phase(Phase::Manifest, manifest_started.elapsed());
+    let playlist = match playlist {
+        Ok(playlist) => playlist,
+        Err(e) => {
+            report.warn(format!("could not read audio renditions: {}", error::with_causes(&e)));
+            return (source.url.clone(), Vec::new());
+        }
+    };
+    if playlist.audio.is_empty() {
+        report.warn("source has no alternative audio renditions; using the default audio");
+        return (source.url.clone(), Vec::new());
+    }
+
+    let (selected, missing) = playlist.select_audio(&config.audio_langs);
+    for lang in missing {
+        report.warn(format!(
+            "audio language {} not available (available: {})",
+            lang,
+            playlist.audio_languages().join(", ")
+        ));
+    }
+    let variant = playlist.select_variant(quality);
+    match variant {
+        Some(variant) if !selected.is_empty() => {
+            let tracks = selected
+                .iter()
+                .filter_map(|r| {
+                    r.uri.clone().map(|url| utils::AudioTrackInput {
+                        url,
+                        language: r.language.clone(),
+                    })
+                })
+                .collect();
+            (variant.uri.clone(), tracks)
+        }
+        _ => (source.url.clone(), Vec::new()),
+    }
+}
+
+/// Extracts resolution value from labels like "720p", "1080p HD", etc.
+pub fn extract_resolution(label: &str) -> Option<u32> {
+    // Find digits followed by 'p'
+    let re = regex::Regex::new(r"(\d+)p").ok()?;
+    re.captures(label)
+        .and_then(|caps| caps.get(1))
+        .and_then(|res| res.as_str().parse::<u32>().ok())
+}
+
+/// Extracts resolution value from URLs containing resolution information
+/// 
+/// For example: urls containing "r360_1080" would extract 1080
+fn extract_resolution_from_url(url: &str) -> Option<u32> {
+    // Look for common resolution patterns in URLs
+    let res_patterns = [
+        // r360_1080 pattern
+        regex::Regex::new(r"r\d+_(\d+)").ok()?,
+        // direct resolution like 1080p or 720p
+        regex::Regex::new(r"(\d+)p").ok()?,
+        // Any sequence of 3-4 digits that might represent resolution
+        regex::Regex::new(r"/(\d{3,4})(/|_|\.)").ok()?
+    ];
+    
+    for pattern in &res_patterns {
+        if let Some(caps) = pattern.captures(url) {
+            if let Some(res_match) = caps.get(1) {
+                if let Ok(res) = res_match.as_str().parse::<u32>() {
+                    return Some(res);
+                }
+            }
+        }
+    }
+    
+    None
+}
+
+/// Prints video metadata in the configured output format
+fn print_video_details(video: &models::Video, config: &AppConfig) -> Result<()> {
+    if let Some(output) = config.format_output(video)? {
+        println!("{}", output);
+    } else {
+        // Compact output
+        println!("Title: {}", video.title);
+        println!("ID: {}", video.id);
+        println!(
+            "Program: {}",
+            video.program.as_ref().and_then(|p| p.headline.as_deref()).unwrap_or("N/A")
+        );
+        println!("Duration: {}", video.duration.map_or_else(|| "N/A".to_string(), dates::format_clock));
+        println!("Date: {}", video.exhibited_at.map_or_else(|| "N/A".to_string(), |t| t.format("%Y-%m-%d %H:%M").to_string()));
+        println!("Available for: {}", video.available_for.as_deref().unwrap_or("N/A"));
+    }
+    Ok(())
+}
+
+/// Runs the video command for one video and prints its report
+///
+/// # Arguments
+/// * `video_id` - The ID of the video
+/// * `download` - Whether to download the video
+/// * `custom_filename` - Optional custom filename for the downloaded video
+/// * `quality_override` - Optional quality setting that overrides the global config
+/// * `output_dir_override` - Optional output directory that overrides the global config
+/// * `config` - The application configuration
+/// * `fetch_full_info` - Whether to print the full session information
+///
+/// # Returns
+/// An error if the video could not be fetched or downloaded
+pub async fn process_single_video(
+    video_id: VideoId,
+    download: bool,
+    custom_filename: Option<String>,
+    quality_override: Option<Quality>,
+    output_dir_override: Option<String>,
+    config: &AppConfig,
+    fetch_full_info: bool,
+) -> Result<()> {
+    let mut report = ItemReport::new(&video_id, config.timings);
+    let result = process_video(video_id, download, custom_filename, quality_override, output_dir_override, config, fetch_full_info, &mut report).await;
+    if download {
+        if let Err(e) = &result {
+            report.fail(e);
+        }
+        record_download_outcome(&report, config).await;
+        #[cfg(feature = "cli")]
+        if config.notify {
+            notify::item_finished(&report);
+        }
+    }
+    result?;
+    ensure_description_sidecar(&mut report, None, config);
+    // stdout carries one document in machine output: the report of a download, else the video details
+    if download || (config.timings && !output::machine_output()) {
+        report.print(&config.output_format)?;
+    } else if config.timings {
+        report.print_timings();
+    }
+    if let ItemStatus::Failed { error } = &report.status {
+        return Err(Error::Invalid(error.clone()));
+    }
+    Ok(())
+}
+
+/// Another source to try after `failed` stalled: a backup source if the session has one, else the
+/// best of the others for `quality`; expired sources aren't tried
+fn fallback_source(sources: &[Source], failed: &Source, quality: Quality, resolutions: &HashMap<String, u32>) -> Option<Source> {
+    let others: Vec<Source> = sources.iter().filter(|s| s.url != failed.url && !s.is_expired()).cloned().collect();
+    let backups: Vec<Source> = others.iter().filter(|s| s.kind == SourceKind::Backup).cloned().collect();
+    select_best_stream(if backups.is_empty() { &others } else { &backups }, quality, resolutions)
+}
+
+/// Fetches a new session for a source whose signed URL has expired or is about to, and picks the
+/// same stream from it (by kind and label), or the best one for `quality` if it is gone
+///
+/// Falls back to the stale source, with a warning, if the session can't be refreshed.
+async fn refresh_source(
+    video_id: &VideoId,
+    stale: Source,
+    quality: Quality,
+    resolutions: &HashMap<String, u32>,
+    config: &AppConfig,
+    report: &mut ItemReport,
+) -> Source {
+    output::info!("The stream URL of {} has expired or is about to, fetching a new session", video_id);
+    let session_started = Instant::now();
+    let session = config.client.fetch_video_session(video_id).await;
+    report.record_phase(Phase::Session, session_started.elapsed());
+    let sources = match session {
+        Ok(session) => session.sources,
+        Err(e) => {
+            report.warn(format!("could not refresh the expired session: {}", e));
+            return stale;
+        }
+    };
+    let same = sources
+        .iter()
+        .find(|source| source.kind == stale.kind && source.label == stale.label && !source.is_expired())
+        .cloned();
+    match same.or_else(|| select_best_stream(&sources, quality, resolutions)) {
+        Some(fresh) => fresh,
+        None => {
+            report.warn("the refreshed session has no streams, using the expired URL");
+            stale
+        }
+    }
+}
+
+/// Records a live HLS stream for a fixed wall-clock duration
+///
+/// ffmpeg follows the live playlist as it refreshes; the recording is written as MPEG-TS
+/// (which stays playable if interrupted) and remuxed into the final container at the end.
+/// Signed live URLs expire long before a program ends, so ffmpeg is stopped before the source
+/// expires, and also when it fails, and the recording resumes into the same `.ts` file with a
+/// new session. Cancelling `config.cancel` stops ffmpeg and keeps what was recorded so far.
+///
+/// # Arguments
+/// * `media_id` - The live media ID, to fetch new sessions with
+/// * `source` - The stream to record
+/// * `path` - The final output file (e.g. `.mp4`)
+/// * `duration` - How long to record
+/// * `config` - The application configuration
+/// * `report` - Collects warnings about refreshed sessions and resumed recordings
+pub async fn record_live(
+    media_id: &VideoId,
+    source: Source,
+    path: &std::path::Path,
+    duration: std::time::Duration,
+    config: &AppConfig,
+    report: &mut ItemReport,
+) -> Result<()> {
+    if let Some(parent_dir) = path.parent() {
+        if !parent_dir.exists() {
+            tokio::fs::create_dir_all(parent_dir)
+                .await
+                .map_err(|e| Error::io(format!("Failed to create directory: {}", parent_dir.display()), e))?;
+        }
+    }
+
+    let temp_path = path.with_extension("recording.ts");
+    let part_path = path.with_extension("recording.part.ts");
+    let part_str = part_path
+        .to_str()
+        .ok_or_else(|| Error::Invalid(format!("Invalid output path for ffmpeg: {}", part_path.display())))?
+        .to_string();
+    let _ = tokio::fs::remove_file(&temp_path).await;
+
+    let margin = chrono::Duration::seconds(constants::SESSION_REFRESH_MARGIN_SECS);
+    let quality = config.client.quality();
+    let deadline = Instant::now() + duration;
+    let mut source = source;
+    let mut resumes = 0;
+    output::info!("Recording {} for {}s to {}", media_id, duration.as_secs(), path.display());
+    let outcome = loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.as_secs() == 0 {
+            break Ok(());
+        }
+        if source.expires_within(margin) {
+            source = refresh_source(media_id, source, quality, &HashMap::new(), config, report).await;
+        }
+        // Stop short of the URL's expiry; a source that expires sooner than that is recorded until it fails
+        let until_expiry = source
+            .expiration_time
+            .and_then(|expires| (expires - chrono::Utc::now() - margin).to_std().ok())
+            .filter(|left| left.as_secs() > 0);
+        let length = until_expiry.map_or(remaining, |left| left.min(remaining));
+
+        let mut args = vec![
+            "-y".to_string(),
+            "-progress".to_string(),
+            "pipe:1".to_string(),
+            "-nostats".to_string(),
+            "-protocol_whitelist".to_string(),
+            "file,http,https,tcp,tls,crypto".to_string(),
+        ];
+        args.extend(config.ffmpeg_input_options(&source.url));
+        args.extend(
+            [
+                // Start at the live edge and keep reloading the playlist through hiccups
+                "-live_start_index",
+                "-1",
+                "-max_reload",
+                "1000",
+                "-m3u8_hold_counters",
+                "1000",
+                "-i",
+                &source.url,
+                "-t",
+                &length.as_secs().to_string(),
+                "-c",
+                "copy",
+                "-f",
+                "mpegts",
+                &part_str,
+            ]
+            .map(String::from),
+        );
+        let result = crate::downloader::run_ffmpeg_process(
+            &args,
+            &source.url,
+            &part_path,
+            &config.cancel,
+            config.progress.as_ref(),
+            config.read_timeout,
+        )
+        .await;
+        append_recording_part(&part_path, &temp_path).await?;
+        match result {
+            Ok(()) => resumes = 0,
+            Err(Error::Cancelled) => break Err(Error::Cancelled),
+            Err(e) if resumes < constants::LIVE_RECORD_MAX_RESUMES => {
+                resumes += 1;
+                output::info!("Recording interrupted; resuming with a new session");
+                report.warn(format!("recording resumed after: {}", error::with_causes(&e)));
+                source = refresh_source(media_id, source, quality, &HashMap::new(), config, report).await;
+            }
+            Err(e) => break Err(e),
+        }
+    };
+
+    if !temp_path.exists() {
+        return outcome;
+    }
+    if let Err(e) = &outcome {
+        output::warning!("Recording stopped early ({}); finalizing what was recorded", e);
+    }
+    output::info!("Finalizing recording into {}", path.display());
+    let temp_str = temp_path
+        .to_str()
+        .ok_or_else(|| Error::Invalid(format!("Invalid output path for ffmpeg: {}", temp_path.display())))?
+        .to_string();
+    let output_str = path
+        .to_str()
+        .ok_or_else(|| Error::Invalid(format!("Invalid output path for ffmpeg: {}", path.display())))?
+        .to_string();
+    utils::run_ffmpeg(&[
+        "-hide_banner".to_string(),
+        "-y".to_string(),
+        "-i".to_string(),
+        temp_str,
+        "-c".to_string(),
+        "copy".to_string(),
+        "-bsf:a".to_string(),
+        "aac_adtstoasc".to_string(),
+        output_str,
+    ])
+    .await
+    .map_err(|e| e.context(format!("Failed to finalize recording; raw stream kept at {}", temp_path.display())))?;
+    tokio::fs::remove_file(&temp_path).await.ok();
+    outcome
+}
+
+/// Appends one ffmpeg run of a recording to the MPEG-TS file collecting all of them; TS streams
+/// can be joined byte by byte
+async fn append_recording_part(part_path: &std::path::Path, temp_path: &std::path::Path) -> Result<()> {
+    let mut part = match tokio::fs::File::open(part_path).await {
+        Ok(part) => part,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(Error::io(format!("Failed to read {}", part_path.display()), e)),
+    };
+    let mut recording = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(temp_path)
+        .await
+        .map_err(|e| Error::io(format!("Failed to open {}", temp_path.display()), e))?;
+    tokio::io::copy(&mut part, &mut recording)
+        .await
+        .map_err(|e| Error::io(format!("Failed to append to {}", temp_path.display()), e))?;
+    drop(part);
+    tokio::fs::remove_file(part_path).await.ok();
+    Ok(())
+}
+
+/// Asks the user which of a session's sources to download (`--interactive`)
+#[cfg(feature = "cli")]
+fn pick_source(sources: &[Source]) -> Result<Source> {
+    let labels: Vec<String> = sources
+        .iter()
+        .map(|source| {
+            let mut label = format!(
+                "{} ({}{})",
+                source.label.as_deref().filter(|l| !l.is_empty()).unwrap_or("unlabeled"),
+                source.kind,
+                source.cdn.as_deref().map(|cdn| format!(", {}", cdn)).unwrap_or_default()
+            );
+            if !source.audio_languages.is_empty() {
+                label.push_str(&format!(" audio: {}", source.audio_languages.join(", ")));
+            }
+            label
+        })
+        .collect();
+    let index = picker::pick(Msg::ChooseStream.text(), &labels)?;
+    Ok(sources[index].clone())
+}
+
+#[cfg(not(feature = "cli"))]
+fn pick_source(_sources: &[Source]) -> Result<Source> {
+    Err(Error::Invalid("choosing a stream interactively needs the cli feature".to_string()))
+}
+
+/// Handles the video command, fetching video information and optionally downloading the video
+///
+/// # Arguments
+/// * `video_id` - The ID of the video to fetch
+/// * `download` - Whether to download the video
+/// * `custom_filename` - Optional custom filename for the downloaded video
+/// * `quality_override` - Optional quality override for the video
+/// * `output_dir_override` - Optional output directory for the downloaded video
+/// * `config` - The application configuration
+/// * `fetch_full_info` - Whether to fetch full video info (true) or basic info (false)
+/// * `report` - Collects the item's final status and non-fatal warnings
+///
+/// # Returns
+/// Result indicating success or error
+#[allow(clippy::too_many_arguments)]
+pub async fn process_video(
+    video_id: VideoId,
+    download: bool,
+    custom_filename: Option<String>,
+    quality_override: Option<Quality>,
+    output_dir_override: Option<String>,
+    config: &AppConfig,
+    fetch_full_info: bool, // True for VideoInfo, false for Video (basic)
+    report: &mut ItemReport,
+) -> Result<()> {
+    // Plain info requests don't need a playback session
+    if !download && !fetch_full_info {
+        match config.client.fetch_video_details(&video_id).await {
+            Ok(video) => {
+                report.title = Some(video.title.clone());
+                return print_video_details(&video, config);
+            }
+            Err(e) => report.warn(format!("metadata lookup failed, falling back to a playback session: {}", e)),
+        }
+    }
+
+    output::info!("Fetching video session for ID: {}", video_id);
+    progress::emit(config.progress.as_ref(), ProgressEvent::phase(Phase::Session));
+    let session_started = Instant::now();
+    let session_result = config.client.fetch_video_session(&video_id).await;
+    report.record_phase(Phase::Session, session_started.elapsed());
+    match session_result {
+        Ok(mut session) => {
+            if fetch_full_info {
+                // Surface the audio languages available in each HLS source
+                let manifest_started = Instant::now();
+                hls::annotate_audio_languages(&mut session.sources, config).await;
+                report.record_phase(Phase::Manifest, manifest_started.elapsed());
+            }
+            report.title = session.resource.as_ref().and_then(|r| r.name.clone());
+            // video-info always prints the full session, as compact JSON in compact mode
+            let formatted = match config.format_output(&session)? {
+                None if fetch_full_info => {
+                    Some(serde_json::to_string(&session).map_err(|e| Error::parse("Failed to serialize to JSON", e))?)
+                }
+                formatted => formatted,
+            };
+            if let Some(output) = formatted {
+                // A download prints its item report instead
+                if !download {
+                    println!("{}", output);
+                }
+            } else {
+                // Compact output for basic video info
+                if let Some(resource) = &session.resource {
+                     println!("Title: {}", resource.name.as_deref().unwrap_or("N/A"));
+                     println!("ID: {}", resource.id.as_deref().unwrap_or("N/A"));
+                } else {
+                    println!("Video ID: {}", video_id); // Fallback if resource details are not in session
+                }
+                println!("Available Streams:");
+                for source in &session.sources {
+                    println!("  - Label: {}, URL: {}", source.label.as_deref().unwrap_or("N/A"), source.url);
+                    if !source.audio_languages.is_empty() {
+                        println!("    Audio: {}", source.audio_languages.join(", "));
+                    }
+                }
+            }
+
+            if download {
+                let quality = quality_override.unwrap_or(config.client.quality());
+                // Only sources whose resolution can't be guessed from label/URL need their manifest
+                // fetched, and only when there is a choice to make by resolution
+                let unranked: Vec<&Source> = session.sources.iter().filter(|s| needs_manifest_lookup(s)).collect();
+                let ranks_by_resolution = !matches!(quality, Quality::Bitrate(_));
+                let resolutions = if ranks_by_resolution && session.sources.len() > 1 && !unranked.is_empty() {
+                    progress::emit(config.progress.as_ref(), ProgressEvent::phase(Phase::Manifest));
+                    let manifest_started = Instant::now();
+                    let (resolutions, warnings) = hls::lookup_resolutions(&unranked, config).await;
+                    report.record_phase(Phase::Manifest, manifest_started.elapsed());
+                    warnings.into_iter().for_each(|w| report.warn(w));
+                    resolutions
+                } else {
+                    HashMap::new()
+                };
+                let chosen = if config.interactive && session.sources.len() > 1 {
+                    Some(pick_source(&session.sources)?)
+                } else {
+                    select_best_stream(&session.sources, quality, &resolutions)
+                };
+                if let Some(stream_source) = chosen {
+                    if stream_source.label.as_deref().is_none_or(str::is_empty) {
+                        match source_resolution(&stream_source, &resolutions) {
+                            Some(res) => report.warn(format!("label missing, guessed {}p from URL/manifest", res)),
+                            None => report.warn("label missing, stream resolution unknown"),
+                        }
+                    }
+                    let filename = match (custom_filename, config.library_layout) {
+                        (Some(name), _) => PathBuf::from(name),
+                        (None, _) if config.output_template.is_some() => {
+                            let info = EpisodeInfo::from_session(&session, &video_id);
+                            let template = config.output_template.as_deref().unwrap_or_default();
+                            layout::render_template(template, &info, &video_id, "mp4", config.filename_spaces)
+                        }
+                        (None, Some(layout)) => {
+                            let info = EpisodeInfo::from_session(&session, &video_id);
+                            layout::layout_path(layout, &info, "mp4", config.filename_spaces)
+                        }
+                        (None, None) => {
+                            let title = session.resource.as_ref().and_then(|r| r.name.as_deref()).unwrap_or(&video_id);
+                            // Assuming mp4, might need to check source type
+                            PathBuf::from(filename::sanitize_filename(title, Some("mp4"), config.filename_spaces))
+                        }
+                    };
+
+                    let output_dir = output_dir_override
+                        .map(PathBuf::from)
+                        .unwrap_or_else(|| config.download_dir.clone());
+                    let mut library = Library::load(&output_dir)?;
+                    let fingerprint = ContentFingerprint::from_session(&session);
+                    if config.redownload_if_changed {
+                        match library.status(&video_id, &fingerprint) {
+                            LibraryStatus::Unchanged => {
+                                output::info!("Skipping {}: already downloaded and unchanged upstream", video_id);
+                                report.status = ItemStatus::Skipped {
+                                    reason: "already downloaded and unchanged upstream".to_string(),
+                                };
+                                return Ok(());
+                            }
+                            LibraryStatus::Changed(changes) => {
+                                output::info!("Upstream version of {} changed ({}), re-downloading", video_id, changes.join(", "));
+                            }
+                            LibraryStatus::New => {}
+                        }
+                    }
+                    let mut download_path = filename::fit_path(&output_dir.join(filename), constants::MAX_PATH_LEN);
+                    // Sessions go stale while earlier steps (or earlier items of a batch) run, and the CDN
+                    // answers an expired URL with 403
+                    let stream_source = if stream_source.expires_within(chrono::Duration::seconds(constants::SESSION_REFRESH_MARGIN_SECS)) {
+                        refresh_source(&video_id, stream_source, quality, &resolutions, config, report).await
+                    } else {
+                        stream_source
+                    };
+
+                    output::info!(
+                        "Downloading video from {} to {}",
+                        stream_source.url, // Use stream_source.url instead of stream_source
+                        download_path.display()
+                    );
+                    let started = webhook::WebhookEvent::new(webhook::EventKind::DownloadStarted, &video_id, report.title.as_deref())
+                        .with_path(&download_path);
+                    webhook::send(&started, config).await;
+                    let downloader = config.downloader.downloader()?;
+                    let download_started = Instant::now();
+                    let mut source = stream_source;
+                    let mut switched = false;
+                    loop {
+                        let (download_url, audio_tracks) = resolve_audio_tracks(&source, quality, config, report).await;
+                        let job = DownloadJob {
+                            url: &download_url,
+                            path: &download_path,
+                            audio_tracks: &audio_tracks,
+                            quality,
+                            input_options: &config.ffmpeg_input_options(&download_url),
+                            cancel: &config.cancel,
+                            progress: config.progress.as_ref(),
+                            stall_timeout: config.read_timeout,
+                            timeout: config.download_timeout,
+                            precheck: config.precheck,
+                        };
+                        match utils::download_file(config.client.http(), downloader.as_ref(), job).await {
+                            // A dead CDN connection is worth one more try from another source
+                            Err(e) if !switched && matches!(e.without_context(), Error::Stalled(_)) => {
+                                let Some(fallback) = fallback_source(&session.sources, &source, quality, &resolutions) else {
+                                    return Err(e);
+                                };
+                                output::warning!("{}; switching to another source", e);
+                                report.warn(format!("{}, switched to the {} source", e, fallback.kind));
+                                source = fallback;
+                                switched = true;
+                            }
+                            // The backend may have changed the extension to match what it wrote
+                            result => {
+                                download_path = result?;
+                                break;
+                            }
+                        }
+                    }
+                    report.record_phase(Phase::Download, download_started.elapsed());
+                    output::info!("Download complete: {}", download_path.display());
+                    if config.normalize_audio || config.contact_sheet.is_some() {
+                        progress::emit(config.progress.as_ref(), ProgressEvent::phase(Phase::PostProcess));
+                    }
+                    if config.normalize_audio {
+                        let post_started = Instant::now();
+                        if let Err(e) = utils::normalize_audio(&download_path, config.loudness_target, config.loudnorm_two_pass).await {
+                            report.warn(format!("audio normalization failed: {}", error::with_causes(&e)));
+                        }
+                        report.record_phase(Phase::PostProcess, post_started.elapsed());
+                    }
+                    if let Some((columns, rows)) = config.contact_sheet {
+                        let post_started = Instant::now();
+                        match utils::generate_contact_sheet(&download_path, columns, rows, &config.contact_sheet_format).await {
+                            Ok(sheet_path) => {
+                                output::info!("Contact sheet written to {}", sheet_path.display());
+                                report.sidecars.push(sheet_path);
+                            }
+                            Err(e) => report.warn(format!("contact sheet generation failed: {}", error::with_causes(&e))),
+                        }
+                        report.record_phase(Phase::PostProcess, post_started.elapsed());
+                    }
+                    if config.write_description {
+                        let description = session.metadata.as_ref().and_then(|m| m.description.as_deref());
+                        if let Some(description) = description.filter(|d| !d.trim().is_empty()) {
+                            match utils::write_description(&download_path, description) {
+                                Ok(sidecar_path) => report.sidecars.push(sidecar_path),
+                                Err(e) => report.warn(error::with_causes(&e)),
+                            }
+                        }
+                    }
+                    library.record(&video_id, report.title.clone(), &download_path, fingerprint);
+                    library.save()?;
+                    report.status = ItemStatus::Downloaded;
+                    report.path = Some(download_path);
+                } else {
+                    output::error!("Could not find a suitable stream to download for quality {}", quality);
+                    report.status = ItemStatus::Failed {
+                        error: format!("no suitable stream for quality {}", quality),
+                    };
+                }
+            }
+        }
+        Err(e) => {
+            output::error!("Error fetching video session for {}: {}", video_id, e);
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// Publishes the outcome of a requested download: a webhook event and a history entry
+async fn record_download_outcome(report: &ItemReport, config: &AppConfig) {
+    if let Some(event) = webhook::WebhookEvent::from_report(report) {
+        webhook::send(&event, config).await;
+    }
+    if report.status != ItemStatus::Fetched {
+        if let Err(e) = library::append_history(&config.state_dir, &HistoryEntry::from_report(report)) {
+            output::warning!("could not record download history: {}", error::with_causes(&e));
+        }
+    }
+}
+
+/// Makes sure a downloaded item got its `--write-description` sidecar
+///
+/// The session metadata is preferred; listing commands can pass the item's summary as a
+/// fallback. Items without any description get a warning instead.
+pub fn ensure_description_sidecar(report: &mut ItemReport, fallback: Option<&str>, config: &AppConfig) {
+    if !config.write_description || report.status != ItemStatus::Downloaded {
+        return;
+    }
+    if report.sidecars.iter().any(|p| p.extension().is_some_and(|e| e == "description")) {
+        return;
+    }
+    let Some(media_path) = report.path.clone() else {
+        return;
+    };
+    match fallback.filter(|d| !d.trim().is_empty()) {
+        Some(description) => match utils::write_description(&media_path, description) {
+            Ok(sidecar_path) => report.sidecars.push(sidecar_path),
+            Err(e) => report.warn(error::with_causes(&e)),
+        },
+        None => report.warn("no description available for --write-description"),
+    }
+}
+
+/// A video queued for a batch download by a listing command
+pub struct BatchItem {
+    pub video_id: VideoId,
+    pub headline: Option<String>,
+    /// Listing summary, used as the `--write-description` fallback
+    pub summary: Option<String>,
+}
+
+/// Downloads every item of a listing, continuing past failures, and prints the batch report
+///
+/// # Arguments
+/// * `items` - The videos to download
+/// * `config` - The application configuration
+///
+/// # Returns
+/// The batch report with the outcome of every item
+pub async fn download_batch(items: Vec<BatchItem>, config: &AppConfig) -> Result<BatchReport> {
+    let mut batch_report = BatchReport::default();
+    if items.is_empty() {
+        output::info!("No videos found to download.");
+        return Ok(batch_report);
+    }
+    output::info!("Attempting to download all {} videos...", items.len());
+    for batch_item in items {
+        if config.cancel.is_cancelled() {
+            output::info!("Download cancelled; skipping the remaining videos.");
+            break;
+        }
+        let video_id_to_download = &batch_item.video_id;
+        output::info!("--- Downloading video: {} ({}) ---", batch_item.headline.as_deref().unwrap_or("N/A"), video_id_to_download);
+        // Use default quality and output dir from global config for batch downloads
+        // Filename will be auto-generated based on title
+        let mut item_report = ItemReport::new(video_id_to_download, config.timings);
+        if let Err(e) = process_video(
+            video_id_to_download.clone(),
+            true,
+            None, // No custom filename for batch
+            None, // Use global quality
+            None, // Use global output dir
+            config,
+            false, // Don't need full info print during batch download
+            &mut item_report,
+        ).await {
+            output::error!("Failed to download video {}: {}", video_id_to_download, e);
+            item_report.fail(&e);
+            // Continue with the next video
+        }
+        if item_report.title.is_none() {
+            item_report.title = batch_item.headline.clone();
+        }
+        ensure_description_sidecar(&mut item_report, batch_item.summary.as_deref(), config);
+        record_download_outcome(&item_report, config).await;
+        batch_report.push(item_report);
+        output::info!("--------------------------------------");
+    }
+    batch_report.print(&config.output_format)?;
+    #[cfg(feature = "cli")]
+    if config.notify {
+        notify::batch_finished(&batch_report);
+    }
+    Ok(batch_report)
+}
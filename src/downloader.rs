@@ -0,0 +1,299 @@
+// src/downloader.rs
+use crate::net::HttpClient;
+use crate::progress::ProgressSink;
+use crate::quality::Quality;
+use crate::error::{Error, Result};
+use crate::utils::AudioTrackInput;
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+pub use tokio_util::sync::CancellationToken;
+use crate::output;
+use crate::progress::Meter;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process::Command;
+
+/// A stream to save to a local file
+#[derive(Debug, Clone, Copy)]
+pub struct DownloadJob<'a> {
+    /// Playlist of the stream; a video-only variant when `audio_tracks` is non-empty
+    pub url: &'a str,
+    /// Output file; its directory already exists
+    pub path: &'a Path,
+    /// Separate audio renditions to mux in, each tagged with its language
+    pub audio_tracks: &'a [AudioTrackInput],
+    /// Variant the native downloader picks when `url` is a master playlist (`--quality`)
+    pub quality: Quality,
+    /// ffmpeg options for every input, e.g. the read timeout and TLS settings (`AppConfig::ffmpeg_input_options`)
+    pub input_options: &'a [String],
+    /// Stops the download when cancelled; the backend then removes its partial output and fails
+    /// with `Error::Cancelled`
+    pub cancel: &'a CancellationToken,
+    /// Receives `progress::ProgressEvent`s while the download runs
+    pub progress: Option<&'a ProgressSink>,
+    /// Aborts the download (a native segment, or ffmpeg as a whole) when no data arrives for this long,
+    /// failing with `Error::Stalled`
+    pub stall_timeout: Option<Duration>,
+    /// Total time the download may take, enforced by `utils::download_file`
+    pub timeout: Option<Duration>,
+    /// Whether `utils::download_file` checks that the URL answers before starting the backend
+    pub precheck: bool,
+}
+
+/// A download backend, called by `utils::download_file` once the URL and output directory are checked
+///
+/// New backends implement this and get a `DownloaderKind`, without changes to the command handlers.
+/// They should notice `job.cancel` promptly, as embedders use it to stop downloads from a UI.
+/// They return the file they wrote, which is `job.path` unless its extension doesn't fit the
+/// container they produce.
+pub trait Downloader: std::fmt::Debug + Send + Sync {
+    fn download<'a>(&'a self, client: &'a HttpClient, job: DownloadJob<'a>) -> BoxFuture<'a, Result<PathBuf>>;
+}
+
+/// The download backends (`--downloader`, `downloader` in the config file)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum DownloaderKind {
+    /// The `ffmpeg` program, which also muxes separate audio tracks
+    Ffmpeg,
+    /// Fetching the HLS segments directly, without external programs
+    Native,
+}
+
+impl Default for DownloaderKind {
+    /// ffmpeg when the build has it, otherwise the native downloader
+    fn default() -> Self {
+        if cfg!(feature = "ffmpeg") {
+            DownloaderKind::Ffmpeg
+        } else {
+            DownloaderKind::Native
+        }
+    }
+}
+
+impl DownloaderKind {
+    /// The backend of this kind, or an error if the build doesn't include it
+    pub fn downloader(self) -> Result<Box<dyn Downloader>> {
+        match self {
+            #[cfg(feature = "ffmpeg")]
+            DownloaderKind::Ffmpeg => Ok(Box::new(FfmpegDownloader)),
+            #[cfg(feature = "native-downloader")]
+            DownloaderKind::Native => Ok(Box::new(NativeHlsDownloader)),
+            #[allow(unreachable_patterns)]
+            DownloaderKind::Ffmpeg => Err(Error::Invalid("This build has no ffmpeg downloader; enable the ffmpeg feature".to_string())),
+            #[allow(unreachable_patterns)]
+            DownloaderKind::Native => Err(Error::Invalid("This build has no native downloader; enable the native-downloader feature".to_string())),
+        }
+    }
+}
+
+/// Downloads and remuxes with the `ffmpeg` program
+#[cfg(feature = "ffmpeg")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FfmpegDownloader;
+
+#[cfg(feature = "ffmpeg")]
+impl Downloader for FfmpegDownloader {
+    fn download<'a>(&'a self, _client: &'a HttpClient, job: DownloadJob<'a>) -> BoxFuture<'a, Result<PathBuf>> {
+        Box::pin(async move { run_ffmpeg(job).await.map(|()| job.path.to_path_buf()) })
+    }
+}
+
+/// Downloads HLS streams by fetching their segments (see `hls::download_stream`)
+///
+/// Encrypted streams and separate audio tracks aren't supported. MPEG-TS streams are saved with
+/// a `.ts` extension whatever `job.path` asked for, as they aren't remuxed.
+#[cfg(feature = "native-downloader")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NativeHlsDownloader;
+
+#[cfg(feature = "native-downloader")]
+impl Downloader for NativeHlsDownloader {
+    fn download<'a>(&'a self, client: &'a HttpClient, job: DownloadJob<'a>) -> BoxFuture<'a, Result<PathBuf>> {
+        Box::pin(async move {
+            if !job.audio_tracks.is_empty() {
+                return Err(Error::Invalid("Muxing separate audio tracks needs the ffmpeg downloader".to_string()));
+            }
+            crate::hls::download_stream(client, job.url, job.path, job.quality, job.cancel, job.progress, job.stall_timeout).await
+        })
+    }
+}
+
+/// Runs ffmpeg to copy the stream (and audio renditions) into `path`
+// TODO: Allow configuring ffmpeg path.
+#[cfg(feature = "ffmpeg")]
+async fn run_ffmpeg(job: DownloadJob<'_>) -> Result<()> {
+    let DownloadJob { url, path, audio_tracks, input_options, cancel, progress, stall_timeout, .. } = job;
+    let output_path_str = path.to_str().ok_or_else(|| {
+        Error::Invalid(format!("Invalid output path for ffmpeg: {}", path.display()))
+    })?;
+
+    // Construct and execute ffmpeg command
+    let mut args: Vec<String> = vec![
+        "-y".to_string(), // Overwrite output files without asking
+        "-progress".to_string(), // Progress as key=value lines on stdout, instead of the stats on stderr
+        "pipe:1".to_string(),
+        "-nostats".to_string(),
+        "-protocol_whitelist".to_string(),
+        "file,http,https,tcp,tls,crypto".to_string(),
+    ];
+    args.extend(input_options.iter().cloned());
+    args.push("-i".to_string());
+    args.push(url.to_string());
+    for track in audio_tracks {
+        args.extend(input_options.iter().cloned());
+        args.push("-i".to_string());
+        args.push(track.url.clone());
+    }
+    if !audio_tracks.is_empty() {
+        args.push("-map".to_string());
+        args.push("0:v:0".to_string());
+        for (index, track) in audio_tracks.iter().enumerate() {
+            args.push("-map".to_string());
+            args.push(format!("{}:a:0", index + 1));
+            if let Some(language) = &track.language {
+                args.push(format!("-metadata:s:a:{}", index));
+                args.push(format!("language={}", language));
+            }
+        }
+    }
+    args.extend(
+        ["-c", "copy", "-bsf:a", "aac_adtstoasc", output_path_str]
+            .iter()
+            .map(|a| a.to_string()),
+    );
+
+    match run_ffmpeg_process(&args, url, path, cancel, progress, stall_timeout).await {
+        Err(failure @ (Error::Cancelled | Error::Stalled(_))) => {
+            let _ = tokio::fs::remove_file(path).await;
+            if matches!(failure, Error::Cancelled) {
+                output::info!("Cancelled the download of {}", url);
+            }
+            Err(failure)
+        }
+        result => result,
+    }
+}
+
+/// Runs ffmpeg with `args`, which write `path` from `url`, reporting its progress
+///
+/// ffmpeg is killed when `cancel` fires (`Error::Cancelled`), when its output stops growing for
+/// `stall_timeout` (`Error::Stalled`) or when the future is dropped; the partial output is left to
+/// the caller.
+pub(crate) async fn run_ffmpeg_process(
+    args: &[String],
+    url: &str,
+    path: &Path,
+    cancel: &CancellationToken,
+    progress: Option<&ProgressSink>,
+    stall_timeout: Option<Duration>,
+) -> Result<()> {
+    tracing::debug!(args = ?args, "running ffmpeg");
+    output::verbose!(
+        "Executing ffmpeg command: ffmpeg {}",
+        args.iter().map(|a| format!("\"{}\"", a)).collect::<Vec<_>>().join(" ")
+    );
+
+    let mut cmd = Command::new("ffmpeg");
+    cmd.args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| Error::Ffmpeg(format!("Failed to spawn ffmpeg command. Is ffmpeg installed and in your PATH? ({})", e)))?;
+    let stdout = child.stdout.take().ok_or_else(|| Error::Ffmpeg("ffmpeg stdout is not piped".to_string()))?;
+    let mut stderr = child.stderr.take().ok_or_else(|| Error::Ffmpeg("ffmpeg stderr is not piped".to_string()))?;
+
+    // When the output last grew, in milliseconds since ffmpeg started, for the stall watchdog
+    let started = Instant::now();
+    let last_growth = AtomicU64::new(0);
+
+    // Each progress block ends with a `progress=continue` (or `progress=end`) line
+    let read_progress = async {
+        let mut meter = Meter::new(progress);
+        let mut lines = BufReader::new(stdout).lines();
+        let mut last_size = 0;
+        while let Some(line) = lines.next_line().await? {
+            if let Some(size) = line.strip_prefix("total_size=") {
+                if let Ok(bytes) = size.trim().parse() {
+                    meter.set_bytes(bytes);
+                    if bytes > last_size {
+                        last_size = bytes;
+                        last_growth.store(started.elapsed().as_millis() as u64, Ordering::Relaxed);
+                    }
+                }
+            } else if line.starts_with("progress=") {
+                meter.report(false);
+            }
+        }
+        std::io::Result::Ok(())
+    };
+    let read_stderr = async {
+        let mut buffer = Vec::new();
+        stderr.read_to_end(&mut buffer).await.map(|_| buffer)
+    };
+
+    // ffmpeg keeps reporting progress while its input hangs, so a stall shows as output that stops growing
+    let watchdog = async {
+        let Some(limit) = stall_timeout else {
+            return std::future::pending().await;
+        };
+        loop {
+            let idle = started.elapsed().saturating_sub(Duration::from_millis(last_growth.load(Ordering::Relaxed)));
+            if idle >= limit {
+                return limit;
+            }
+            tokio::time::sleep(limit - idle).await;
+        }
+    };
+
+    // Wait for the command to complete while reading its output, unless the download is cancelled or stalls
+    let finished = tokio::select! {
+        finished = async { tokio::join!(child.wait(), read_stderr, read_progress) } => Ok(finished),
+        _ = cancel.cancelled() => Err(Error::Cancelled),
+        limit = watchdog => Err(Error::Stalled(format!("ffmpeg received no data for {}s from {}", limit.as_secs(), url))),
+    };
+    let (status, stderr_output, progress_result) = match finished {
+        Ok(finished) => finished,
+        Err(failure) => {
+            let _ = child.kill().await;
+            return Err(failure);
+        }
+    };
+    let status = status.map_err(|e| Error::io("Failed to wait for ffmpeg command execution", e))?;
+    let stderr_output = stderr_output.map_err(|e| Error::io("Failed to read ffmpeg output", e))?;
+    if let Err(e) = progress_result {
+        output::verbose!("Failed to read ffmpeg progress: {}", e);
+    }
+
+    // Check ffmpeg's exit status
+    if status.success() {
+        output::info!(
+            "ffmpeg successfully downloaded {} to {}",
+            url,
+            path.display()
+        );
+        // Optionally print ffmpeg's stderr if it contains useful info (ffmpeg often uses stderr for progress/info)
+        let stderr_output = String::from_utf8_lossy(&stderr_output);
+        if !stderr_output.is_empty() {
+            output::verbose!("ffmpeg stderr:\n{}", stderr_output);
+        }
+        Ok(())
+    } else {
+        let stderr_str = String::from_utf8_lossy(&stderr_output);
+        Err(Error::Ffmpeg(format!(
+            "ffmpeg command failed with status: {}.\\nInput URL: {}\\nOutput Path: {}\\n\\nffmpeg stderr:\\n{}",
+            status,
+            url,
+            path.display(),
+            stderr_str
+        )))
+    }
+}
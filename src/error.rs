@@ -0,0 +1,310 @@
+// src/error.rs
+use http::StatusCode;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Errors returned by the library's client, API calls and credential storage
+///
+/// API failures are sorted into the kinds callers act on differently (log in again, subscribe,
+/// use a Brazilian connection, wait, give up) rather than left as HTTP status codes.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// No session or an expired one
+    #[error("{0}")]
+    Auth(String),
+    /// The session is valid but the account's plan doesn't include the content
+    #[error("Subscription required: {0}")]
+    SubscriptionRequired(String),
+    /// The content can only be watched from Brazil or from another region
+    #[error("Content restricted to Brazil: {0}")]
+    GeoBlocked(String),
+    /// The content is rated above what the account's profile may watch, or needs age verification
+    #[error("Age restricted: {0}")]
+    AgeRestricted(String),
+    /// The content existed but is no longer offered: its availability window ended or it was
+    /// taken down
+    #[error("No longer available: {0}")]
+    Unavailable(String),
+    /// The video, title or other resource doesn't exist
+    #[error("{0}")]
+    NotFound(String),
+    /// The content is DRM-protected
+    #[error("{0}")]
+    Drm(String),
+    /// The API rejected the request for being sent too often
+    #[error("Rate limited by the API{}", retry_after.map(|delay| format!(", retry after {}s", delay.as_secs())).unwrap_or_default())]
+    RateLimited { retry_after: Option<Duration> },
+    /// Any other unsuccessful HTTP response
+    #[error("HTTP error: {status} - {body}")]
+    Http { status: StatusCode, body: String },
+    /// Any other error reported by the API in a successful response
+    #[error("API returned an error: {0}")]
+    Api(String),
+    /// The request couldn't be sent or its response couldn't be read
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("Request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    /// A `protocol::Fetch` implementation couldn't send the request or read its response
+    #[error("Request failed: {0}")]
+    Fetch(String),
+    /// Reading or writing a local file failed
+    #[error("{context}")]
+    Io {
+        context: String,
+        #[source]
+        source: std::io::Error,
+    },
+    /// A response, cookie file or stored file isn't in the expected format
+    #[error("{context}")]
+    Parse {
+        context: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// A setting, argument or input the library can't work with
+    #[error("{0}")]
+    Invalid(String),
+    /// ffmpeg or ffprobe couldn't be started or failed
+    #[error("{0}")]
+    Ffmpeg(String),
+    /// A download stopped receiving data for longer than its stall timeout, or ran past its time limit
+    #[error("{0}")]
+    Stalled(String),
+    /// A download stopped through its `CancellationToken`
+    #[error("Download cancelled")]
+    Cancelled,
+    /// Some items of a batch failed
+    #[error("{failed} of {total} {what} failed")]
+    Partial {
+        failed: usize,
+        total: usize,
+        what: &'static str,
+    },
+    /// A bug: the library panicked during a call through the C interface, which reports it
+    /// instead of unwinding into the host program
+    #[error("Internal error: {0}")]
+    Panicked(String),
+    /// Another error, with what was being done when it happened
+    #[error("{context}")]
+    Context {
+        context: String,
+        #[source]
+        source: Box<Error>,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn io(context: impl Into<String>, source: std::io::Error) -> Self {
+        Error::Io { context: context.into(), source }
+    }
+
+    pub(crate) fn parse(context: impl Into<String>, source: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Error::Parse { context: context.into(), source: source.into() }
+    }
+
+    /// Wraps the error with what was being done when it happened
+    pub(crate) fn context(self, context: impl Into<String>) -> Self {
+        Error::Context { context: context.into(), source: Box::new(self) }
+    }
+
+    /// The error under any `Context` layers, for matching on its kind
+    pub fn without_context(&self) -> &Error {
+        match self {
+            Error::Context { source, .. } => source.without_context(),
+            error => error,
+        }
+    }
+
+    /// Sorts an unsuccessful HTTP response into an error kind by its status and body
+    pub(crate) fn from_response(status: StatusCode, retry_after: Option<Duration>, body: String) -> Self {
+        match status {
+            StatusCode::TOO_MANY_REQUESTS => Error::RateLimited { retry_after },
+            StatusCode::UNAUTHORIZED => Error::Auth(format!("HTTP error: {} - {}", status, body)),
+            StatusCode::NOT_FOUND => Error::NotFound(format!("HTTP error: {} - {}", status, body)),
+            StatusCode::GONE => Error::Unavailable(format!("HTTP error: {} - {}", status, body)),
+            StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS => Error::GeoBlocked(format!("HTTP error: {} - {}", status, body)),
+            // Globo answers 403 both for missing entitlements and for requests from outside Brazil
+            StatusCode::FORBIDDEN => match serde_json::from_str::<crate::models::ApiErrorResponse>(&body) {
+                Ok(api_error) => match Error::from_refusal(api_error.message, api_error.code.as_deref()) {
+                    Error::Api(_) => Error::Auth(format!("HTTP error: {} - {}", status, body)),
+                    error => error,
+                },
+                Err(_) => match Kind::of(&body) {
+                    Some(kind) => kind.error(format!("HTTP error: {} - {}", status, body)),
+                    None => Error::Auth(format!("HTTP error: {} - {}", status, body)),
+                },
+            },
+            // Other statuses are only sorted by a JSON error object's code and message, since any
+            // page (a CDN's HTML, a stack trace) can mention words such as "region" or "license"
+            _ => match serde_json::from_str::<crate::models::ApiErrorResponse>(&body) {
+                Ok(api_error) => match Error::from_refusal(api_error.message, api_error.code.as_deref()) {
+                    Error::Api(_) => Error::Http { status, body },
+                    error => error,
+                },
+                Err(_) => Error::Http { status, body },
+            },
+        }
+    }
+
+    /// Sorts a GraphQL error into an error kind by its code (e.g. "GEO_BLOCKED" or
+    /// "SUBSCRIPTION_REQUIRED")
+    ///
+    /// The message isn't read: GraphQL also reports validation errors, whose wording (a missing
+    /// `$region` variable, a removed field) says nothing about why content was refused.
+    pub(crate) fn from_api_error(message: String, code: Option<&str>) -> Self {
+        match code.and_then(Kind::of) {
+            Some(kind) => kind.error(message),
+            None => Error::Api(message),
+        }
+    }
+
+    /// Sorts a refusal from the playback API (or a 403's error object) into an error kind by its
+    /// code, else by the wording of its message
+    pub(crate) fn from_refusal(message: String, code: Option<&str>) -> Self {
+        match code.and_then(Kind::of).or_else(|| Kind::of(&message)) {
+            Some(kind) => kind.error(message),
+            None => Error::Api(message),
+        }
+    }
+
+    /// True when the request couldn't be sent or its response read, as opposed to being rejected
+    pub fn is_network(&self) -> bool {
+        match self.without_context() {
+            #[cfg(not(target_arch = "wasm32"))]
+            Error::Request(_) => true,
+            Error::Fetch(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Displays an error followed by its causes, separated by colons, e.g. for a one-line report
+pub fn with_causes(error: &(dyn std::error::Error + 'static)) -> String {
+    std::iter::successors(Some(error), |cause| cause.source())
+        .map(|cause| cause.to_string())
+        .collect::<Vec<_>>()
+        .join(": ")
+}
+
+/// Failure kinds recognizable from an API error code or the wording of an error message
+enum Kind {
+    GeoBlocked,
+    Drm,
+    AgeRestricted,
+    Subscription,
+    Auth,
+    Unavailable,
+    NotFound,
+}
+
+impl Kind {
+    /// Matches codes such as "GEO_BLOCKED", "AGE_RESTRICTED" or "CONTENT_EXPIRED" as well as
+    /// English and Portuguese messages; checked in order, so "not available in your country" is
+    /// geo-blocking rather than unavailable content
+    ///
+    /// Only whole words and phrases count, so "geometry" isn't "geo" and "relicensed" isn't "license".
+    fn of(message: &str) -> Option<Kind> {
+        let words: Vec<String> = message
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_string)
+            .collect();
+        let text = format!(" {} ", words.join(" "));
+        let mentions = |phrases: &[&str]| phrases.iter().any(|phrase| text.contains(&format!(" {} ", phrase)));
+        if mentions(&[
+            "geo",
+            "geoblock",
+            "geoblocked",
+            "geoblocking",
+            "georestricted",
+            "geolocation",
+            "geolocalização",
+            "geolocalizacao",
+            "country",
+            "your region",
+            "this region",
+            "your location",
+            "sua região",
+            "sua regiao",
+            "sua localização",
+            "sua localizacao",
+            "territory",
+            "território",
+            "territorio",
+            "outside brazil",
+            "fora do brasil",
+            "fora do país",
+            "fora do pais",
+        ]) {
+            Some(Kind::GeoBlocked)
+        } else if mentions(&["drm", "widevine", "playready", "fairplay", "license", "licence", "licença", "licenca"]) {
+            Some(Kind::Drm)
+        } else if mentions(&[
+            "age restricted",
+            "age restriction",
+            "age rating",
+            "age verification",
+            "parental",
+            "adult",
+            "adults",
+            "classificação",
+            "classificacao",
+            "faixa etária",
+            "faixa etaria",
+        ]) {
+            Some(Kind::AgeRestricted)
+        } else if mentions(&[
+            "subscription",
+            "subscribe",
+            "subscriber",
+            "subscribers",
+            "assinante",
+            "assinantes",
+            "assinatura",
+            "entitlement",
+            "entitled",
+            "plano",
+        ]) {
+            Some(Kind::Subscription)
+        } else if mentions(&["not logged in", "unauthorized", "login", "unauthenticated", "session expired", "token expired", "sessão expirada", "sessao expirada"]) {
+            Some(Kind::Auth)
+        } else if mentions(&[
+            "expired",
+            "expirado",
+            "expirada",
+            "unavailable",
+            "no longer available",
+            "not available",
+            "taken down",
+            "takedown",
+            "removed",
+            "indisponível",
+            "indisponivel",
+            "removido",
+            "removida",
+            "fora do ar",
+        ]) {
+            Some(Kind::Unavailable)
+        } else if mentions(&["not found", "não encontrado", "não encontrada", "nao encontrado", "nao encontrada"]) {
+            Some(Kind::NotFound)
+        } else {
+            None
+        }
+    }
+
+    fn error(self, message: String) -> Error {
+        match self {
+            Kind::GeoBlocked => Error::GeoBlocked(message),
+            Kind::Drm => Error::Drm(message),
+            Kind::AgeRestricted => Error::AgeRestricted(message),
+            Kind::Subscription => Error::SubscriptionRequired(message),
+            Kind::Auth => Error::Auth(message),
+            Kind::Unavailable => Error::Unavailable(message),
+            Kind::NotFound => Error::NotFound(message),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+// src/exit_code.rs
+use crate::error::Error;
+use crate::i18n::Msg;
+
+// Process exit codes, stable so wrapper scripts can branch on the kind of failure.
+// 2 is left to clap, which uses it for invalid command-line usage.
+pub const SUCCESS: u8 = 0;
+pub const GENERIC: u8 = 1;
+pub const AUTH: u8 = 3;
+pub const GEO_BLOCKED: u8 = 4;
+pub const DRM: u8 = 5;
+pub const NOT_FOUND: u8 = 6;
+pub const NETWORK: u8 = 7;
+pub const FFMPEG: u8 = 8;
+pub const PARTIAL_FAILURE: u8 = 9;
+pub const SUBSCRIPTION_REQUIRED: u8 = 10;
+pub const AGE_RESTRICTED: u8 = 11;
+pub const UNAVAILABLE: u8 = 12;
+pub const CANCELLED: u8 = 130; // As for a shell command stopped by Ctrl-C
+
+/// Picks the exit code for an error by looking through its cause chain
+///
+/// Takes any error, so the binary can pass an `anyhow::Error` (`error.as_ref()`) whose context
+/// layers wrap a library `Error`.
+pub fn classify(error: &(dyn std::error::Error + 'static)) -> u8 {
+    let mut chain = std::iter::successors(Some(error), |cause| cause.source());
+    if let Some(library_error) = chain.clone().find_map(|cause| cause.downcast_ref::<Error>()) {
+        return classify_library_error(library_error);
+    }
+    if chain.any(|cause| cause.downcast_ref::<reqwest::Error>().is_some()) {
+        return NETWORK;
+    }
+    GENERIC
+}
+
+/// A short explanation of what an exit code means for the user and what to try next
+pub fn explain(code: u8) -> Option<Msg> {
+    match code {
+        AUTH => Some(Msg::ExplainAuth),
+        GEO_BLOCKED => Some(Msg::ExplainGeoBlocked),
+        DRM => Some(Msg::ExplainDrm),
+        NOT_FOUND => Some(Msg::ExplainNotFound),
+        NETWORK => Some(Msg::ExplainNetwork),
+        FFMPEG => Some(Msg::ExplainFfmpeg),
+        PARTIAL_FAILURE => Some(Msg::ExplainPartialFailure),
+        SUBSCRIPTION_REQUIRED => Some(Msg::ExplainSubscriptionRequired),
+        AGE_RESTRICTED => Some(Msg::ExplainAgeRestricted),
+        UNAVAILABLE => Some(Msg::ExplainUnavailable),
+        _ => None,
+    }
+}
+
+fn classify_library_error(error: &Error) -> u8 {
+    match error.without_context() {
+        Error::Auth(_) => AUTH,
+        Error::SubscriptionRequired(_) => SUBSCRIPTION_REQUIRED,
+        Error::GeoBlocked(_) => GEO_BLOCKED,
+        Error::AgeRestricted(_) => AGE_RESTRICTED,
+        Error::Unavailable(_) => UNAVAILABLE,
+        Error::Drm(_) => DRM,
+        Error::NotFound(_) => NOT_FOUND,
+        Error::Request(_) | Error::Fetch(_) | Error::Stalled(_) => NETWORK,
+        Error::Ffmpeg(_) => FFMPEG,
+        Error::Partial { .. } => PARTIAL_FAILURE,
+        Error::Cancelled => CANCELLED,
+        Error::RateLimited { .. }
+        | Error::Http { .. }
+        | Error::Api(_)
+        | Error::Io { .. }
+        | Error::Parse { .. }
+        | Error::Invalid(_)
+        | Error::Panicked(_)
+        | Error::Context { .. } => GENERIC,
+    }
+}
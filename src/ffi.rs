@@ -0,0 +1,244 @@
+// src/ffi.rs
+//! C interface for embedding the client, e.g. in media-center plugins written in C or C++
+//!
+//! The declarations are in `include/globo_play.h`, generated from this module with
+//! `cbindgen --config cbindgen.toml --output include/globo_play.h`. Every call blocks until it is
+//! done, and a client must only be used by one thread at a time. Strings returned by the library
+//! are released with `globo_play_string_free`; when a call fails, `globo_play_last_error` tells why.
+//!
+//! The shared library is built with
+//! `cargo rustc --release --lib --crate-type cdylib --no-default-features --features ffi,native-tls,ffmpeg`.
+use crate::blocking;
+use crate::client::GloboClient;
+use crate::downloader::{CancellationToken, DownloadJob, DownloaderKind};
+use crate::error::{self, Error, Result};
+use crate::ids::VideoId;
+use crate::progress::ProgressSink;
+use crate::{download, exit_code, utils};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::path::Path;
+use std::ptr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often `globo_play_download` reports progress
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A client created by `globo_play_client_new`
+pub struct GloboPlayClient(blocking::GloboClient);
+
+/// Receives the number of bytes written to the output file so far, on the thread that called
+/// `globo_play_download`
+pub type GloboPlayProgressCallback = Option<unsafe extern "C" fn(bytes_written: u64, user_data: *mut c_void)>;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(error: &Error) {
+    let message = CString::new(error::with_causes(error).replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Runs the body of an entry point, turning a panic into `Error::Panicked`, since unwinding
+/// into C would abort the host program
+fn catch_panic<T>(body: impl FnOnce() -> Result<T>) -> Result<T> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(body)).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        Err(Error::Panicked(message))
+    })
+}
+
+/// Reads a string argument that may be NULL
+unsafe fn optional_str<'a>(value: *const c_char, name: &str) -> Result<Option<&'a str>> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(value)
+        .to_str()
+        .map(Some)
+        .map_err(|_| Error::Invalid(format!("{} is not valid UTF-8", name)))
+}
+
+unsafe fn required_str<'a>(value: *const c_char, name: &str) -> Result<&'a str> {
+    optional_str(value, name)?.ok_or_else(|| Error::Invalid(format!("{} is NULL", name)))
+}
+
+unsafe fn client_ref<'a>(client: *const GloboPlayClient) -> Result<&'a blocking::GloboClient> {
+    client.as_ref().map(|client| &client.0).ok_or_else(|| Error::Invalid("client is NULL".to_string()))
+}
+
+/// Creates a client, or returns NULL on failure
+///
+/// # Arguments
+/// * `cookie_file` - Netscape-format cookie file with the session cookies, or NULL for no session
+/// * `quality` - Quality to pick ("max", "min", a height like "720p" or a bitrate like "2500k"), or NULL for the default
+///
+/// # Safety
+/// The arguments must be NULL or NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn globo_play_client_new(cookie_file: *const c_char, quality: *const c_char) -> *mut GloboPlayClient {
+    let result = catch_panic(|| {
+        let mut builder = GloboClient::builder();
+        if let Some(path) = optional_str(cookie_file, "cookie_file")? {
+            builder = builder.cookie_file(path);
+        }
+        if let Some(quality) = optional_str(quality, "quality")? {
+            builder = builder.quality(quality.parse()?);
+        }
+        builder.build_blocking()
+    });
+    match result {
+        Ok(client) => Box::into_raw(Box::new(GloboPlayClient(client))),
+        Err(e) => {
+            set_last_error(&e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Releases a client
+///
+/// # Safety
+/// `client` must be NULL or a client from `globo_play_client_new` that isn't used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn globo_play_client_free(client: *mut GloboPlayClient) {
+    if !client.is_null() {
+        // Shutting down the client's runtime runs arbitrary drop code
+        let freed = catch_panic(|| {
+            drop(Box::from_raw(client));
+            Ok(())
+        });
+        if let Err(e) = freed {
+            set_last_error(&e);
+        }
+    }
+}
+
+/// Creates a playback session for a video and returns it as JSON, or NULL on failure
+///
+/// The string must be released with `globo_play_string_free`.
+///
+/// # Safety
+/// `client` must come from `globo_play_client_new` and `video_id` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn globo_play_fetch_session_json(client: *const GloboPlayClient, video_id: *const c_char) -> *mut c_char {
+    let result = catch_panic(|| {
+        let client = client_ref(client)?;
+        let video_id: VideoId = required_str(video_id, "video_id")?.parse()?;
+        let session = client.video_session(&video_id)?;
+        let json = serde_json::to_string(&session).map_err(|e| Error::parse("Failed to serialize the session", e))?;
+        CString::new(json).map_err(|e| Error::parse("The session contains a NUL byte", e))
+    });
+    match result {
+        Ok(json) => json.into_raw(),
+        Err(e) => {
+            set_last_error(&e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Downloads a video's best stream for the client's quality to `output_path`
+///
+/// `progress`, if not NULL, is called with `user_data` about twice a second while the download
+/// runs, and once more when it has finished. Builds without ffmpeg save MPEG-TS streams next
+/// to `output_path` with a `.ts` extension instead.
+///
+/// # Returns
+/// 0 on success, otherwise the exit code of the command-line tool for the failure (3 for auth,
+/// 4 geo-blocked, 5 DRM, 6 not found, 7 network, 8 ffmpeg, 10 subscription required, 11 age
+/// restricted, 12 no longer available, 1 anything else)
+///
+/// # Safety
+/// `client` must come from `globo_play_client_new`, `video_id` and `output_path` must be
+/// NUL-terminated strings, and `progress` must be safe to call with `user_data`.
+#[no_mangle]
+pub unsafe extern "C" fn globo_play_download(
+    client: *const GloboPlayClient,
+    video_id: *const c_char,
+    output_path: *const c_char,
+    progress: GloboPlayProgressCallback,
+    user_data: *mut c_void,
+) -> c_int {
+    // The download task only records the progress events; the callback runs on this thread
+    let bytes_written = Arc::new(AtomicU64::new(0));
+    let report = || {
+        if let Some(progress) = progress {
+            progress(bytes_written.load(Ordering::Relaxed), user_data);
+        }
+    };
+    let result = catch_panic(|| {
+        let client = client_ref(client)?;
+        let video_id: VideoId = required_str(video_id, "video_id")?.parse()?;
+        let path = Path::new(required_str(output_path, "output_path")?);
+        let session = client.video_session(&video_id)?;
+        let inner = client.inner();
+        let source = download::select_best_stream(&session.sources, inner.quality(), &HashMap::new())
+            .ok_or_else(|| Error::NotFound(format!("No suitable stream found for video {}", video_id)))?;
+        let downloader = DownloaderKind::default().downloader()?;
+        let cancel = CancellationToken::new();
+        let sink = {
+            let bytes_written = bytes_written.clone();
+            ProgressSink::new(move |event| bytes_written.store(event.bytes, Ordering::Relaxed))
+        };
+        let job = DownloadJob {
+            url: &source.url,
+            path,
+            audio_tracks: &[],
+            quality: inner.quality(),
+            input_options: &[],
+            cancel: &cancel,
+            progress: Some(&sink),
+            stall_timeout: None,
+            timeout: None,
+            precheck: true,
+        };
+        client.wait(async {
+            let download = utils::download_file(inner.http(), downloader.as_ref(), job);
+            tokio::pin!(download);
+            let mut ticks = tokio::time::interval(PROGRESS_INTERVAL);
+            loop {
+                tokio::select! {
+                    result = &mut download => return result,
+                    _ = ticks.tick() => report(),
+                }
+            }
+        })?;
+        report();
+        Ok(())
+    });
+    match result {
+        Ok(()) => exit_code::SUCCESS as c_int,
+        Err(e) => {
+            set_last_error(&e);
+            exit_code::classify(&e) as c_int
+        }
+    }
+}
+
+/// Releases a string returned by the library
+///
+/// # Safety
+/// `value` must be NULL or a string returned by this library that isn't used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn globo_play_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+/// Describes the last failure on the calling thread, or returns NULL if nothing failed yet
+///
+/// The string is owned by the library and stays valid until the next failure on the same thread.
+#[no_mangle]
+pub extern "C" fn globo_play_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
@@ -0,0 +1,216 @@
+// src/filename.rs
+//! File names built from titles: keeps accents and readable punctuation, and makes the result
+//! valid on Linux, macOS and Windows (invalid characters, reserved names, length limits)
+use crate::constants;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use unicode_normalization::UnicodeNormalization;
+
+/// How spaces in titles appear in file names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum SpaceStyle {
+    /// `Jornal Nacional - Edição de 12-03.mp4`
+    #[default]
+    Keep,
+    /// `Jornal_Nacional-Edição_de_12-03.mp4`
+    Underscore,
+    /// `Jornal-Nacional-Edição-de-12-03.mp4`
+    Dash,
+}
+
+/// Device names Windows won't open as files, even with an extension
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1", "LPT2", "LPT3",
+    "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Cleans a title or other value for use inside a file name
+///
+/// Accents are kept (composed, so the same title always gives the same bytes), characters that
+/// aren't allowed in file names on some system are replaced by a close equivalent (`:` and `/`
+/// by `-`, `"` by `'`) or dropped (`?`, `*`, `<`, `>`), invisible characters are removed and
+/// runs of whitespace become one space, which is then written as `spaces` asks.
+pub fn sanitize_value(value: &str, spaces: SpaceStyle) -> String {
+    let mut cleaned = String::with_capacity(value.len());
+    let mut chars = value.nfc().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            // "Jornal: Edição" reads as "Jornal - Edição", while "12:30" becomes "12-30"
+            ':' if chars.peek().is_some_and(|next| next.is_whitespace()) => cleaned.push_str(" -"),
+            ':' | '/' | '\\' | '|' => cleaned.push('-'),
+            '"' => cleaned.push('\''),
+            '?' | '*' | '<' | '>' => {}
+            '\u{00AD}' | '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}' => {}
+            c if c.is_whitespace() || c.is_control() => cleaned.push(' '),
+            c => cleaned.push(c),
+        }
+    }
+    let words: Vec<&str> = cleaned.split(' ').filter(|word| !word.is_empty()).collect();
+    let joined = words.join(" ");
+    let joined = joined.trim_start_matches('.');
+    match spaces {
+        SpaceStyle::Keep => joined.to_string(),
+        SpaceStyle::Underscore => collapse_separators(&joined.replace(" - ", "-").replace(' ', "_")),
+        SpaceStyle::Dash => collapse_separators(&joined.replace(' ', "-")),
+    }
+}
+
+/// Replaces runs of `-` and `_` such as `_-_` or `---` by their first `-`, or `_` if there is none
+fn collapse_separators(name: &str) -> String {
+    let mut collapsed = String::with_capacity(name.len());
+    let mut run = String::new();
+    for c in name.chars() {
+        if c == '-' || c == '_' {
+            run.push(c);
+            continue;
+        }
+        if !run.is_empty() {
+            collapsed.push(if run.contains('-') { '-' } else { '_' });
+            run.clear();
+        }
+        collapsed.push(c);
+    }
+    if !run.is_empty() {
+        collapsed.push(if run.contains('-') { '-' } else { '_' });
+    }
+    collapsed
+}
+
+/// Makes a complete path component safe to create: drops the trailing dots and spaces Windows
+/// strips, renames Windows device names (`CON.mp4` becomes `CON_.mp4`) and shortens it to
+/// `constants::MAX_FILENAME_BYTES`, keeping its extension
+pub fn finish_component(component: &str) -> String {
+    let component = component.trim_end_matches(['.', ' ']);
+    if component.is_empty() {
+        return "_".to_string();
+    }
+    let (stem, extension) = split_extension(component);
+    // Windows also reserves the device names followed by any extension, e.g. `CON.2024`
+    let device = stem.split('.').next().unwrap_or(stem);
+    let stem = if RESERVED_NAMES.iter().any(|name| device.eq_ignore_ascii_case(name)) {
+        format!("{}_{}", device, &stem[device.len()..])
+    } else {
+        stem.to_string()
+    };
+    let suffix = extension.map(|extension| format!(".{}", extension)).unwrap_or_default();
+    let limit = constants::MAX_FILENAME_BYTES.saturating_sub(suffix.len());
+    if stem.len() <= limit {
+        return format!("{}{}", stem, suffix);
+    }
+    format!("{}{}", truncate(&stem, limit, char::len_utf8), suffix)
+}
+
+/// Shortens the file name of `path` so the whole path, made absolute, is at most `max_len` long
+/// (see `constants::MAX_PATH_LEN`), keeping its extension
+///
+/// Length is counted the way the platform limits it: in UTF-16 units on Windows, bytes elsewhere.
+/// Only the file name is shortened; a path whose directories alone are too long keeps a one-character name.
+pub fn fit_path(path: &Path, max_len: usize) -> PathBuf {
+    let units: fn(char) -> usize = if cfg!(windows) { char::len_utf16 } else { char::len_utf8 };
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let len: usize = absolute.to_string_lossy().chars().map(units).sum();
+    let Some(name) = path.file_name().and_then(|name| name.to_str()).filter(|_| len > max_len) else {
+        return path.to_path_buf();
+    };
+    let (stem, extension) = split_extension(name);
+    let stem_len: usize = stem.chars().map(units).sum();
+    let stem = match truncate(stem, stem_len.saturating_sub(len - max_len), units) {
+        "" => stem.chars().next().map_or_else(|| "_".to_string(), String::from),
+        stem => stem.to_string(),
+    };
+    path.with_file_name(match extension {
+        Some(extension) => format!("{}.{}", stem, extension),
+        None => stem,
+    })
+}
+
+/// Cuts `stem` at a character boundary to at most `limit` units as counted by `units`, dropping
+/// the separators the cut leaves at the end
+fn truncate(stem: &str, limit: usize, units: fn(char) -> usize) -> &str {
+    let mut used = 0;
+    let mut end = 0;
+    for (index, c) in stem.char_indices() {
+        used += units(c);
+        if used > limit {
+            break;
+        }
+        end = index + c.len_utf8();
+    }
+    stem[..end].trim_end_matches(['.', ' ', '-', '_'])
+}
+
+/// Splits a file extension of up to five letters and digits off `name`
+fn split_extension(name: &str) -> (&str, Option<&str>) {
+    match name.rsplit_once('.') {
+        Some((stem, extension))
+            if !stem.is_empty() && (1..=5).contains(&extension.len()) && extension.chars().all(|c| c.is_ascii_alphanumeric()) =>
+        {
+            (stem, Some(extension))
+        }
+        _ => (name, None),
+    }
+}
+
+/// A file or directory name for `name`: `sanitize_value` followed by `finish_component`
+///
+/// # Arguments
+/// * `name` - Title or other text to name the file after
+/// * `extension` - Extension without the leading dot, kept when the name is shortened
+/// * `spaces` - How spaces are written
+///
+/// # Returns
+/// A name such as `Jornal Nacional - Edição de 12-03.mp4`
+pub fn sanitize_filename(name: &str, extension: Option<&str>, spaces: SpaceStyle) -> String {
+    let name = sanitize_value(name, spaces);
+    let stem = name.trim_end_matches(['.', ' ']);
+    match extension {
+        Some(extension) => finish_component(&format!("{}.{}", if stem.is_empty() { "_" } else { stem }, extension)),
+        None => finish_component(stem),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renames_windows_device_names() {
+        assert_eq!(finish_component("CON.mp4"), "CON_.mp4");
+        assert_eq!(finish_component("lpt1.2024.mp4"), "lpt1_.2024.mp4");
+        assert_eq!(finish_component("Console.mp4"), "Console.mp4");
+        assert_eq!(sanitize_filename("nul", Some("mp4"), SpaceStyle::Keep), "nul_.mp4");
+    }
+
+    #[test]
+    fn truncates_multibyte_names_at_a_character_boundary() {
+        let name = format!("{}.mp4", "Ação ".repeat(60));
+        let finished = finish_component(&name);
+        assert!(finished.len() <= constants::MAX_FILENAME_BYTES);
+        assert!(finished.ends_with("ção.mp4"), "{}", finished);
+        assert!(finished.starts_with("Ação Ação"));
+    }
+
+    #[test]
+    fn writes_spaces_in_each_style() {
+        let title = "Jornal Nacional: Edição de 12/03";
+        assert_eq!(sanitize_filename(title, Some("mp4"), SpaceStyle::Keep), "Jornal Nacional - Edição de 12-03.mp4");
+        assert_eq!(sanitize_filename(title, Some("mp4"), SpaceStyle::Underscore), "Jornal_Nacional-Edição_de_12-03.mp4");
+        assert_eq!(sanitize_filename(title, Some("mp4"), SpaceStyle::Dash), "Jornal-Nacional-Edição-de-12-03.mp4");
+    }
+
+    #[test]
+    fn shortens_the_file_name_to_fit_the_whole_path() {
+        let dir = std::env::temp_dir().join("a".repeat(150));
+        let path = dir.join(format!("{}.mp4", "Jornal Nacional ".repeat(8)));
+        let fitted = fit_path(&path, 260);
+        assert_eq!(fitted.parent(), Some(dir.as_path()));
+        assert!(fitted.to_string_lossy().len() <= 260, "{}", fitted.display());
+        let name = fitted.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with("Jornal Nacional Jornal") && name.ends_with(".mp4") && !name.ends_with(" .mp4"), "{}", name);
+
+        let short = dir.join("Short.mp4");
+        assert_eq!(fit_path(&short, 260), short);
+    }
+}
@@ -0,0 +1,548 @@
+// src/hls.rs
+
+use crate::config::AppConfig;
+use crate::models::Source;
+use crate::quality::Quality;
+use crate::error::{Error, Result};
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A single variant stream (`#EXT-X-STREAM-INF`) of an HLS master playlist
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Variant {
+    pub uri: String,
+    pub bandwidth: Option<u64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub codecs: Option<String>,
+    pub audio_group: Option<String>,
+}
+
+/// An alternative audio rendition (`#EXT-X-MEDIA:TYPE=AUDIO`), e.g. dubbed and original audio
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AudioRendition {
+    pub group_id: String,
+    pub language: Option<String>,
+    pub name: Option<String>,
+    pub default: bool,
+    pub uri: Option<String>,
+}
+
+impl AudioRendition {
+    /// Returns true if this rendition matches a `--audio-lang` value (`pt`, `en`, `original`, ...)
+    pub fn matches(&self, wanted: &str) -> bool {
+        if wanted.eq_ignore_ascii_case("original") {
+            return self
+                .name
+                .as_deref()
+                .is_some_and(|n| n.to_lowercase().contains("original"));
+        }
+        self.language.as_deref().is_some_and(|lang| {
+            let lang = lang.to_lowercase();
+            let wanted = wanted.to_lowercase();
+            lang == wanted || lang.starts_with(&format!("{}-", wanted))
+        })
+    }
+}
+
+/// Variant table parsed from an HLS master playlist
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct MasterPlaylist {
+    pub variants: Vec<Variant>,
+    #[serde(default)]
+    pub audio: Vec<AudioRendition>,
+}
+
+impl MasterPlaylist {
+    /// Height of the best variant, used as the resolution of the whole source
+    pub fn max_height(&self) -> Option<u32> {
+        self.variants.iter().filter_map(|v| v.height).max()
+    }
+
+    /// Distinct language tags of the audio renditions, in playlist order
+    pub fn audio_languages(&self) -> Vec<String> {
+        let mut languages: Vec<String> = Vec::new();
+        for rendition in &self.audio {
+            let tag = rendition
+                .language
+                .clone()
+                .or_else(|| rendition.name.clone())
+                .unwrap_or_else(|| rendition.group_id.clone());
+            if !languages.contains(&tag) {
+                languages.push(tag);
+            }
+        }
+        languages
+    }
+
+    /// Picks the video variant for `quality`, e.g. to mux with separate audio renditions
+    ///
+    /// A height or bitrate picks the best variant not above it, or the lowest one if all are.
+    pub fn select_variant(&self, quality: Quality) -> Option<&Variant> {
+        let by_bandwidth = |v: &&Variant| v.bandwidth.unwrap_or(0);
+        let lowest = || self.variants.iter().min_by_key(by_bandwidth);
+        match quality {
+            Quality::Max => self.variants.iter().max_by_key(by_bandwidth),
+            Quality::Min => lowest(),
+            Quality::Height(height) => self
+                .variants
+                .iter()
+                .filter(|v| v.height.is_some_and(|h| h <= height))
+                .max_by_key(|v| (v.height, v.bandwidth))
+                .or_else(lowest),
+            Quality::Bitrate(kbps) => self
+                .variants
+                .iter()
+                .filter(|v| v.bandwidth.is_some_and(|b| b <= u64::from(kbps) * 1000))
+                .max_by_key(by_bandwidth)
+                .or_else(lowest),
+        }
+    }
+
+    /// Selects one audio rendition per requested language
+    ///
+    /// # Returns
+    /// The matched renditions (with a URI) and the requested languages that had no match
+    pub fn select_audio(&self, wanted: &[String]) -> (Vec<&AudioRendition>, Vec<String>) {
+        let mut selected: Vec<&AudioRendition> = Vec::new();
+        let mut missing = Vec::new();
+        for lang in wanted {
+            match self.audio.iter().find(|r| r.uri.is_some() && r.matches(lang)) {
+                Some(rendition) => {
+                    if !selected.iter().any(|s| s.uri == rendition.uri) {
+                        selected.push(rendition);
+                    }
+                }
+                None => missing.push(lang.clone()),
+            }
+        }
+        (selected, missing)
+    }
+}
+
+/// Splits an attribute list like `BANDWIDTH=1280000,CODECS="avc1,mp4a"` into key/value pairs
+pub fn parse_attributes(list: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut key = String::new();
+    let mut value = String::new();
+    let mut in_value = false;
+    let mut in_quotes = false;
+
+    for c in list.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '=' if !in_value && !in_quotes => in_value = true,
+            ',' if !in_quotes => {
+                if !key.is_empty() {
+                    attributes.insert(key.trim().to_string(), value.clone());
+                }
+                key.clear();
+                value.clear();
+                in_value = false;
+            }
+            c if in_value => value.push(c),
+            c => key.push(c),
+        }
+    }
+    if !key.is_empty() {
+        attributes.insert(key.trim().to_string(), value);
+    }
+    attributes
+}
+
+/// Resolves a possibly relative playlist URI against the playlist URL
+pub fn resolve_uri(base_url: &str, uri: &str) -> String {
+    reqwest::Url::parse(base_url)
+        .and_then(|base| base.join(uri))
+        .map(|u| u.to_string())
+        .unwrap_or_else(|_| uri.to_string())
+}
+
+/// Parses the variant table of an HLS master playlist
+///
+/// # Arguments
+/// * `content` - The playlist text
+/// * `base_url` - URL the playlist was fetched from, used to resolve relative variant URIs
+pub fn parse_master_playlist(content: &str, base_url: &str) -> MasterPlaylist {
+    let mut variants = Vec::new();
+    let mut audio = Vec::new();
+    let mut pending: Option<HashMap<String, String>> = None;
+
+    for line in content.lines().map(str::trim) {
+        if let Some(attrs) = line.strip_prefix("#EXT-X-MEDIA:") {
+            let attrs = parse_attributes(attrs);
+            if attrs.get("TYPE").map(String::as_str) == Some("AUDIO") {
+                audio.push(AudioRendition {
+                    group_id: attrs.get("GROUP-ID").cloned().unwrap_or_default(),
+                    language: attrs.get("LANGUAGE").cloned(),
+                    name: attrs.get("NAME").cloned(),
+                    default: attrs.get("DEFAULT").map(String::as_str) == Some("YES"),
+                    uri: attrs.get("URI").map(|uri| resolve_uri(base_url, uri)),
+                });
+            }
+        } else if let Some(attrs) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+            pending = Some(parse_attributes(attrs));
+        } else if !line.is_empty() && !line.starts_with('#') {
+            if let Some(attrs) = pending.take() {
+                let (width, height) = attrs
+                    .get("RESOLUTION")
+                    .and_then(|r| r.split_once('x'))
+                    .map(|(w, h)| (w.parse().ok(), h.parse().ok()))
+                    .unwrap_or((None, None));
+                variants.push(Variant {
+                    uri: resolve_uri(base_url, line),
+                    bandwidth: attrs.get("BANDWIDTH").and_then(|b| b.parse().ok()),
+                    width,
+                    height,
+                    codecs: attrs.get("CODECS").cloned(),
+                    audio_group: attrs.get("AUDIO").cloned(),
+                });
+            }
+        }
+    }
+
+    MasterPlaylist { variants, audio }
+}
+
+/// Segment list parsed from an HLS media playlist
+#[derive(Debug, Clone, Default)]
+pub struct MediaPlaylist {
+    /// Initialization section (`#EXT-X-MAP`) of fragmented MP4 streams
+    pub init_segment: Option<String>,
+    pub segments: Vec<String>,
+    /// Encryption method (`#EXT-X-KEY`), e.g. `AES-128`, when segments are encrypted
+    pub encryption: Option<String>,
+}
+
+/// Parses the segment list of an HLS media playlist
+///
+/// # Arguments
+/// * `content` - The playlist text
+/// * `base_url` - URL the playlist was fetched from, used to resolve relative segment URIs
+pub fn parse_media_playlist(content: &str, base_url: &str) -> MediaPlaylist {
+    let mut playlist = MediaPlaylist::default();
+    for line in content.lines().map(str::trim) {
+        if let Some(attrs) = line.strip_prefix("#EXT-X-KEY:") {
+            let method = parse_attributes(attrs).remove("METHOD").unwrap_or_default();
+            playlist.encryption = (method != "NONE").then_some(method);
+        } else if let Some(attrs) = line.strip_prefix("#EXT-X-MAP:") {
+            playlist.init_segment = parse_attributes(attrs).get("URI").map(|uri| resolve_uri(base_url, uri));
+        } else if !line.is_empty() && !line.starts_with('#') {
+            playlist.segments.push(resolve_uri(base_url, line));
+        }
+    }
+    playlist
+}
+
+/// Downloads an HLS stream by fetching its segments into `path`, without ffmpeg
+///
+/// Master playlists are resolved to the variant `quality` picks (see
+/// `MasterPlaylist::select_variant`). The segments are written as they are, with a `.part`
+/// suffix that is removed once every segment has arrived. Fragmented MP4 goes to `path`; MPEG-TS,
+/// which isn't remuxed, goes to `path` with a `.ts` extension, so it isn't mistaken for an MP4.
+/// Encrypted segments and separate audio renditions need the ffmpeg backend.
+///
+/// # Returns
+/// The file written
+///
+/// Up to `constants::SEGMENT_FETCH_CONCURRENCY` segments are fetched at once over the client's
+/// connection pool. A segment that fails or receives no data for `stall_timeout` is fetched
+/// again, as often as the client's retry policy allows.
+#[cfg(feature = "native-downloader")]
+pub async fn download_stream(
+    client: &crate::net::HttpClient,
+    url: &str,
+    path: &std::path::Path,
+    quality: Quality,
+    cancel: &tokio_util::sync::CancellationToken,
+    progress: Option<&crate::progress::ProgressSink>,
+    stall_timeout: Option<std::time::Duration>,
+) -> Result<PathBuf> {
+    use futures_util::StreamExt;
+    use tokio::io::AsyncWriteExt;
+
+    let mut playlist_url = url.to_string();
+    let mut content = fetch_playlist(client, &playlist_url).await?;
+    if !content.trim_start().starts_with("#EXTM3U") {
+        return Err(Error::Invalid(format!("{} is not an HLS playlist; the native downloader only handles HLS streams", url)));
+    }
+    if content.contains("#EXT-X-STREAM-INF") {
+        let master = parse_master_playlist(&content, &playlist_url);
+        let variant = master
+            .select_variant(quality)
+            .ok_or_else(|| Error::Invalid(format!("No variants in HLS playlist {}", url)))?;
+        if variant.audio_group.is_some() && master.audio.iter().any(|rendition| rendition.uri.is_some()) {
+            return Err(Error::Invalid(format!(
+                "{} has separate audio renditions, which the native downloader can't mux; use the ffmpeg backend",
+                url
+            )));
+        }
+        playlist_url = variant.uri.clone();
+        content = fetch_playlist(client, &playlist_url).await?;
+    }
+    let playlist = parse_media_playlist(&content, &playlist_url);
+    if let Some(method) = &playlist.encryption {
+        return Err(Error::Invalid(format!(
+            "{} has {} encrypted segments, which the native downloader can't decrypt; use the ffmpeg backend",
+            url, method
+        )));
+    }
+    if playlist.segments.is_empty() {
+        return Err(Error::Invalid(format!("No segments in HLS playlist {}", playlist_url)));
+    }
+
+    // Segments without an EXT-X-MAP initialization section are MPEG-TS
+    let path = if playlist.init_segment.is_none() && !path.extension().is_some_and(|e| e.eq_ignore_ascii_case("ts")) {
+        path.with_extension("ts")
+    } else {
+        path.to_path_buf()
+    };
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".part");
+    let partial = PathBuf::from(partial);
+    let mut file = tokio::fs::File::create(&partial)
+        .await
+        .map_err(|e| Error::io(format!("Failed to create {}", partial.display()), e))?;
+    let segments: Vec<&String> = playlist.init_segment.iter().chain(&playlist.segments).collect();
+    let mut meter = crate::progress::Meter::new(progress);
+    meter.set_segments_total(segments.len());
+    // A few segments are fetched at once over the client's shared connections; they are written
+    // in playlist order as each one completes
+    let urls: Vec<(usize, String)> = segments.iter().map(|segment| segment.to_string()).enumerate().collect();
+    let mut fetches = futures_util::stream::iter(urls)
+        .map(|(index, segment)| async move { fetch_segment_with_retries(client, &segment, index, cancel, stall_timeout).await })
+        .buffered(crate::constants::SEGMENT_FETCH_CONCURRENCY);
+    let mut index = 0;
+    while let Some(result) = fetches.next().await {
+        let segment = segments[index];
+        index += 1;
+        // A segment is only written once it has arrived whole, so a retry doesn't leave partial data behind
+        let result = match result {
+            Ok(bytes) => file.write_all(&bytes).await.map(|_| meter.add_bytes(bytes.len() as u64)).map_err(|e| Error::io(format!("Failed to write {}", partial.display()), e)),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            drop(fetches);
+            let _ = tokio::fs::remove_file(&partial).await;
+            if matches!(e, Error::Cancelled) {
+                crate::output::info!("Cancelled the download of {}", url);
+                return Err(e);
+            }
+            return Err(e.context(format!("Failed to download segment {} of {}: {}", index, segments.len(), segment)));
+        }
+        crate::output::verbose!("Downloaded segment {}/{}", index, segments.len());
+        meter.segment_done();
+    }
+    drop(fetches);
+    drop(meter);
+    file.flush().await.map_err(|e| Error::io(format!("Failed to write {}", partial.display()), e))?;
+    drop(file);
+    tokio::fs::rename(&partial, &path)
+        .await
+        .map_err(|e| Error::io(format!("Failed to move {} to {}", partial.display(), path.display()), e))?;
+    crate::output::info!("Downloaded {} segments of {} to {}", segments.len(), url, path.display());
+    Ok(path)
+}
+
+/// Fetches a media segment, again after failures as often as the client's retry policy allows,
+/// unless `cancel` stops it
+#[cfg(feature = "native-downloader")]
+async fn fetch_segment_with_retries(
+    client: &crate::net::HttpClient,
+    url: &str,
+    index: usize,
+    cancel: &tokio_util::sync::CancellationToken,
+    stall_timeout: Option<std::time::Duration>,
+) -> Result<Vec<u8>> {
+    let retry = client.retry_policy();
+    let mut attempt = 0;
+    loop {
+        let result = tokio::select! {
+            result = fetch_segment(client, url, stall_timeout) => result,
+            _ = cancel.cancelled() => Err(Error::Cancelled),
+        };
+        match result {
+            Err(e) if attempt < retry.retries && !matches!(e, Error::Cancelled) => {
+                let delay = retry.delay(attempt);
+                attempt += 1;
+                crate::output::verbose!("Retry {}/{} of segment {} in {:?}: {}", attempt, retry.retries, index + 1, delay, crate::error::with_causes(&e));
+                tokio::time::sleep(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Fetches a media segment, failing with `Error::Stalled` if no data arrives for `stall_timeout`
+#[cfg(feature = "native-downloader")]
+async fn fetch_segment(client: &crate::net::HttpClient, url: &str, stall_timeout: Option<std::time::Duration>) -> Result<Vec<u8>> {
+    let mut response = client.get(url).send().await?.error_for_status()?;
+    let mut bytes = Vec::new();
+    loop {
+        let chunk = match stall_timeout {
+            Some(limit) => tokio::time::timeout(limit, response.chunk()).await.map_err(|_| {
+                Error::Stalled(format!("No data for {}s from {}", limit.as_secs(), url))
+            })??,
+            None => response.chunk().await?,
+        };
+        match chunk {
+            Some(chunk) => bytes.extend_from_slice(&chunk),
+            None => return Ok(bytes),
+        }
+    }
+}
+
+#[cfg(feature = "native-downloader")]
+async fn fetch_playlist(client: &crate::net::HttpClient, url: &str) -> Result<String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::from(e).context(format!("Failed to fetch playlist {}", url)))?;
+    Ok(response.text().await?)
+}
+
+/// Per-run cache of parsed master playlists keyed by source URL, optionally backed by a short-lived disk cache
+#[derive(Debug, Default)]
+pub struct VariantCache {
+    entries: Mutex<HashMap<String, Arc<MasterPlaylist>>>,
+    disk_ttl: Option<Duration>,
+}
+
+impl VariantCache {
+    /// Creates a cache; a non-zero `disk_ttl_secs` also persists entries to the cache directory for that long
+    pub fn new(disk_ttl_secs: u64) -> Self {
+        VariantCache {
+            entries: Mutex::new(HashMap::new()),
+            disk_ttl: (disk_ttl_secs > 0).then(|| Duration::from_secs(disk_ttl_secs)),
+        }
+    }
+
+    fn disk_path(url: &str) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        url.hash(&mut hasher);
+        crate::paths::cache_dir()
+            .join("manifests")
+            .join(format!("{:016x}.json", hasher.finish()))
+    }
+
+    fn read_disk(&self, url: &str) -> Option<MasterPlaylist> {
+        let ttl = self.disk_ttl?;
+        let path = Self::disk_path(url);
+        let age = std::fs::metadata(&path).ok()?.modified().ok()?.elapsed().ok()?;
+        if age > ttl {
+            return None;
+        }
+        let content = std::fs::read_to_string(&path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn write_disk(&self, url: &str, playlist: &MasterPlaylist) -> Result<()> {
+        if self.disk_ttl.is_none() {
+            return Ok(());
+        }
+        let path = Self::disk_path(url);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| Error::io(format!("Failed to create {}", parent.display()), e))?;
+        }
+        let content = serde_json::to_string(playlist).map_err(|e| Error::parse("Failed to serialize the manifest", e))?;
+        std::fs::write(&path, content).map_err(|e| Error::io(format!("Failed to write {}", path.display()), e))?;
+        Ok(())
+    }
+
+    /// Returns the parsed master playlist for `url`, fetching and parsing it only on a cache miss
+    pub async fn get_or_fetch(&self, url: &str, config: &AppConfig) -> Result<Arc<MasterPlaylist>> {
+        if let Some(hit) = self.entries.lock().unwrap().get(url) {
+            return Ok(hit.clone());
+        }
+
+        let playlist = match self.read_disk(url) {
+            Some(playlist) => playlist,
+            None => {
+                let body = config
+                    .client
+                    .http()
+                    .get(url)
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map_err(|e| Error::from(e).context(format!("Failed to fetch manifest {}", url)))?
+                    .text()
+                    .await?;
+                let playlist = parse_master_playlist(&body, url);
+                if let Err(e) = self.write_disk(url, &playlist) {
+                    tracing::debug!(%url, error = %e, "could not write the manifest cache");
+                }
+                playlist
+            }
+        };
+
+        let playlist = Arc::new(playlist);
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(url.to_string(), playlist.clone());
+        Ok(playlist)
+    }
+}
+
+/// Looks up the resolution of every HLS source in parallel, keyed by source URL
+///
+/// Sources whose manifest can't be fetched are left out of the map and reported as warnings.
+pub async fn lookup_resolutions(sources: &[&Source], config: &AppConfig) -> (HashMap<String, u32>, Vec<String>) {
+    let lookups = sources
+        .iter()
+        .filter(|s| s.url.contains(".m3u8"))
+        .map(|s| async move {
+            config
+                .manifest_cache
+                .get_or_fetch(&s.url, config)
+                .await
+                .map(|playlist| (s.url.clone(), playlist.max_height()))
+        });
+
+    let mut resolutions = HashMap::new();
+    let mut warnings = Vec::new();
+    for result in join_all(lookups).await {
+        match result {
+            Ok((url, Some(height))) => {
+                resolutions.insert(url, height);
+            }
+            Ok((url, None)) => warnings.push(format!("manifest {} lists no variant resolutions", url)),
+            Err(e) => warnings.push(format!("manifest lookup failed: {:#}", e)),
+        }
+    }
+    (resolutions, warnings)
+}
+
+/// Fetches the master playlist of every HLS source in parallel, in source order
+///
+/// Non-HLS sources and manifests that can't be fetched yield `None`.
+pub async fn fetch_playlists(sources: &[Source], config: &AppConfig) -> Vec<Option<Arc<MasterPlaylist>>> {
+    let lookups = sources.iter().map(|s| async move {
+        if !s.url.contains(".m3u8") {
+            return None;
+        }
+        config.manifest_cache.get_or_fetch(&s.url, config).await.ok()
+    });
+    join_all(lookups).await
+}
+
+/// Fills `Source::audio_languages` from the audio renditions of each HLS source's manifest
+///
+/// Manifests that can't be fetched leave the source untouched.
+pub async fn annotate_audio_languages(sources: &mut [Source], config: &AppConfig) {
+    let playlists = fetch_playlists(sources, config).await;
+    for (source, playlist) in sources.iter_mut().zip(playlists) {
+        if let Some(playlist) = playlist {
+            source.audio_languages = playlist.audio_languages();
+        }
+    }
+}
@@ -0,0 +1,168 @@
+// src/i18n.rs
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Language of user-facing messages (`--lang`)
+///
+/// Machine-readable output (JSON, CSV, webhook payloads) always stays in English.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Lang {
+    /// English
+    En,
+    /// Brazilian Portuguese
+    #[cfg_attr(feature = "cli", value(name = "pt-BR", alias = "pt"))]
+    PtBr,
+}
+
+static LANG: AtomicU8 = AtomicU8::new(Lang::En as u8);
+
+/// Sets the message language once at startup: `--lang` if given, otherwise the locale
+/// environment (`LC_ALL`, `LC_MESSAGES`, `LANG`), falling back to English
+pub fn init(lang: Option<Lang>) {
+    let lang = lang.unwrap_or_else(detect);
+    LANG.store(lang as u8, Ordering::Relaxed);
+}
+
+/// Returns the current message language
+pub fn lang() -> Lang {
+    if LANG.load(Ordering::Relaxed) == Lang::PtBr as u8 {
+        Lang::PtBr
+    } else {
+        Lang::En
+    }
+}
+
+/// Picks the language from the first non-empty locale variable, e.g. `pt_BR.UTF-8`
+fn detect() -> Lang {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty());
+    match locale {
+        Some(locale) if locale.to_lowercase().starts_with("pt") => Lang::PtBr,
+        _ => Lang::En,
+    }
+}
+
+/// Translatable user-facing messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    Banner,
+    Warning,
+    Error,
+    Hint,
+    // Item states in compact reports
+    Fetched,
+    Downloaded,
+    Skipped,
+    Failed,
+    // Batch summary counters
+    BatchReport,
+    CountDownloaded,
+    CountSkipped,
+    CountFailed,
+    CountWarnings,
+    AggregatedTimings,
+    Timings,
+    // Desktop notifications
+    DownloadComplete,
+    DownloadFailed,
+    BatchComplete,
+    BatchCompleteWithFailures,
+    // Interactive prompts
+    ChooseStream,
+    ChooseFeed,
+    ChooseEpisode,
+    SelectionCancelled,
+    NeedsTerminal,
+    // Explanations of exit codes
+    ExplainAuth,
+    ExplainGeoBlocked,
+    ExplainDrm,
+    ExplainNotFound,
+    ExplainNetwork,
+    ExplainFfmpeg,
+    ExplainPartialFailure,
+    ExplainSubscriptionRequired,
+    ExplainAgeRestricted,
+    ExplainUnavailable,
+}
+
+impl Msg {
+    /// The message in the current language
+    pub fn text(self) -> &'static str {
+        let (en, pt) = match self {
+            Msg::Banner => ("Command-line utility", "Utilitário de linha de comando"),
+            Msg::Warning => ("Warning:", "Aviso:"),
+            Msg::Error => ("Error:", "Erro:"),
+            Msg::Hint => ("Hint:", "Dica:"),
+            Msg::Fetched => ("fetched", "obtido"),
+            Msg::Downloaded => ("downloaded", "baixado"),
+            Msg::Skipped => ("skipped", "ignorado"),
+            Msg::Failed => ("failed", "falhou"),
+            Msg::BatchReport => ("Batch report", "Relatório do lote"),
+            Msg::CountDownloaded => ("downloaded", "baixados"),
+            Msg::CountSkipped => ("skipped", "ignorados"),
+            Msg::CountFailed => ("failed", "com falha"),
+            Msg::CountWarnings => ("warnings", "avisos"),
+            Msg::AggregatedTimings => ("Aggregated timings", "Tempos agregados"),
+            Msg::Timings => ("timings", "tempos"),
+            Msg::DownloadComplete => ("Download complete", "Download concluído"),
+            Msg::DownloadFailed => ("Download failed", "Falha no download"),
+            Msg::BatchComplete => ("Batch download complete", "Downloads em lote concluídos"),
+            Msg::BatchCompleteWithFailures => (
+                "Batch download finished with failures",
+                "Downloads em lote concluídos com falhas",
+            ),
+            Msg::ChooseStream => ("Choose the stream to download", "Escolha o stream para baixar"),
+            Msg::ChooseFeed => ("Choose the feed", "Escolha o sinal"),
+            Msg::ChooseEpisode => ("Several videos share this episode number", "Vários vídeos têm este número de episódio"),
+            Msg::SelectionCancelled => ("Selection cancelled", "Seleção cancelada"),
+            Msg::NeedsTerminal => ("--interactive needs a terminal to ask", "--interactive precisa de um terminal para perguntar"),
+            Msg::ExplainAuth => (
+                "Globo Play refused the session. Export fresh cookies from a logged-in browser and pass them with --cookie or store them with `login`.",
+                "O Globo Play recusou a sessão. Exporte cookies atualizados de um navegador logado e passe-os com --cookie ou guarde-os com `login`.",
+            ),
+            Msg::ExplainGeoBlocked => (
+                "This content is only available from Brazil. Try again from a Brazilian connection, or route the requests through a proxy in Brazil (HTTPS_PROXY or a `proxy` in a [[hosts]] rule of the config file).",
+                "Este conteúdo só está disponível no Brasil. Tente novamente a partir de uma conexão brasileira, ou envie as requisições por um proxy no Brasil (HTTPS_PROXY ou um `proxy` em uma regra [[hosts]] do arquivo de configuração).",
+            ),
+            Msg::ExplainDrm => (
+                "This video is DRM-protected and cannot be downloaded.",
+                "Este vídeo é protegido por DRM e não pode ser baixado.",
+            ),
+            Msg::ExplainNotFound => (
+                "The video or program was not found. Check the ID or URL; it may have been removed.",
+                "O vídeo ou programa não foi encontrado. Confira o ID ou a URL; ele pode ter sido removido.",
+            ),
+            Msg::ExplainNetwork => (
+                "Globo Play could not be reached. Check your internet connection and try again.",
+                "Não foi possível acessar o Globo Play. Verifique sua conexão com a internet e tente novamente.",
+            ),
+            Msg::ExplainFfmpeg => (
+                "ffmpeg failed or is not installed. Make sure ffmpeg and ffprobe are on your PATH; -v shows the command that ran.",
+                "O ffmpeg falhou ou não está instalado. Verifique se ffmpeg e ffprobe estão no PATH; -v mostra o comando executado.",
+            ),
+            Msg::ExplainPartialFailure => (
+                "Some items failed; the report above lists them with the reason for each.",
+                "Alguns itens falharam; o relatório acima lista cada um com o motivo.",
+            ),
+            Msg::ExplainSubscriptionRequired => (
+                "Your account's plan doesn't include this content. Check the plans that offer it on globoplay.globo.com, or use the cookies of an account that subscribes to it.",
+                "O plano da sua conta não inclui este conteúdo. Confira os planos que o oferecem em globoplay.globo.com, ou use os cookies de uma conta que o assine.",
+            ),
+            Msg::ExplainAgeRestricted => (
+                "This content is age restricted. Use the cookies of an adult profile, or turn off the profile's parental controls in Globo Play's settings.",
+                "Este conteúdo tem restrição de idade. Use os cookies de um perfil adulto, ou desative o controle parental do perfil nas configurações do Globo Play.",
+            ),
+            Msg::ExplainUnavailable => (
+                "This content is no longer offered: its availability period ended or it was taken down. Search the program for a newer edition or an excerpt.",
+                "Este conteúdo não é mais oferecido: o período de disponibilidade terminou ou ele foi retirado do ar. Procure no programa uma edição mais recente ou um trecho.",
+            ),
+        };
+        match lang() {
+            Lang::En => en,
+            Lang::PtBr => pt,
+        }
+    }
+}
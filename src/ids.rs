@@ -0,0 +1,161 @@
+// src/ids.rs
+//! Typed IDs, so a title ID can't be passed where a video ID is expected
+//!
+//! Parsing (`str::parse`, also used for command-line arguments) checks the format and accepts
+//! Globo Play links as well as bare IDs. IDs read from API responses are taken as sent.
+use crate::error::{Error, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+macro_rules! id_type {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            pub fn into_string(self) -> String {
+                self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        // Reading an ID as a string is fine; turning a string into an ID goes through parsing
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = Error;
+
+            fn try_from(value: String) -> Result<Self> {
+                value.parse()
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = Error;
+
+            fn try_from(value: &str) -> Result<Self> {
+                value.parse()
+            }
+        }
+    };
+}
+
+id_type!(
+    /// A video's numeric ID, e.g. "4452349" (also the media ID of live channels)
+    VideoId
+);
+id_type!(
+    /// A title's (program's) alphanumeric ID, e.g. "q2Zt5vFxYg"
+    TitleId
+);
+id_type!(
+    /// A live channel's ID, e.g. "globo"
+    ChannelId
+);
+
+impl VideoId {
+    /// Finds a video link (`globoplay.globo.com/v/1234567/`) in `text`
+    pub fn find_in(text: &str) -> Option<Self> {
+        let link = Regex::new(r"globoplay\.globo\.com/v/(\d+)").ok()?;
+        link.captures(text).map(|caps| VideoId(caps[1].to_string()))
+    }
+}
+
+impl FromStr for VideoId {
+    type Err = Error;
+
+    /// Accepts a numeric ID or a video link
+    fn from_str(input: &str) -> Result<Self> {
+        let input = input.trim();
+        if !input.is_empty() && input.chars().all(|c| c.is_ascii_digit()) {
+            return Ok(VideoId(input.to_string()));
+        }
+        VideoId::find_in(input).ok_or_else(|| {
+            Error::Invalid(format!("'{}' is not a video ID (digits or a globoplay.globo.com/v/... link)", input))
+        })
+    }
+}
+
+impl TitleId {
+    /// Finds a program link (`globoplay.globo.com/<slug>/t/<titleId>/`) in `text`
+    pub fn find_in(text: &str) -> Option<Self> {
+        let link = Regex::new(r"globoplay\.globo\.com/[^/?#\s]+/t/([A-Za-z0-9]+)").ok()?;
+        link.captures(text).map(|caps| TitleId(caps[1].to_string()))
+    }
+}
+
+impl FromStr for TitleId {
+    type Err = Error;
+
+    /// Accepts an alphanumeric ID or a program link
+    fn from_str(input: &str) -> Result<Self> {
+        let input = input.trim();
+        if !input.is_empty() && input.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Ok(TitleId(input.to_string()));
+        }
+        TitleId::find_in(input).ok_or_else(|| {
+            Error::Invalid(format!(
+                "'{}' is not a title ID (letters and digits, or a globoplay.globo.com/<program>/t/... link)",
+                input
+            ))
+        })
+    }
+}
+
+impl FromStr for ChannelId {
+    type Err = Error;
+
+    /// Accepts letters, digits, '-' and '_'
+    fn from_str(input: &str) -> Result<Self> {
+        let input = input.trim();
+        if !input.is_empty() && input.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Ok(ChannelId(input.to_string()));
+        }
+        Err(Error::Invalid(format!("'{}' is not a channel ID (see the channels command)", input)))
+    }
+}
@@ -0,0 +1,176 @@
+// src/layout.rs
+
+use crate::filename::{self, SpaceStyle};
+use crate::ids::VideoId;
+use crate::models::VideoSession;
+use chrono::{Datelike, NaiveDate};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Folder structure used when writing downloads into a media-server library
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum LibraryLayout {
+    /// `Show/Season XX/Show - SxxExx - Title.mp4`, date-based names when no episode number is known
+    Plex,
+    /// `Show/Season XX/Show - SxxExx - Title.mp4`, synthesizing SxxExx from the air date when needed
+    Jellyfin,
+}
+
+/// Show/season/episode information derived from a video session's program metadata
+#[derive(Debug, Clone)]
+pub struct EpisodeInfo {
+    pub show: String,
+    pub title: String,
+    pub season: Option<u32>,
+    pub episode: Option<u32>,
+    pub air_date: Option<NaiveDate>,
+}
+
+impl EpisodeInfo {
+    /// Derives episode information from the session metadata, falling back to the resource name
+    ///
+    /// Season/episode numbers come from the metadata when present, otherwise they are
+    /// detected from the title or description text (e.g. "Capítulo 152").
+    pub fn from_session(session: &VideoSession, video_id: &VideoId) -> Self {
+        let resource_name = session.resource.as_ref().and_then(|r| r.name.clone());
+        match &session.metadata {
+            Some(metadata) => {
+                let (detected_season, detected_episode) = detect_episode_numbers(&metadata.title);
+                let (description_season, description_episode) = metadata
+                    .description
+                    .as_deref()
+                    .map(detect_episode_numbers)
+                    .unwrap_or((None, None));
+                EpisodeInfo {
+                    show: metadata
+                        .program
+                        .clone()
+                        .or_else(|| resource_name.clone())
+                        .unwrap_or_else(|| video_id.to_string()),
+                    title: metadata.title.clone(),
+                    season: metadata.season.or(detected_season).or(description_season),
+                    episode: metadata.episode.or(detected_episode).or(description_episode),
+                    air_date: metadata.exhibited_at.or(metadata.created_at).map(|t| t.date_naive()),
+                }
+            }
+            None => {
+                let name = resource_name.unwrap_or_else(|| video_id.to_string());
+                let (season, episode) = detect_episode_numbers(&name);
+                EpisodeInfo {
+                    show: name.clone(),
+                    title: name,
+                    season,
+                    episode,
+                    air_date: None,
+                }
+            }
+        }
+    }
+}
+
+/// Detects season and episode numbers in free text such as headlines
+///
+/// Recognizes `S02E05`, `T2E5`/`T2 E5`, "Temporada 2", "Capítulo 152", "Episódio 3" and "Ep. 3".
+///
+/// # Returns
+/// A `(season, episode)` tuple where either part may be missing
+pub fn detect_episode_numbers(text: &str) -> (Option<u32>, Option<u32>) {
+    static COMBINED: OnceLock<Regex> = OnceLock::new();
+    static SEASON: OnceLock<Regex> = OnceLock::new();
+    static EPISODE: OnceLock<Regex> = OnceLock::new();
+    let combined = COMBINED.get_or_init(|| Regex::new(r"(?i)\b[ST](\d{1,2})\s*E(\d{1,4})\b").expect("valid episode pattern"));
+    if let Some(caps) = combined.captures(text) {
+        return (caps[1].parse().ok(), caps[2].parse().ok());
+    }
+
+    let season = SEASON.get_or_init(|| Regex::new(r"(?i)\btemporada\s*(\d{1,2})\b").expect("valid season pattern"));
+    let episode = EPISODE
+        .get_or_init(|| Regex::new(r"(?i)\b(?:cap[ií]tulo|epis[oó]dio|ep\.?)\s*(\d{1,4})\b").expect("valid episode pattern"));
+    let season = season.captures(text).and_then(|caps| caps[1].parse().ok());
+    let episode = episode.captures(text).and_then(|caps| caps[1].parse().ok());
+    (season, episode)
+}
+
+/// Builds the path (relative to the download directory) for an episode in the given layout
+///
+/// # Arguments
+/// * `layout` - The media-server layout to follow
+/// * `info` - Show/season/episode information for the video
+/// * `extension` - File extension without the leading dot
+/// * `spaces` - How spaces in the show and episode titles are written
+///
+/// # Returns
+/// A relative path such as `Show/Season 01/Show - S01E05 - Title.mp4`
+pub fn layout_path(layout: LibraryLayout, info: &EpisodeInfo, extension: &str, spaces: SpaceStyle) -> PathBuf {
+    let show = filename::sanitize_value(&info.show, spaces);
+    let title = filename::sanitize_value(&info.title, spaces);
+
+    let (season_number, episode_tag) = match (info.season, info.episode, info.air_date) {
+        (season, Some(episode), _) => {
+            let season = season.unwrap_or(1);
+            (season, format!("S{:02}E{:02}", season, episode))
+        }
+        (_, None, Some(date)) => match layout {
+            // Plex matches date-based episodes directly from the filename
+            LibraryLayout::Plex => (date.year() as u32, date.format("%Y-%m-%d").to_string()),
+            // Jellyfin relies on SxxExx, so use the year as season and MMDD as episode
+            LibraryLayout::Jellyfin => (
+                date.year() as u32,
+                format!("S{}E{:02}{:02}", date.year(), date.month(), date.day()),
+            ),
+        },
+        (season, None, None) => (season.unwrap_or(1), String::new()),
+    };
+
+    let file_name = if episode_tag.is_empty() {
+        format!("{} - {}.{}", show, title, extension)
+    } else {
+        format!("{} - {} - {}.{}", show, episode_tag, title, extension)
+    };
+
+    let mut path = PathBuf::from(filename::finish_component(&show));
+    path.push(format!("Season {:02}", season_number));
+    path.push(filename::finish_component(&file_name));
+    path
+}
+
+/// Renders an output filename template into a path relative to the download directory
+///
+/// Supported placeholders: `{id}`, `{title}`, `{program}`, `{season}`, `{episode}`, `{date}` and `{ext}`.
+/// Season and episode are zero-padded to two digits; unknown values render as empty strings,
+/// except the season, which defaults to `01` when an episode number is known.
+///
+/// # Arguments
+/// * `template` - Template such as `"{program}/Season {season}/{program} - S{season}E{episode} - {title}.{ext}"`
+/// * `info` - Show/season/episode information for the video
+/// * `video_id` - The video ID, for the `{id}` placeholder
+/// * `extension` - File extension without the leading dot
+/// * `spaces` - How spaces in the substituted values are written; the template's own text is kept
+pub fn render_template(template: &str, info: &EpisodeInfo, video_id: &VideoId, extension: &str, spaces: SpaceStyle) -> PathBuf {
+    let season = info.season.or(info.episode.map(|_| 1));
+    let values = [
+        ("{id}", video_id.to_string()),
+        ("{title}", info.title.clone()),
+        ("{program}", info.show.clone()),
+        ("{season}", season.map(|s| format!("{:02}", s)).unwrap_or_default()),
+        ("{episode}", info.episode.map(|e| format!("{:02}", e)).unwrap_or_default()),
+        ("{date}", info.air_date.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_default()),
+        ("{ext}", extension.to_string()),
+    ];
+
+    // Substitute per path component so values can't introduce extra directories
+    template
+        .split('/')
+        .filter(|component| !component.is_empty())
+        .map(|component| {
+            let rendered = values.iter().fold(component.to_string(), |acc, (placeholder, value)| {
+                acc.replace(placeholder, &filename::sanitize_value(value, spaces))
+            });
+            filename::finish_component(&rendered)
+        })
+        .collect()
+}
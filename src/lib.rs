@@ -0,0 +1,83 @@
+// src/lib.rs
+//! Globo Play client library
+//!
+//! The `api` module talks to the Globo Play APIs, `models` holds their response types, `config`
+//! builds the shared `AppConfig` (HTTP client, quality, output settings) and `download` selects
+//! streams and downloads videos with ffmpeg. The `globo_play_rust` binary is a thin front-end
+//! over `cli` and `commands`.
+//!
+//! For `wasm32` targets only the metadata core is built: `protocol`, `models`, `ids`, `quality`
+//! and `error`, with their helpers `constants`, `dates`, `i18n` and `output`.
+
+#[cfg(not(any(feature = "native-tls", feature = "rustls", target_arch = "wasm32")))]
+compile_error!("enable a TLS backend for the HTTP client: the `native-tls` or `rustls` feature");
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod api;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod client;
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(feature = "cli")]
+pub mod commands;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod config;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod config_migrate;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod config_validate;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cookies;
+pub mod dates;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod diagnostics;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod download;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod downloader;
+pub mod error;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod exit_code;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod filename;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod hls;
+pub mod i18n;
+pub mod ids;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod layout;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod library;
+pub mod models;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod net;
+#[cfg(feature = "cli")]
+pub mod notify;
+pub mod output;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod paths;
+#[cfg(feature = "cli")]
+pub mod picker;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod progress;
+pub mod protocol;
+pub mod quality;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod report;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod secrets;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod target;
+#[cfg(feature = "cli")]
+pub mod tui;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod utils;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod webhook;
+pub mod constants;
+
+pub use error::{Error, Result};
@@ -0,0 +1,277 @@
+// src/library.rs
+
+use crate::dates::{self, Timestamp};
+use crate::error::{Error, Result};
+use crate::ids::{TitleId, VideoId};
+use crate::models::VideoSession;
+use crate::output;
+use crate::report::{ItemReport, ItemStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Name of the library file kept at the root of a download directory
+pub const LIBRARY_FILE_NAME: &str = ".globo_play_library.json";
+
+/// Name of the file in the state directory recording when each followed title was last checked for new episodes
+pub const SYNC_STATE_FILE_NAME: &str = "sync.json";
+
+/// Name of the append-only download history in the state directory (one JSON entry per line)
+pub const HISTORY_FILE_NAME: &str = "history.jsonl";
+
+// Names the sync state and history had when they were kept in the download directory
+const LEGACY_SYNC_STATE_FILE_NAME: &str = ".globo_play_sync.json";
+const LEGACY_HISTORY_FILE_NAME: &str = ".globo_play_history.jsonl";
+
+/// Upstream fields used to detect whether a video was re-published or corrected
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, Default)]
+pub struct ContentFingerprint {
+    pub duration: Option<u64>,
+    // Compared as instants, so the same time written in another format isn't a change
+    #[serde(default, with = "dates::timestamp")]
+    pub updated_at: Option<Timestamp>,
+    #[serde(default, with = "dates::timestamp")]
+    pub exhibited_at: Option<Timestamp>,
+}
+
+impl ContentFingerprint {
+    /// Builds a fingerprint from the metadata returned with a video session
+    pub fn from_session(session: &VideoSession) -> Self {
+        match &session.metadata {
+            Some(metadata) => ContentFingerprint {
+                duration: metadata.duration.and_then(|d| u64::try_from(d.num_seconds()).ok()),
+                updated_at: metadata.updated_at,
+                exhibited_at: metadata.exhibited_at,
+            },
+            None => ContentFingerprint::default(),
+        }
+    }
+
+    /// Describes which fields differ between two fingerprints, for user-facing messages
+    pub fn describe_changes(&self, newer: &ContentFingerprint) -> Vec<String> {
+        let mut changes = Vec::new();
+        if self.duration != newer.duration {
+            changes.push(format!("duration {:?} -> {:?}", self.duration, newer.duration));
+        }
+        if self.updated_at != newer.updated_at {
+            changes.push(format!("updated_at {:?} -> {:?}", self.updated_at, newer.updated_at));
+        }
+        if self.exhibited_at != newer.exhibited_at {
+            changes.push(format!("exhibited_at {:?} -> {:?}", self.exhibited_at, newer.exhibited_at));
+        }
+        changes
+    }
+}
+
+/// A single downloaded item tracked in the library
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LibraryEntry {
+    pub video_id: String,
+    pub title: Option<String>,
+    pub path: PathBuf,
+    pub downloaded_at: String, // RFC 3339 timestamp
+    pub fingerprint: ContentFingerprint,
+}
+
+/// Outcome of comparing a video against what the library already holds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LibraryStatus {
+    /// Never downloaded into this library (or the file has since been removed)
+    New,
+    /// Downloaded before and the upstream fingerprint is unchanged
+    Unchanged,
+    /// Downloaded before but the upstream fingerprint differs
+    Changed(Vec<String>),
+}
+
+/// Record of downloaded items, persisted as JSON in the download directory
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct Library {
+    #[serde(default)]
+    pub items: HashMap<String, LibraryEntry>,
+    #[serde(skip)]
+    file_path: PathBuf,
+}
+
+impl Library {
+    /// Loads the library stored in `dir`, or returns an empty one if none exists yet
+    pub fn load(dir: &Path) -> Result<Self> {
+        let file_path = dir.join(LIBRARY_FILE_NAME);
+        let mut library = if file_path.exists() {
+            let content = fs::read_to_string(&file_path)
+                .map_err(|e| Error::io(format!("Failed to read library file: {}", file_path.display()), e))?;
+            serde_json::from_str::<Library>(&content)
+                .map_err(|e| Error::parse(format!("Failed to parse library file: {}", file_path.display()), e))?
+        } else {
+            Library::default()
+        };
+        library.file_path = file_path;
+        Ok(library)
+    }
+
+    /// Writes the library back to disk
+    pub fn save(&self) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| Error::parse("Failed to serialize the library", e))?;
+        fs::write(&self.file_path, content)
+            .map_err(|e| Error::io(format!("Failed to write library file: {}", self.file_path.display()), e))
+    }
+
+    /// Compares the given fingerprint with the recorded entry for `video_id`
+    pub fn status(&self, video_id: &VideoId, fingerprint: &ContentFingerprint) -> LibraryStatus {
+        match self.items.get(video_id.as_str()) {
+            Some(entry) if entry.path.exists() => {
+                if entry.fingerprint == *fingerprint {
+                    LibraryStatus::Unchanged
+                } else {
+                    LibraryStatus::Changed(entry.fingerprint.describe_changes(fingerprint))
+                }
+            }
+            _ => LibraryStatus::New,
+        }
+    }
+
+    /// Records (or replaces) the entry for a completed download
+    pub fn record(&mut self, video_id: &VideoId, title: Option<String>, path: &Path, fingerprint: ContentFingerprint) {
+        self.items.insert(
+            video_id.to_string(),
+            LibraryEntry {
+                video_id: video_id.to_string(),
+                title,
+                path: path.to_path_buf(),
+                downloaded_at: chrono::Local::now().to_rfc3339(),
+                fingerprint,
+            },
+        );
+    }
+}
+
+/// When each followed title was last checked for new episodes, persisted as JSON in the state directory
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct SyncState {
+    #[serde(default)]
+    pub last_checked: HashMap<String, String>, // Title ID -> RFC 3339 timestamp
+    #[serde(skip)]
+    file_path: PathBuf,
+}
+
+impl SyncState {
+    /// Loads the sync state stored in `dir`, or returns an empty one if none exists yet
+    pub fn load(dir: &Path) -> Result<Self> {
+        let file_path = dir.join(SYNC_STATE_FILE_NAME);
+        let mut state = if file_path.exists() {
+            let content = fs::read_to_string(&file_path)
+                .map_err(|e| Error::io(format!("Failed to read sync state: {}", file_path.display()), e))?;
+            serde_json::from_str::<SyncState>(&content)
+                .map_err(|e| Error::parse(format!("Failed to parse sync state: {}", file_path.display()), e))?
+        } else {
+            SyncState::default()
+        };
+        state.file_path = file_path;
+        Ok(state)
+    }
+
+    /// Writes the sync state back to disk
+    pub fn save(&self) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| Error::parse("Failed to serialize the sync state", e))?;
+        if let Some(parent) = self.file_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| Error::io(format!("Failed to create {}", parent.display()), e))?;
+        }
+        fs::write(&self.file_path, content)
+            .map_err(|e| Error::io(format!("Failed to write sync state: {}", self.file_path.display()), e))
+    }
+
+    /// Date of the last check of `title_id`, if it was ever checked
+    pub fn last_checked_date(&self, title_id: &TitleId) -> Option<chrono::NaiveDate> {
+        self.last_checked
+            .get(title_id.as_str())
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.date_naive())
+    }
+
+    /// Records that `title_id` was checked just now
+    pub fn mark_checked(&mut self, title_id: &TitleId) {
+        self.last_checked
+            .insert(title_id.to_string(), chrono::Local::now().to_rfc3339());
+    }
+}
+
+/// One executed download, as recorded in the history file
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct HistoryEntry {
+    pub timestamp: String, // RFC 3339
+    pub video_id: String,
+    pub title: Option<String>,
+    pub path: Option<PathBuf>,
+    #[serde(flatten)]
+    pub status: ItemStatus,
+}
+
+impl HistoryEntry {
+    /// Builds an entry for a processed item, stamped with the current time
+    pub fn from_report(report: &ItemReport) -> Self {
+        HistoryEntry {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            video_id: report.video_id.clone(),
+            title: report.title.clone(),
+            path: report.path.clone(),
+            status: report.status.clone(),
+        }
+    }
+}
+
+/// Appends an entry to the history file in `dir`
+pub fn append_history(dir: &Path, entry: &HistoryEntry) -> Result<()> {
+    let file_path = dir.join(HISTORY_FILE_NAME);
+    fs::create_dir_all(dir).map_err(|e| Error::io(format!("Failed to create {}", dir.display()), e))?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&file_path)
+        .map_err(|e| Error::io(format!("Failed to open history: {}", file_path.display()), e))?;
+    let line = serde_json::to_string(entry).map_err(|e| Error::parse("Failed to serialize the history entry", e))?;
+    writeln!(file, "{}", line)
+        .map_err(|e| Error::io(format!("Failed to write history: {}", file_path.display()), e))
+}
+
+/// Reads every entry of the history file in `dir`, oldest first; unreadable lines are skipped with a warning
+pub fn load_history(dir: &Path) -> Result<Vec<HistoryEntry>> {
+    let file_path = dir.join(HISTORY_FILE_NAME);
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&file_path)
+        .map_err(|e| Error::io(format!("Failed to read history: {}", file_path.display()), e))?;
+    let mut entries = Vec::new();
+    for (number, line) in content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => output::warning!("skipping line {} of {}: {}", number + 1, file_path.display(), e),
+        }
+    }
+    Ok(entries)
+}
+
+/// Moves the sync state and history from a download directory, where older versions kept
+/// them, into the state directory, unless the state directory already has its own
+pub fn migrate_legacy_state(download_dir: &Path, state_dir: &Path) -> Result<()> {
+    for (legacy_name, name) in [
+        (LEGACY_SYNC_STATE_FILE_NAME, SYNC_STATE_FILE_NAME),
+        (LEGACY_HISTORY_FILE_NAME, HISTORY_FILE_NAME),
+    ] {
+        let legacy = download_dir.join(legacy_name);
+        let target = state_dir.join(name);
+        if !legacy.exists() || target.exists() {
+            continue;
+        }
+        fs::create_dir_all(state_dir).map_err(|e| Error::io(format!("Failed to create {}", state_dir.display()), e))?;
+        // rename fails across filesystems, e.g. from a NAS download directory
+        if fs::rename(&legacy, &target).is_err() {
+            fs::copy(&legacy, &target).map_err(|e| Error::io(format!("Failed to copy {} to {}", legacy.display(), target.display()), e))?;
+            fs::remove_file(&legacy).map_err(|e| Error::io(format!("Failed to remove {}", legacy.display()), e))?;
+        }
+        output::info!("Moved {} to {}", legacy.display(), target.display());
+    }
+    Ok(())
+}
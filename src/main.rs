@@ -1,411 +1,174 @@
 // src/main.rs
-
-mod api;
-mod cli;
-mod config;
-mod models;
-mod utils;
-mod constants;
-
 use anyhow::{Context, Result};
 use clap::Parser;
-use cli::{Cli, Commands};
-use config::AppConfig;
-use models::Source;
-use std::path::PathBuf;
-
-/// Selects the best stream source based on the specified quality preference.
-/// 
-/// # Arguments
-/// * `sources` - A slice of available video sources
-/// * `quality_preference` - Quality preference ("max", "min", or specific quality like "720p")
-///
-/// # Returns
-/// Option containing the URL of the selected source, or None if no sources available
-fn select_best_stream(sources: &[Source], quality_preference: &str, cli_quality_arg: Option<&str>) -> Option<Source> {
-    if sources.is_empty() {
-        return None;
-    }
-
-    // Attempt 1: Exact match on label based on quality_preference (e.g., "1080p")
-    let exact_match = sources.iter().find(|s| {
-        s.label.as_ref().map_or(false, |lbl| !lbl.is_empty() && lbl.contains(quality_preference))
-    });
-    if exact_match.is_some() {
-        return exact_match.cloned();
-    }
-
-    // Attempt 2: If CLI quality is "high" or "low", use dedicated functions
-    if let Some(cli_quality) = cli_quality_arg {
-        if cli_quality == "high" {
-            return find_highest_quality_source(sources, true);
-        } else if cli_quality == "low" {
-            return find_lowest_quality_source(sources, true);
-        }
-    } 
-
-    // Attempt 3: Default to primary source or first source
-    let primary_source = sources.iter().find(|s| 
-        s.source_type.as_str() == "primary" || 
-        s.type_.as_str() == "primary"
-    );
-
-    primary_source.cloned().or_else(|| sources.first().cloned())
-}
-
-/// Finds the highest quality source from a list of sources
-/// 
-/// Attempts to parse resolution values like "1080p", "720p", etc. or uses metadata
-fn find_highest_quality_source(sources: &[Source], prefer_primary: bool) -> Option<Source> {
-    let mut best_source: Option<Source> = None;
-    let mut max_resolution: u32 = 0;
-
-    let filtered_sources = sources.iter().filter(|s| {
-        if prefer_primary {
-            // Prefer "source_type" if available, otherwise fallback to "type"
-            s.source_type.as_str() == "primary" || s.type_.as_str() == "primary"
-        } else {
-            true // Consider all sources if not preferring primary
-        }
-    });
+use globo_play::cli::{Cli, Commands, LogFormat};
+use globo_play::config::{self, AppConfig};
+use globo_play::i18n::{self, Msg};
+use globo_play::diagnostics::{self, LogBuffer};
+use globo_play::{commands, download, exit_code, output, target};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
 
-    for source in filtered_sources {
-        let mut current_resolution: Option<u32> = None;
-
-        if let Some(label_str) = source.label.as_deref() {
-            if !label_str.is_empty() {
-                if let Some(res) = extract_resolution(label_str) {
-                    current_resolution = Some(res);
-                }
-            }
-        }
-
-        if current_resolution.is_none() {
-            if let Some(res) = extract_resolution_from_url(&source.url) {
-                current_resolution = Some(res);
-            }
-        }
-
-        if current_resolution.is_none() {
-            if let Some(asset_key_str) = source.asset_key.as_deref() {
-                 if !asset_key_str.is_empty() {
-                    if let Some(res) = extract_resolution(asset_key_str) { // asset_key can also contain resolution info
-                        current_resolution = Some(res);
-                    }
-                }
-            }
-        }
-
-        if let Some(resolution) = current_resolution {
-            if resolution > max_resolution {
-                max_resolution = resolution;
-                best_source = Some(source.clone());
+/// Main entry point for the application
+#[tokio::main]
+async fn main() -> std::process::ExitCode {
+    match run().await {
+        Ok(()) => std::process::ExitCode::from(exit_code::SUCCESS),
+        Err(e) => {
+            output::log_error(&format!("{:#}", e));
+            eprintln!("{} {:?}", output::paint_stderr(Msg::Error.text(), output::Color::Red), e);
+            let code = exit_code::classify(e.as_ref());
+            if let Some(explanation) = exit_code::explain(code) {
+                eprintln!("{} {}", output::paint_stderr(Msg::Hint.text(), output::Color::Bold), explanation.text());
             }
+            std::process::ExitCode::from(code)
         }
     }
-
-    best_source
 }
 
-/// Finds the lowest quality source from a list of sources
-fn find_lowest_quality_source(sources: &[Source], prefer_primary: bool) -> Option<Source> {
-    let mut worst_source: Option<Source> = None;
-    let mut min_resolution: u32 = u32::MAX;
-
-    let filtered_sources = sources.iter().filter(|s| {
-        if prefer_primary {
-            // Prefer "source_type" if available, otherwise fallback to "type"
-            s.source_type.as_str() == "primary" || s.type_.as_str() == "primary"
-        } else {
-            true // Consider all sources if not preferring primary
+/// Logs `tracing` events to stderr: RUST_LOG selects them when set, otherwise `--debug` (or `-vv`)
+/// shows the crate's debug events and only warnings are shown without it
+///
+/// With a `log_file`, the crate's debug events are also appended to it as JSON lines, whatever the
+/// console verbosity. With `--debug-dump`, they are also kept in the returned buffer for the bundle.
+fn init_tracing(cli: &Cli, log_file: Option<&Path>) -> Option<LogBuffer> {
+    let default_filter = if cli.debug || cli.verbose >= 2 { "warn,globo_play=debug,globo_play_rust=debug" } else { "warn" };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_filter));
+    // Warnings and errors printed by `output` are already on the console; they're only for the log file
+    let filter = filter.add_directive("globo_play::output=off".parse().expect("valid directive"));
+    let console = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_ansi(output::stderr_color());
+    let console = match cli.log_format {
+        LogFormat::Text => console.with_filter(filter).boxed(),
+        LogFormat::Json => console.json().with_filter(filter).boxed(),
+    };
+    let file = log_file.and_then(|path| match open_log_file(path) {
+        Ok(file) => Some(
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_writer(std::sync::Mutex::new(file))
+                .with_ansi(false)
+                .with_filter(EnvFilter::new(globo_play::constants::LOG_FILE_FILTER)),
+        ),
+        Err(e) => {
+            output::warning!("Not logging to {}: {:#}", path.display(), e);
+            None
         }
     });
-
-    for source in filtered_sources {
-        let mut current_resolution: Option<u32> = None;
-
-        if let Some(label_str) = source.label.as_deref() {
-            if !label_str.is_empty() {
-                if let Some(res) = extract_resolution(label_str) {
-                    current_resolution = Some(res);
-                }
-            }
-        }
-        
-        if current_resolution.is_none() {
-            if let Some(res) = extract_resolution_from_url(&source.url) {
-                current_resolution = Some(res);
-            }
-        }
-
-        if current_resolution.is_none() {
-            if let Some(asset_key_str) = source.asset_key.as_deref() {
-                if !asset_key_str.is_empty() {
-                    if let Some(res) = extract_resolution(asset_key_str) { // asset_key can also contain resolution info
-                        current_resolution = Some(res);
-                    }
-                }
-            }
-        }
-
-        if let Some(resolution) = current_resolution {
-            if resolution < min_resolution {
-                min_resolution = resolution;
-                worst_source = Some(source.clone());
-            }
-        }
-    }
-
-    worst_source
-}
-
-/// Extracts resolution value from labels like "720p", "1080p HD", etc.
-fn extract_resolution(label: &str) -> Option<u32> {
-    // Find digits followed by 'p'
-    let re = regex::Regex::new(r"(\d+)p").ok()?;
-    re.captures(label)
-        .and_then(|caps| caps.get(1))
-        .and_then(|res| res.as_str().parse::<u32>().ok())
+    let debug_log = cli.debug_dump.as_ref().map(|_| LogBuffer::default());
+    let dump = debug_log.clone().map(|buffer| {
+        tracing_subscriber::fmt::layer()
+            .json()
+            .with_writer(move || buffer.clone())
+            .with_ansi(false)
+            .with_filter(EnvFilter::new(globo_play::constants::LOG_FILE_FILTER))
+    });
+    tracing_subscriber::registry().with(console).with(file).with(dump).init();
+    debug_log
 }
 
-/// Extracts resolution value from URLs containing resolution information
-/// 
-/// For example: urls containing "r360_1080" would extract 1080
-fn extract_resolution_from_url(url: &str) -> Option<u32> {
-    // Look for common resolution patterns in URLs
-    let res_patterns = [
-        // r360_1080 pattern
-        regex::Regex::new(r"r\d+_(\d+)").ok()?,
-        // direct resolution like 1080p or 720p
-        regex::Regex::new(r"(\d+)p").ok()?,
-        // Any sequence of 3-4 digits that might represent resolution
-        regex::Regex::new(r"/(\d{3,4})(/|_|\.)").ok()?
-    ];
-    
-    for pattern in &res_patterns {
-        if let Some(caps) = pattern.captures(url) {
-            if let Some(res_match) = caps.get(1) {
-                if let Ok(res) = res_match.as_str().parse::<u32>() {
-                    return Some(res);
-                }
-            }
-        }
+/// Writes the --debug-dump bundle, warning instead of failing the run when it can't be written
+fn write_debug_dump(path: &str, config: Option<&AppConfig>, args: &[OsString], error: Option<&anyhow::Error>, log: &LogBuffer) {
+    let path = PathBuf::from(shellexpand::tilde(path).into_owned());
+    match diagnostics::write_bundle(&path, config, args, error.map(|e| e.as_ref() as _), log) {
+        Ok(()) => output::info!("Wrote diagnostics bundle to {}; credentials are redacted, but check it before sharing", path.display()),
+        Err(e) => output::warning!("Could not write the diagnostics bundle: {}", e),
     }
-    
-    None
-}
-
-/// Sanitizes a string to be used as a valid filename
-///
-/// Removes special characters and replaces spaces with underscores
-///
-/// # Arguments
-/// * `name` - The string to sanitize
-///
-/// # Returns
-/// A sanitized string suitable for use as a filename
-fn sanitize_filename(name: &str) -> String {
-    name.chars()
-        .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-' || *c == '_')
-        .collect::<String>()
-        .replace(' ', "_")
 }
 
-/// Handles the video command, fetching video information and optionally downloading the video
-///
-/// # Arguments
-/// * `video_id` - The ID of the video to fetch
-/// * `download` - Whether to download the video
-/// * `custom_filename` - Optional custom filename for the downloaded video
-/// * `quality_override` - Optional quality override for the video
-/// * `output_dir_override` - Optional output directory for the downloaded video
-/// * `config` - The application configuration
-/// * `fetch_full_info` - Whether to fetch full video info (true) or basic info (false)
-///
-/// # Returns
-/// Result indicating success or error
-async fn handle_video_command(
-    video_id: String,
-    download: bool,
-    custom_filename: Option<String>,
-    quality_override: Option<String>,
-    output_dir_override: Option<String>,
-    config: &AppConfig,
-    fetch_full_info: bool, // True for VideoInfo, false for Video (basic)
-) -> Result<()> {
-    println!("Fetching video session for ID: {}", video_id);
-    match api::fetch_video_session(&video_id, config).await {
-        Ok(session) => {
-            if fetch_full_info || config.output_format == "json" || config.output_format == "pretty" {
-                let output_str = if config.output_format == "pretty" {
-                    serde_json::to_string_pretty(&session)?
-                } else {
-                    serde_json::to_string(&session)?
-                };
-                println!("{}", output_str);
-            } else {
-                // Compact output for basic video info
-                if let Some(resource) = &session.resource {
-                     println!("Title: {}", resource.name.as_deref().unwrap_or("N/A"));
-                     println!("ID: {}", resource.id.as_deref().unwrap_or("N/A"));
-                } else {
-                    println!("Video ID: {}", video_id); // Fallback if resource details are not in session
-                }
-                println!("Available Streams:");
-                for source in &session.sources {
-                    println!("  - Label: {}, URL: {}", source.label.as_deref().unwrap_or("N/A"), source.url);
-                }
-            }
-
-            if download {
-                let quality_pref = quality_override.as_ref().unwrap_or(&config.video_quality);
-                // Pass the cli_quality_arg to select_best_stream
-                let cli_quality_arg = quality_override.as_deref(); 
-                if let Some(stream_source) = select_best_stream(&session.sources, quality_pref, cli_quality_arg) {
-                    let filename = custom_filename.unwrap_or_else(|| {
-                        let title = session.resource.as_ref().map_or_else(
-                            || video_id.clone(),
-                            |r| sanitize_filename(r.name.as_deref().unwrap_or(&video_id)),
-                        );
-                        format!("{}.mp4", title) // Assuming mp4, might need to check source type
-                    });
-
-                    let output_dir = output_dir_override
-                        .map(PathBuf::from)
-                        .unwrap_or_else(|| config.download_dir.clone());
-                    let mut download_path = output_dir;
-                    download_path.push(filename);
-
-                    println!(
-                        "Downloading video from {} to {}",
-                        stream_source.url, // Use stream_source.url instead of stream_source
-                        download_path.display()
-                    );
-                    utils::download_file(&config.http_client, &stream_source.url, &download_path).await?; // Use &stream_source.url
-                    println!("Download complete: {}", download_path.display());
-                } else {
-                    eprintln!("Could not find a suitable stream to download for quality preference: {}", quality_pref);
-                }
-            }
-        }
-        Err(e) => {
-            eprintln!("Error fetching video session for {}: {}", video_id, e);
-            return Err(e.into());
-        }
+/// Opens the log file for appending, creating it and its directory if needed
+fn open_log_file(path: &Path) -> Result<std::fs::File> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).context(format!("Failed to create directory: {}", parent.display()))?;
     }
-    Ok(())
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .context(format!("Failed to open log file: {}", path.display()))
 }
 
-/// Handles fetching videos by date and optionally downloading all videos in the result
-///
-/// # Arguments
-/// * `title_id` - The ID of the title/program to fetch videos for
-/// * `from_date_opt` - Optional start date (format: YYYY-MM-DD)
-/// * `to_date_opt` - Optional end date (format: YYYY-MM-DD)
-/// * `download_all` - Whether to download all videos in the result
-/// * `config` - The application configuration
-///
-/// # Returns
-/// Result indicating success or error
-async fn handle_videos_by_date_command(
-    title_id: String,
-    from_date_opt: Option<String>,
-    to_date_opt: Option<String>,
-    download_all: bool,
-    config: &AppConfig,
-) -> Result<()> {
-    let today = chrono::Local::now().date_naive();
-    let from_date = from_date_opt.unwrap_or_else(|| today.format("%Y-%m-%d").to_string());
-    let to_date = to_date_opt.unwrap_or_else(|| from_date.clone()); // Default to_date to from_date if not specified
-
-    // For simplicity, fetch first page, 20 items. Pagination can be added later.
-    let page = 1;
-    let per_page = 20;
-
-    println!(
-        "Fetching videos for title ID: {} from {} to {} (page {}, per_page {})",
-        title_id, from_date, to_date, page, per_page
-    );
-
-    match api::fetch_videos_by_date(&title_id, &from_date, &to_date, page, per_page, config).await {
-        Ok(response) => {
-            if config.output_format == "pretty" {
-                println!("{}", serde_json::to_string_pretty(&response.items)?);
-            } else if config.output_format == "json" {
-                println!("{}", serde_json::to_string(&response.items)?);
-            } else {
-                // Compact output
-                println!("Found {} videos:", response.items.len());
-                for video_item in &response.items {
-                    println!(
-                        "  ID: {}, Title: {}, Date: {}",
-                        video_item.id,
-                        video_item.headline.as_deref().unwrap_or("N/A"),
-                        video_item.date_formated.as_deref().unwrap_or("N/A")
-                    );
-                }
-            }
-
-            if download_all {
-                if response.items.is_empty() {
-                    println!("No videos found to download.");
-                    return Ok(());
-                }
-                println!("Attempting to download all {} videos...", response.items.len());
-                for video_item in response.items {
-                    let video_id_to_download = video_item.resource_id.as_ref().unwrap_or(&video_item.id);
-                    println!("--- Downloading video: {} ({}) ---", video_item.headline.as_deref().unwrap_or("N/A"), video_id_to_download);
-                    // Use default quality and output dir from global config for batch downloads
-                    // Filename will be auto-generated based on title
-                    if let Err(e) = handle_video_command(
-                        video_id_to_download.clone(),
-                        true,
-                        None, // No custom filename for batch
-                        None, // Use global quality
-                        None, // Use global output dir
-                        config,
-                        false, // Don't need full info print during batch download
-                    ).await {
-                        eprintln!("Failed to download video {}: {}", video_id_to_download, e);
-                        // Continue with the next video
-                    }
-                    println!("--------------------------------------");
-                }
-            }
-        }
-        Err(e) => {
-            eprintln!("Error fetching videos by date for {}: {}", title_id, e);
-            return Err(e.into());
-        }
+async fn run() -> Result<()> {
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let cli = Cli::parse_from(&args);
+    // Parse again with the config file's defaults for this subcommand, if it has any
+    let cli = match config::command_defaults(&cli, &args).context("Failed to apply command defaults from the config file")? {
+        Some(merged) => Cli::parse_from(merged),
+        None => cli,
+    };
+    let settings = config::early_settings(&cli);
+    output::set_level(if cli.quiet { output::QUIET } else { output::NORMAL.saturating_add(cli.verbose) });
+    output::set_machine_output(config::machine_output(&cli, &settings));
+    output::init_color(cli.no_color);
+    i18n::init(cli.lang);
+    let debug_log = init_tracing(&cli, config::log_file(&cli, &settings).as_deref());
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), args = ?args, "run started");
+
+    // Completion scripts go to stdout as-is, so skip the banner and configuration setup
+    if let Some(Commands::Completions { shell }) = cli.command {
+        commands::handle_completions_command(shell, config::profile_names(&cli));
+        return Ok(());
+    }
+    // Config management has to work even when the config file is missing or broken
+    if let Some(Commands::Config { action }) = &cli.command {
+        return commands::handle_config_command(action, &cli).await;
+    }
+    // Credentials are stored without loading the configuration, which would need them
+    match &cli.command {
+        Some(Commands::Login { cookie_file }) => return commands::handle_login_command(cookie_file),
+        Some(Commands::Logout) => return commands::handle_logout_command(),
+        _ => {}
     }
-    Ok(())
-}
 
-/// Main entry point for the application
-#[tokio::main]
-async fn main() -> Result<()> {
     // Display welcome banner
     let version = env!("CARGO_PKG_VERSION");
-    println!("Globo Play Rust v{} - Command-line utility", version);
-    println!("----------------------------------------");
+    output::info!("Globo Play Rust v{} - {}", version, Msg::Banner.text());
+    output::info!("----------------------------------------");
+
+    let config = match AppConfig::from_cli(&cli).await.context("Failed to load application configuration") {
+        Ok(config) => config,
+        Err(e) => {
+            if let (Some(path), Some(log)) = (&cli.debug_dump, &debug_log) {
+                write_debug_dump(path, None, &args, Some(&e), log);
+            }
+            return Err(e);
+        }
+    };
 
-    let cli = Cli::parse();
-    let config = AppConfig::from_cli(&cli).await.context("Failed to load application configuration")?;
+    tracing::debug!(?cli, ?config, "configuration loaded");
 
-    if config.debug_mode {
-        println!("DEBUG: CLI args: {:?}", cli);
-        println!("DEBUG: AppConfig: {:?}", config);
+    let result = run_command(cli.command, &config).await;
+    // Cookies the server refreshed during the command keep the session alive for the next run
+    if let Err(e) = config.save_cookies() {
+        output::warning!("Could not save the refreshed session cookies: {:#}", e);
+    }
+    if let (Some(path), Some(log)) = (&cli.debug_dump, &debug_log) {
+        write_debug_dump(path, Some(&config), &args, result.as_ref().err(), log);
     }
+    result
+}
 
-    match cli.command {
+/// Runs a subcommand that needs the loaded configuration
+async fn run_command(command: Option<Commands>, config: &AppConfig) -> Result<()> {
+    match command {
         Some(Commands::Video {
             video_id,
             download,
             filename,
             quality,
             output_dir,
+            list_formats,
         }) => {
-            handle_video_command(video_id, download, filename, quality, output_dir, &config, false).await?
+            let video_id = target::resolve_video_id(&video_id, config).await?;
+            if list_formats {
+                commands::handle_formats_command(video_id, config).await?
+            } else {
+                download::process_single_video(video_id, download, filename, quality, output_dir, config, false).await?
+            }
         }
         Some(Commands::VideoInfo {
             video_id,
@@ -413,33 +176,156 @@ async fn main() -> Result<()> {
             filename,
             quality,
             output_dir,
+            list_formats,
+        }) => {
+            let video_id = target::resolve_video_id(&video_id, config).await?;
+            if list_formats {
+                commands::handle_formats_command(video_id, config).await?
+            } else {
+                download::process_single_video(video_id, download, filename, quality, output_dir, config, true).await?
+            }
+        }
+        Some(Commands::Dl {
+            video_id,
+            filename,
+            quality,
+            output_dir,
         }) => {
-            handle_video_command(video_id, download, filename, quality, output_dir, &config, true).await?
+            let video_id = target::resolve_video_id(&video_id, config).await?;
+            download::process_single_video(video_id, true, filename, quality, output_dir, config, false).await?
         }
         Some(Commands::VideosByDate {
             title_id,
             from_date,
             to_date,
             download_all,
+            kind,
+            page,
+            per_page,
+            all_pages,
         }) => {
-            handle_videos_by_date_command(title_id, from_date, to_date, download_all, &config).await?
+            commands::handle_videos_by_date_command(
+                title_id,
+                from_date,
+                to_date,
+                download_all,
+                kind,
+                page,
+                per_page,
+                all_pages,
+                config,
+            )
+            .await?
+        }
+        Some(Commands::Search { query, page, per_page }) => {
+            commands::handle_search_command(query, page, per_page, config).await?
+        }
+        Some(Commands::Title { title_id }) => commands::handle_title_command(title_id, config).await?,
+        Some(Commands::Episodes {
+            title_id,
+            season,
+            download_all,
+        }) => {
+            commands::handle_episodes_command(title_id, season, download_all, config).await?
+        }
+        Some(Commands::ContinueWatching { download }) => {
+            commands::handle_continue_watching_command(download, config).await?
+        }
+        Some(Commands::MyList { download_new }) => commands::handle_my_list_command(download_new, config).await?,
+        Some(Commands::Trending { rail }) => commands::handle_trending_command(rail, config).await?,
+        Some(Commands::Chapters {
+            title_id,
+            from_chapter,
+            to_chapter,
+            download,
+        }) => commands::handle_chapters_command(title_id, from_chapter, to_chapter, download, config).await?,
+        Some(Commands::Graphql {
+            operation,
+            variables,
+            hash,
+            query,
+        }) => commands::handle_graphql_command(operation, variables, hash, query, config).await?,
+        Some(Commands::Get {
+            url,
+            download,
+            quality,
+            output_dir,
+        }) => commands::handle_get_command(url, download, quality, output_dir, config).await?,
+        Some(Commands::Watch { title_id, interval }) => commands::handle_watch_command(title_id, interval, config).await?,
+        Some(Commands::Daemon) => commands::handle_daemon_command(config).await?,
+        Some(Commands::History {
+            search,
+            status,
+            since,
+            limit,
+        }) => commands::handle_history_command(search, status, since, limit, config).await?,
+        Some(Commands::Formats { video_id }) => {
+            let video_id = target::resolve_video_id(&video_id, config).await?;
+            commands::handle_formats_command(video_id, config).await?
         }
+        Some(Commands::Tui) => commands::handle_tui_command(config).await?,
+        Some(Commands::Completions { .. }) => unreachable!("completions are generated before the configuration is loaded"),
+        Some(Commands::Config { .. }) => unreachable!("config commands are handled before the configuration is loaded"),
+        Some(Commands::Login { .. } | Commands::Logout) => {
+            unreachable!("login and logout are handled before the configuration is loaded")
+        }
+        Some(Commands::Channels) => commands::handle_channels_command(config).await?,
+        Some(Commands::Live {
+            channel_id,
+            record,
+            duration,
+            filename,
+        }) => {
+            commands::handle_live_command(channel_id, record, duration, filename, config).await?
+        }
+        Some(Commands::Schedule { channel_id, date }) => {
+            commands::handle_schedule_command(channel_id, date, config).await?
+        }
+        Some(Commands::Browse { category, page, per_page }) => {
+            commands::handle_browse_command(category, page, per_page, config).await?
+        }
+        Some(Commands::Snapshot {
+            title_ids,
+            from_date,
+            to_date,
+            snapshot_dir,
+        }) => {
+            commands::handle_snapshot_command(title_ids, from_date, to_date, snapshot_dir, config).await?
+        }
+        Some(Commands::NextEpisode {
+            title_id,
+            after,
+            days,
+            download,
+        }) => {
+            commands::handle_next_episode_command(title_id, after, days, download, config).await?
+        }
+        Some(Commands::News {
+            programs,
+            date,
+            download_latest,
+        }) => commands::handle_news_command(programs, date, download_latest, config).await?,
+        Some(Commands::Latest {
+            title_id,
+            count,
+            download,
+        }) => commands::handle_latest_command(title_id, count, download, config).await?,
         None => {
-            // No subcommand was given
-            println!("No command provided. Here are some examples to get you started:");
-            println!();
-            println!("  # Get information about a specific video:");
-            println!("  globo_play_rust video VIDEO_ID");
-            println!();
-            println!("  # Download a specific video with highest quality:");
-            println!("  globo_play_rust video VIDEO_ID --download");
-            println!();
-            println!("  # Get videos by date range for a specific title/program:");
-            println!("  globo_play_rust videos-by-date TITLE_ID --from-date 2023-01-01 --to-date 2023-01-31");
-            println!();
-            println!("For more options, use --help:");
-            println!("  globo_play_rust --help");
-            println!("  globo_play_rust video --help");
+            // No subcommand was given; the examples go to stderr when stdout carries JSON or CSV
+            output::info!("No command provided. Here are some examples to get you started:");
+            output::info!("");
+            output::info!("  # Get information about a specific video:");
+            output::info!("  globo_play_rust video VIDEO_ID");
+            output::info!("");
+            output::info!("  # Download a specific video with highest quality:");
+            output::info!("  globo_play_rust video VIDEO_ID --download");
+            output::info!("");
+            output::info!("  # Get videos by date range for a specific title/program:");
+            output::info!("  globo_play_rust videos-by-date TITLE_ID 2023-01-01 2023-01-31");
+            output::info!("");
+            output::info!("For more options, use --help:");
+            output::info!("  globo_play_rust --help");
+            output::info!("  globo_play_rust video --help");
         }
     }
 
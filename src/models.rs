@@ -1,20 +1,29 @@
 // src/models.rs
+use crate::dates::{self, Timestamp};
+use crate::ids::{ChannelId, TitleId, VideoId};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 
+/// Video metadata, fetched without creating a playback session
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Video {
-    pub id: String,
+    pub id: VideoId,
     pub title: String,
     pub description: Option<String>,
-    pub duration: Option<u32>,
+    #[serde(default, with = "dates::seconds")]
+    pub duration: Option<Duration>,
     pub headline: Option<String>,
     pub custom_id: Option<String>,
-    pub resource_id: Option<String>, // Often used to get session info
+    pub resource_id: Option<VideoId>, // Often used to get session info
     pub available_for: Option<String>,
+    #[serde(default, with = "dates::timestamp")]
+    pub exhibited_at: Option<Timestamp>,
+    pub program: Option<TitleRef>,
     // Add other fields as discovered from API responses
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[allow(dead_code)]
 pub struct VideoResource {
     pub id: String,
     pub name: String,
@@ -26,19 +35,124 @@ pub struct VideoResource {
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(from = "RawSource")]
 pub struct Source {
+    /// Whether this is the primary stream or a backup, from the `type` or `sourceType` key
     #[serde(rename = "type")]
-    pub type_: String, // "primary", "fallback"
+    pub kind: SourceKind,
     pub url: String,
     #[serde(default)]
     pub label: Option<String>, // Changed from String to Option<String>
-    #[serde(default, alias = "sourceType")]
-    pub source_type: String, 
     pub cdn: Option<String>, // CDN provider
     pub token: Option<String>, // Authentication token
     pub pop: Option<String>, // Point of presence
     pub asset_key: Option<String>, // Asset key
-    pub expiration_time: Option<u64>, // Expiration timestamp
+    #[serde(default, with = "dates::epoch")]
+    pub expiration_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub audio_languages: Vec<String>, // Filled from the HLS manifest's audio renditions
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>, // Fields not modeled above, kept as sent
+}
+
+impl Source {
+    pub fn is_primary(&self) -> bool {
+        self.kind == SourceKind::Primary
+    }
+
+    /// True once the source's signed URL has expired, if the session said when it does
+    pub fn is_expired(&self) -> bool {
+        self.expires_within(chrono::Duration::zero())
+    }
+
+    /// True if the source's signed URL expires within `margin` (or already has)
+    pub fn expires_within(&self, margin: chrono::Duration) -> bool {
+        self.expiration_time.is_some_and(|expires| expires <= Utc::now() + margin)
+    }
+}
+
+/// A source's role in a session
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum SourceKind {
+    Primary,
+    /// "backup", or "fallback" / "secondary" as some sessions call it
+    Backup,
+    /// A kind this tool doesn't know, kept as sent (empty if the session had none)
+    Unknown(String),
+}
+
+impl From<String> for SourceKind {
+    fn from(kind: String) -> Self {
+        match kind.to_ascii_lowercase().as_str() {
+            "primary" => SourceKind::Primary,
+            "backup" | "fallback" | "secondary" => SourceKind::Backup,
+            _ => SourceKind::Unknown(kind),
+        }
+    }
+}
+
+impl From<SourceKind> for String {
+    fn from(kind: SourceKind) -> String {
+        kind.to_string()
+    }
+}
+
+impl std::fmt::Display for SourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SourceKind::Primary => f.write_str("primary"),
+            SourceKind::Backup => f.write_str("backup"),
+            SourceKind::Unknown(kind) => f.write_str(kind),
+        }
+    }
+}
+
+/// `Source` as sent, with the kind under either or both of two keys
+#[derive(Deserialize)]
+struct RawSource {
+    #[serde(rename = "type", default)]
+    type_: String,
+    #[serde(default, alias = "sourceType")]
+    source_type: String,
+    url: String,
+    #[serde(default)]
+    label: Option<String>,
+    cdn: Option<String>,
+    token: Option<String>,
+    pop: Option<String>,
+    asset_key: Option<String>,
+    #[serde(default, deserialize_with = "dates::epoch::deserialize")]
+    expiration_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    audio_languages: Vec<String>,
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl From<RawSource> for Source {
+    fn from(raw: RawSource) -> Self {
+        // A recognized kind under either key wins, primary first
+        let kind = match (SourceKind::from(raw.type_), SourceKind::from(raw.source_type)) {
+            (SourceKind::Primary, _) | (_, SourceKind::Primary) => SourceKind::Primary,
+            (SourceKind::Backup, _) | (_, SourceKind::Backup) => SourceKind::Backup,
+            (SourceKind::Unknown(type_), SourceKind::Unknown(source_type)) => {
+                SourceKind::Unknown(if type_.is_empty() { source_type } else { type_ })
+            }
+        };
+        Source {
+            kind,
+            url: raw.url,
+            label: raw.label,
+            cdn: raw.cdn,
+            token: raw.token,
+            pop: raw.pop,
+            asset_key: raw.asset_key,
+            expiration_time: raw.expiration_time,
+            audio_languages: raw.audio_languages,
+            extra: raw.extra,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -49,7 +163,9 @@ pub struct VideoSession {
     pub resource: Option<VideoResourceDetails>, // Sometimes the resource details are nested
     pub metadata: Option<VideoMetadata>, // Metadata about the video
     pub thumbs_preview_base_url: Option<String>, // Preview thumbnails URL
-    pub thumbs_url: Option<String> // Thumbnails URL
+    pub thumbs_url: Option<String>, // Thumbnails URL
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>, // Fields not modeled above, kept as sent
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -63,6 +179,7 @@ pub struct VideoResourceDetails {
 
 // Model for a list of videos, as returned by date search or similar endpoints
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[allow(dead_code)]
 pub struct VideoItems {
     pub items: Vec<Video>,
     // Potentially pagination fields like next_page_token, has_next_page, etc.
@@ -73,19 +190,71 @@ pub struct VideoItems {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct DatedVideoItem {
     // Fields specific to the dated video list item
-    pub id: String,
+    pub id: VideoId,
     pub title: String,
     pub date_formated: Option<String>,
     pub headline: Option<String>,
     pub summary: Option<String>,
     pub duration_formatted: Option<String>,
-    pub duration_seconds: Option<u32>,
+    #[serde(default, with = "dates::seconds")]
+    pub duration_seconds: Option<Duration>,
     pub custom_id: Option<String>,
-    pub resource_id: Option<String>,
+    pub resource_id: Option<VideoId>,
     pub video_url: Option<String>, // URL to the video page, not the stream itself
+    #[serde(default, alias = "seasonNumber")]
+    pub season: Option<u32>,
+    #[serde(default, alias = "episodeNumber")]
+    pub episode: Option<u32>,
+    /// GraphQL video type, e.g. "EXCERPT" or "EPISODE"
+    #[serde(default, rename = "type", alias = "kind")]
+    pub kind: Option<String>,
+    #[serde(default, alias = "contentRating", alias = "ageRestriction")]
+    pub content_rating: Option<String>,
     // ... and so on
 }
 
+/// Returns true for Brazilian content ratings suitable for children ("L"/livre, "AL", or an age of 10 or less)
+pub fn is_kids_rating(rating: &str) -> bool {
+    let rating = rating.trim().to_uppercase();
+    match rating.as_str() {
+        "L" | "AL" | "LIVRE" => true,
+        age => age
+            .trim_start_matches('A')
+            .parse::<u32>()
+            .is_ok_and(|age| age <= 10),
+    }
+}
+
+/// Longest duration of an untyped item that is still treated as an excerpt
+const EXCERPT_MAX: Duration = Duration::minutes(10);
+
+impl DatedVideoItem {
+    /// Returns true for short clips (excerpts, teasers, trailers) rather than full episodes
+    ///
+    /// Uses the GraphQL type when present, otherwise falls back to the duration.
+    pub fn is_excerpt(&self) -> bool {
+        match self.kind.as_deref() {
+            Some(kind) => {
+                let kind = kind.to_uppercase();
+                ["EXCERPT", "CLIP", "TEASER", "TRAILER"]
+                    .iter()
+                    .any(|k| kind.contains(k))
+            }
+            None => self.duration_seconds.is_some_and(|d| d < EXCERPT_MAX),
+        }
+    }
+
+    /// Publication date parsed from `date_formated` ("2024-01-05..." or "05/01/2024")
+    pub fn published_date(&self) -> Option<chrono::NaiveDate> {
+        let date = self.date_formated.as_deref()?;
+        date.get(..10).and_then(|d| {
+            chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d")
+                .or_else(|_| chrono::NaiveDate::parse_from_str(d, "%d/%m/%Y"))
+                .ok()
+        })
+    }
+}
+
 /// Comprehensive metadata about a video from the session API response
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct VideoMetadata {
@@ -95,17 +264,27 @@ pub struct VideoMetadata {
     pub type_: Option<String>,
     #[serde(rename = "type")]
     pub video_type: String,
-    pub duration: Option<u64>,
+    #[serde(default, with = "dates::seconds")]
+    pub duration: Option<Duration>,
     pub program: Option<String>,
     pub program_id: Option<u64>,
+    pub season: Option<u32>,
+    pub episode: Option<u32>,
     pub channel: Option<String>,
     pub channel_id: Option<u64>,
     pub category: Option<String>,
-    pub created_at: Option<String>,
-    pub exhibited_at: Option<String>,
+    #[serde(default, with = "dates::timestamp")]
+    pub created_at: Option<Timestamp>,
+    #[serde(default, with = "dates::timestamp")]
+    pub exhibited_at: Option<Timestamp>,
+    /// Changes when Globo re-publishes a corrected edition
+    #[serde(default, with = "dates::timestamp")]
+    pub updated_at: Option<Timestamp>,
     pub url_for_consumption: Option<String>,
     pub codec: Option<String>,
     pub max_height: Option<u64>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>, // Fields not modeled above, kept as sent
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -116,6 +295,292 @@ pub struct DatedVideosResponse {
     // Other metadata related to the list
 }
 
+/// Metadata of a title's videos captured at a point in time by the `snapshot` command
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CatalogSnapshot {
+    pub title_id: TitleId,
+    pub taken_at: String, // RFC 3339 timestamp
+    pub from_date: String,
+    pub to_date: String,
+    pub items: Vec<DatedVideoItem>,
+}
+
+/// One page of a paginated GraphQL result list (search results, episodes, ...)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultPage<T> {
+    #[serde(default)]
+    pub has_next_page: bool,
+    pub next_page: Option<u32>,
+    #[serde(default = "Vec::new")]
+    pub resources: Vec<T>,
+}
+
+/// Summary of a title (program, novela, series...) in search results and catalog listings
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TitleSummary {
+    pub id: TitleId,
+    pub headline: Option<String>,
+    pub description: Option<String>,
+    #[serde(rename = "type")]
+    pub title_type: Option<String>,
+    pub url: Option<String>,
+    #[serde(default, rename = "contentRating", alias = "ageRestriction")]
+    pub content_rating: Option<String>,
+}
+
+/// Title reference embedded in video results
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TitleRef {
+    pub title_id: Option<TitleId>,
+    pub headline: Option<String>,
+}
+
+/// Summary of a video in search results and account lists
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoSummary {
+    pub id: VideoId,
+    pub headline: Option<String>,
+    pub description: Option<String>,
+    #[serde(default, with = "dates::seconds")]
+    pub duration: Option<Duration>,
+    #[serde(default, with = "dates::timestamp")]
+    pub exhibited_at: Option<Timestamp>,
+    #[serde(default, alias = "ageRestriction")]
+    pub content_rating: Option<String>,
+    pub title: Option<TitleRef>,
+}
+
+/// A channel matching a search
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SearchChannel {
+    pub id: ChannelId,
+    pub name: Option<String>,
+    pub slug: Option<String>,
+}
+
+/// Typed results of the search operation, one page per result type
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResults {
+    pub title_result: Option<ResultPage<TitleSummary>>,
+    pub video_result: Option<ResultPage<VideoSummary>>,
+    pub channel_result: Option<ResultPage<SearchChannel>>,
+}
+
+/// Poster image URLs of a title, per platform
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Poster {
+    pub web: Option<String>,
+    pub mobile: Option<String>,
+    pub tv: Option<String>,
+}
+
+/// A season of a seasoned title
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Season {
+    pub id: String,
+    pub number: Option<u32>,
+    pub total_episodes: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SeasonList {
+    #[serde(default)]
+    pub resources: Vec<Season>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct EpisodeCount {
+    pub total: Option<u32>,
+}
+
+/// Episode structure of a title: either seasons or a flat episode list
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TitleStructure {
+    pub default_season_id: Option<String>,
+    pub seasons: Option<SeasonList>,
+    pub episodes: Option<EpisodeCount>,
+}
+
+/// Program/show metadata returned by the title operation
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TitleDetails {
+    pub title_id: TitleId,
+    pub slug: Option<String>,
+    pub headline: Option<String>,
+    pub description: Option<String>,
+    #[serde(rename = "type")]
+    pub title_type: Option<String>,
+    pub format: Option<String>,
+    pub release_year: Option<u32>,
+    #[serde(default)]
+    pub genres_names: Vec<String>,
+    pub content_rating: Option<String>,
+    pub poster: Option<Poster>,
+    pub structure: Option<TitleStructure>,
+}
+
+impl TitleDetails {
+    /// Total number of episodes across all seasons, when known
+    pub fn episode_count(&self) -> Option<u32> {
+        let structure = self.structure.as_ref()?;
+        if let Some(episodes) = structure.episodes.as_ref().and_then(|e| e.total) {
+            return Some(episodes);
+        }
+        structure
+            .seasons
+            .as_ref()
+            .map(|s| s.resources.iter().filter_map(|season| season.total_episodes).sum())
+    }
+}
+
+/// Reference to the playable video of an episode
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct VideoRef {
+    pub id: VideoId,
+}
+
+/// An episode of a title, as listed by the episodes operation
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Episode {
+    pub id: VideoId,
+    pub number: Option<u32>,
+    pub season_number: Option<u32>,
+    pub headline: Option<String>,
+    pub description: Option<String>,
+    #[serde(default, with = "dates::seconds")]
+    pub duration: Option<Duration>,
+    #[serde(default, with = "dates::timestamp")]
+    pub exhibited_at: Option<Timestamp>,
+    pub video: Option<VideoRef>,
+}
+
+impl Episode {
+    /// ID to request a playback session with
+    pub fn video_id(&self) -> &VideoId {
+        self.video.as_ref().map_or(&self.id, |v| &v.id)
+    }
+}
+
+/// Titles and videos saved to the account's "My List"
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MyList {
+    pub titles: Option<ResultPage<TitleSummary>>,
+    pub videos: Option<ResultPage<VideoSummary>>,
+}
+
+/// An in-progress video in the account's continue-watching list
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ContinueWatchingItem {
+    pub id: VideoId,
+    pub headline: Option<String>,
+    pub description: Option<String>,
+    #[serde(default, with = "dates::seconds")]
+    pub duration: Option<Duration>,
+    /// How much was already watched
+    #[serde(default, with = "dates::seconds")]
+    pub resume_point: Option<Duration>,
+    #[serde(default, with = "dates::timestamp")]
+    pub exhibited_at: Option<Timestamp>,
+    pub title: Option<TitleRef>,
+}
+
+impl ContinueWatchingItem {
+    /// Watched percentage, when both the resume point and duration are known
+    pub fn progress_percent(&self) -> Option<i64> {
+        match (self.resume_point, self.duration) {
+            (Some(position), Some(duration)) if duration > Duration::zero() => {
+                Some(position.min(duration).num_seconds() * 100 / duration.num_seconds().max(1))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// An item (title, video or channel) featured in a home-page rail
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HighlightItem {
+    pub id: String,
+    pub headline: Option<String>,
+    #[serde(rename = "type")]
+    pub item_type: Option<String>,
+    pub title_id: Option<TitleId>,
+}
+
+/// A home-page highlight rail such as "most watched" or editor highlights
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct HighlightRail {
+    pub id: String,
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub rail_type: Option<String>,
+    #[serde(default)]
+    pub items: Vec<HighlightItem>,
+}
+
+/// A live channel
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Channel {
+    pub id: ChannelId,
+    pub name: Option<String>,
+    pub slug: Option<String>,
+}
+
+/// A program slot in the electronic program guide
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EpgSlot {
+    pub name: Option<String>,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub title: Option<TitleRef>,
+}
+
+/// A live broadcast of a channel, with the media ID used to open its live session
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Broadcast {
+    pub media_id: Option<VideoId>,
+    pub geofencing: Option<bool>,
+    pub affiliate_code: Option<String>,
+    pub channel: Channel,
+    #[serde(default)]
+    pub epg_current_slots: Vec<EpgSlot>,
+}
+
+impl Broadcast {
+    /// Returns true if `id` names this broadcast by channel ID, slug or media ID
+    pub fn matches(&self, id: &str) -> bool {
+        self.channel.id == id
+            || self.channel.slug.as_deref() == Some(id)
+            || self.media_id.as_ref().is_some_and(|media_id| media_id == id)
+    }
+
+    /// Returns true if this broadcast is the feed of the given regional affiliate
+    pub fn is_affiliate(&self, region: &str) -> bool {
+        self.affiliate_code
+            .as_deref()
+            .is_some_and(|code| code.eq_ignore_ascii_case(region))
+    }
+}
+
+/// A catalog category (novelas, séries, jornalismo, ...)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Category {
+    pub id: String,
+    pub name: Option<String>,
+    pub slug: Option<String>,
+}
+
 // Error structure for API responses
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ApiErrorResponse {
@@ -0,0 +1,664 @@
+// src/net.rs
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use crate::constants;
+use crate::diagnostics::{Exchange, ExchangeLog};
+use crate::error::{Error, Result};
+use crate::output;
+use crate::protocol::{ApiRequest, ApiResponse, Fetch, Method};
+use futures_util::future::BoxFuture;
+use reqwest::{Request, Response, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Network rules for the hosts matching a pattern (`[[hosts]]` in the config file)
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct HostRule {
+    /// Host name, `*.domain` for its subdomains, or `*` for every host
+    pub pattern: String,
+    /// Proxy URL for requests to matching hosts, e.g. "http://proxy.local:3128" or "socks5://127.0.0.1:1080"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Headers added to every request to matching hosts, replacing default ones with the same name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    /// Maximum number of requests per second to matching hosts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<f64>,
+}
+
+impl HostRule {
+    /// True if `host` matches the rule's pattern (case-insensitively)
+    pub fn matches(&self, host: &str) -> bool {
+        let pattern = self.pattern.to_ascii_lowercase();
+        let host = host.to_ascii_lowercase();
+        if pattern == "*" {
+            return true;
+        }
+        match pattern.strip_prefix("*.") {
+            Some(domain) => host.strip_suffix(domain).is_some_and(|prefix| prefix.ends_with('.')),
+            None => host == pattern,
+        }
+    }
+
+    /// Checks the pattern, proxy URL, headers and rate limit
+    pub fn validate(&self) -> Result<()> {
+        if self.pattern.trim().is_empty() {
+            return Err(Error::Invalid("Host rule without a pattern".to_string()));
+        }
+        if let Some(proxy) = &self.proxy {
+            Url::parse(proxy).map_err(|e| Error::Invalid(format!("Invalid proxy '{}' in host rule {}: {}", proxy, self.pattern, e)))?;
+        }
+        CompiledRule::new(self.clone()).map(|_| ())
+    }
+}
+
+/// When and how often failed requests are retried
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; 0 turns retrying off
+    pub retries: u32,
+    /// Delay before the first retry, doubled for each further one
+    pub backoff: Duration,
+    /// Upper bound for the delay between attempts
+    pub max_backoff: Duration,
+    /// HTTP statuses worth retrying; connection errors and timeouts are always retried
+    pub statuses: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            retries: constants::DEFAULT_RETRIES,
+            backoff: Duration::from_millis(constants::DEFAULT_RETRY_BACKOFF_MS),
+            max_backoff: Duration::from_secs(constants::DEFAULT_RETRY_MAX_BACKOFF_SECS),
+            statuses: constants::DEFAULT_RETRY_STATUSES.to_vec(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `retry` (counting from 0)
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.backoff.saturating_mul(2u32.saturating_pow(retry)).min(self.max_backoff)
+    }
+
+    fn retries_status(&self, status: StatusCode) -> bool {
+        self.statuses.contains(&status.as_u16())
+    }
+
+    fn retries_error(error: &reqwest::Error) -> bool {
+        error.is_connect() || error.is_timeout() || error.is_request()
+    }
+
+    /// ffmpeg input options that make it reconnect on the same failures while fetching segments
+    ///
+    /// ffmpeg doubles its own delay between reconnects and gives up once it would exceed the maximum backoff.
+    pub fn ffmpeg_options(&self) -> Vec<String> {
+        if self.retries == 0 {
+            return Vec::new();
+        }
+        let mut options: Vec<String> = ["-reconnect", "1", "-reconnect_streamed", "1", "-reconnect_on_network_error", "1"]
+            .iter()
+            .map(|option| option.to_string())
+            .collect();
+        options.extend(["-reconnect_delay_max".to_string(), self.max_backoff.as_secs().max(1).to_string()]);
+        if !self.statuses.is_empty() {
+            let statuses: Vec<String> = self.statuses.iter().map(u16::to_string).collect();
+            options.extend(["-reconnect_on_http_error".to_string(), statuses.join(",")]);
+        }
+        options
+    }
+}
+
+/// Connection reuse settings of the shared client
+///
+/// Every request of a client goes through one connection pool. The native downloader fetches
+/// several segments at once (`hls::download_stream`), which then reuse warm connections (and
+/// HTTP/2 streams) to the CDN instead of each opening their own.
+#[derive(Debug, Clone)]
+pub struct PoolOptions {
+    /// Idle connections kept open per host; None keeps every one
+    pub max_idle_per_host: Option<usize>,
+    /// How long an idle connection is kept open; None keeps it until the server closes it
+    pub idle_timeout: Option<Duration>,
+    /// Interval of TCP keep-alive probes; None turns them off
+    pub tcp_keepalive: Option<Duration>,
+    /// Interval of HTTP/2 pings that keep connections alive through NATs and proxies; None turns them off
+    pub http2_keep_alive: Option<Duration>,
+    /// Grow HTTP/2 flow-control windows with the measured bandwidth, for fast links with high latency
+    pub http2_adaptive_window: bool,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        PoolOptions {
+            max_idle_per_host: None,
+            idle_timeout: Some(Duration::from_secs(constants::DEFAULT_POOL_IDLE_TIMEOUT_SECS)),
+            tcp_keepalive: None,
+            http2_keep_alive: None,
+            http2_adaptive_window: false,
+        }
+    }
+}
+
+impl PoolOptions {
+    /// Sets the options on `builder`
+    pub fn apply(&self, builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        let mut builder = builder
+            .pool_idle_timeout(self.idle_timeout)
+            .tcp_keepalive(self.tcp_keepalive)
+            .http2_adaptive_window(self.http2_adaptive_window);
+        if let Some(max) = self.max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(interval) = self.http2_keep_alive {
+            builder = builder.http2_keep_alive_interval(interval).http2_keep_alive_while_idle(true);
+        }
+        builder
+    }
+}
+
+/// A host rule ready to apply: parsed headers and the rate limiter shared by all requests it matches
+#[derive(Debug)]
+struct CompiledRule {
+    rule: HostRule,
+    headers: HeaderMap,
+    interval: Option<Duration>,
+    next_slot: Mutex<Instant>,
+}
+
+impl CompiledRule {
+    fn new(rule: HostRule) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &rule.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| Error::Invalid(format!("Invalid header name '{}' in host rule {}", name, rule.pattern)))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| Error::Invalid(format!("Invalid value for header {} in host rule {}", name, rule.pattern)))?;
+            headers.insert(name, value);
+        }
+        let interval = match rule.rate_limit {
+            Some(rate) if rate > 0.0 && rate.is_finite() => Some(Duration::from_secs_f64(1.0 / rate)),
+            Some(rate) => {
+                return Err(Error::Invalid(format!("Invalid rate_limit {} in host rule {}: must be above 0", rate, rule.pattern)))
+            }
+            None => None,
+        };
+        Ok(CompiledRule {
+            rule,
+            headers,
+            interval,
+            next_slot: Mutex::new(Instant::now()),
+        })
+    }
+
+    /// Waits until the rule's rate limit allows another request
+    async fn throttle(&self) {
+        let Some(interval) = self.interval else {
+            return;
+        };
+        // Reserve the next slot under the lock, then sleep without holding it
+        let wait = {
+            let mut next_slot = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let slot = (*next_slot).max(now);
+            *next_slot = slot + interval;
+            slot - now
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Token bucket shared by all API requests of a client: holds up to `burst` requests and refills
+/// at `rate` per second, so a pause lets a few requests through at once and a long batch settles
+/// at the rate
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    /// Available tokens (negative when requests are waiting for them) and when they were counted
+    bucket: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    /// A limiter of `rate` requests per second, starting with a full bucket of `burst`
+    pub fn new(rate: f64, burst: u32) -> Result<Self> {
+        if !(rate > 0.0 && rate.is_finite()) {
+            return Err(Error::Invalid(format!("Invalid API rate limit {}: must be above 0", rate)));
+        }
+        let burst = f64::from(burst.max(1));
+        Ok(RateLimiter { rate, burst, bucket: Mutex::new((burst, Instant::now())) })
+    }
+
+    /// Waits until a token is available and takes it
+    pub async fn acquire(&self) {
+        // Take the token under the lock, going into debt if there is none, then sleep without holding it
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+            let (tokens, counted) = *bucket;
+            let now = Instant::now();
+            let tokens = (tokens + now.duration_since(counted).as_secs_f64() * self.rate).min(self.burst) - 1.0;
+            *bucket = (tokens, now);
+            Duration::from_secs_f64((-tokens).max(0.0) / self.rate)
+        };
+        if !wait.is_zero() {
+            tracing::debug!(wait = ?wait, "waiting for the API rate limit");
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Sends built requests for `HttpClient`; by default the `reqwest::Client` itself
+///
+/// Replace it (`GloboClientBuilder::transport`) to answer requests with canned responses instead of
+/// reaching Globo's servers, e.g. to test response parsing and pagination:
+///
+/// ```
+/// use futures_util::future::BoxFuture;
+/// use globo_play::net::Transport;
+///
+/// #[derive(Debug)]
+/// struct Canned(&'static str);
+///
+/// impl Transport for Canned {
+///     fn execute(&self, _request: reqwest::Request) -> BoxFuture<'_, reqwest::Result<reqwest::Response>> {
+///         let response = http::Response::new(self.0);
+///         Box::pin(async move { Ok(response.into()) })
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> globo_play::Result<()> {
+/// let client = globo_play::client::GloboClient::builder()
+///     .transport(Canned(r#"{"session": "abc", "sources": []}"#))
+///     .build()?;
+/// let session = client.fetch_video_session(&"12345678".parse()?).await?;
+/// assert_eq!(session.session, "abc");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Host rule headers, rate limits and retries are applied before a request reaches the transport.
+///
+/// This is the injection point for native programs: `HttpClient` implements `protocol::Fetch` on
+/// top of it, so the API calls, downloads and the response cache all go through the transport.
+/// Implement `Fetch` instead only for a `protocol::MetadataClient` over another HTTP stack, e.g. in
+/// `wasm32` builds.
+pub trait Transport: std::fmt::Debug + Send + Sync {
+    fn execute(&self, request: Request) -> BoxFuture<'_, reqwest::Result<Response>>;
+}
+
+impl Transport for reqwest::Client {
+    fn execute(&self, request: Request) -> BoxFuture<'_, reqwest::Result<Response>> {
+        Box::pin(reqwest::Client::execute(self, request))
+    }
+}
+
+/// The shared HTTP client with per-host rules and the retry policy applied to each request
+///
+/// The first rule whose pattern matches a request's host applies to it.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    transport: Arc<dyn Transport>,
+    rules: Arc<Vec<CompiledRule>>,
+    retry: Arc<RetryPolicy>,
+    cache: Option<Arc<ResponseCache>>,
+    limiter: Option<Arc<RateLimiter>>,
+    exchanges: Option<Arc<ExchangeLog>>,
+}
+
+impl HttpClient {
+    /// Finishes `builder` with the proxies of `rules` and wraps the client so the rules' headers and
+    /// rate limits and the retry policy are applied
+    pub fn new(builder: reqwest::ClientBuilder, rules: &[HostRule], retry: RetryPolicy) -> Result<Self> {
+        for rule in rules {
+            rule.validate()?;
+        }
+        let rules: Vec<CompiledRule> = rules.iter().cloned().map(CompiledRule::new).collect::<Result<_>>()?;
+        let proxies: Vec<(HostRule, Url)> = rules
+            .iter()
+            .filter_map(|compiled| Some((compiled.rule.clone(), Url::parse(compiled.rule.proxy.as_ref()?).ok()?)))
+            .collect();
+        let mut builder = builder;
+        if !proxies.is_empty() {
+            builder = builder.proxy(reqwest::Proxy::custom(move |url| {
+                let host = url.host_str()?;
+                proxies.iter().find(|(rule, _)| rule.matches(host)).map(|(_, proxy)| proxy.clone())
+            }));
+            // Adding any proxy turns off reqwest's use of the proxy environment variables, so other hosts
+            // need them added back explicitly
+            builder = with_environment_proxies(builder)?;
+        }
+        let client = builder.build()?;
+        Ok(HttpClient {
+            transport: Arc::new(client.clone()),
+            client,
+            rules: Arc::new(rules),
+            retry: Arc::new(retry),
+            cache: None,
+            limiter: None,
+            exchanges: None,
+        })
+    }
+
+    /// Answers cacheable API requests from `cache` while its entries are fresh
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Throttles API requests (not downloads) with `limiter`; answers from the cache aren't counted
+    pub fn with_rate_limit(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = Some(Arc::new(limiter));
+        self
+    }
+
+    /// Records API requests and their responses in `log`, for a diagnostics bundle
+    pub fn with_exchange_log(mut self, log: Arc<ExchangeLog>) -> Self {
+        self.exchanges = Some(log);
+        self
+    }
+
+    /// The log API exchanges are recorded in, if any
+    pub fn exchange_log(&self) -> Option<&Arc<ExchangeLog>> {
+        self.exchanges.as_ref()
+    }
+
+    /// Sends the requests through `transport` instead of the network
+    pub fn with_transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.request(reqwest::Method::GET, url)
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.request(reqwest::Method::POST, url)
+    }
+
+    pub fn head(&self, url: &str) -> RequestBuilder {
+        self.request(reqwest::Method::HEAD, url)
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str) -> RequestBuilder {
+        // An invalid URL is reported by reqwest when the request is sent
+        let host = url.parse::<Url>().ok().and_then(|url| url.host_str().map(str::to_string));
+        let rule = host.and_then(|host| self.rules.iter().position(|compiled| compiled.rule.matches(&host)));
+        RequestBuilder {
+            builder: self.client.request(method, url),
+            transport: Arc::clone(&self.transport),
+            rules: Arc::clone(&self.rules),
+            rule,
+            retry: Arc::clone(&self.retry),
+        }
+    }
+
+    /// The retry policy applied to each request, e.g. for repeating a download step
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// The first rule matching the host of `url`, if any
+    pub fn rule_for(&self, url: &str) -> Option<&HostRule> {
+        let host = url.parse::<Url>().ok()?.host_str()?.to_string();
+        self.rules.iter().map(|compiled| &compiled.rule).find(|rule| rule.matches(&host))
+    }
+}
+
+impl Fetch for HttpClient {
+    async fn fetch(&self, request: ApiRequest) -> Result<ApiResponse> {
+        let cache = self.cache.as_ref().filter(|_| request.cacheable);
+        let key = cache.map(|_| ResponseCache::key(&request));
+        if let (Some(cache), Some(key)) = (cache, &key) {
+            if let Some(response) = cache.get(key) {
+                tracing::debug!(url = %request.url, "response served from the cache");
+                if let Some(log) = &self.exchanges {
+                    log.record(Exchange::new(&request, Ok(&response), Duration::ZERO, true));
+                }
+                return Ok(response);
+            }
+        }
+
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire().await;
+        }
+        let started = Instant::now();
+        let mut builder = match request.method {
+            Method::Get => self.get(&request.url),
+            Method::Post => self.post(&request.url),
+        };
+        for (name, value) in &request.headers {
+            builder = builder.header(*name, value);
+        }
+        if let Some(body) = &request.body {
+            builder = builder.json(body);
+        }
+        let response = async {
+            let response = builder.send().await?;
+            let status = response.status();
+            let retry_after = retry_after(response.headers());
+            let body = response.text().await?;
+            Ok::<_, Error>(ApiResponse { status, retry_after, body })
+        }
+        .await;
+        if let Some(log) = &self.exchanges {
+            log.record(Exchange::new(&request, response.as_ref(), started.elapsed(), false));
+        }
+        let response = response?;
+        if let (Some(cache), Some(key)) = (cache, &key) {
+            if let Err(e) = cache.put(key, &response) {
+                tracing::debug!(url = %request.url, error = %e, "could not write the response cache");
+            }
+        }
+        Ok(response)
+    }
+}
+
+/// On-disk cache of API metadata responses, keyed by method, URL, headers and body
+///
+/// Only successful responses without GraphQL `errors` are kept. Entries aren't tied to an
+/// account, so requests that read the logged-in account (continue watching, My List) are sent
+/// with `cacheable: false` and never stored.
+#[derive(Debug)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+/// A cache entry as written to disk
+#[derive(Serialize, Deserialize)]
+struct CachedResponse {
+    status: u16,
+    body: String,
+}
+
+impl ResponseCache {
+    /// A cache in the `responses` directory of the cache directory whose entries are used for `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        ResponseCache { dir: crate::paths::cache_dir().join("responses"), ttl }
+    }
+
+    fn key(request: &ApiRequest) -> String {
+        let mut hasher = DefaultHasher::new();
+        (request.method == Method::Post).hash(&mut hasher);
+        request.url.hash(&mut hasher);
+        request.headers.hash(&mut hasher);
+        request.body.as_ref().map(|body| body.to_string()).hash(&mut hasher);
+        format!("{:016x}", hasher.finish())
+    }
+
+    fn get(&self, key: &str) -> Option<ApiResponse> {
+        let path = self.dir.join(format!("{}.json", key));
+        let age = std::fs::metadata(&path).ok()?.modified().ok()?.elapsed().ok()?;
+        if age > self.ttl {
+            return None;
+        }
+        let cached: CachedResponse = serde_json::from_str(&std::fs::read_to_string(&path).ok()?).ok()?;
+        Some(ApiResponse { status: StatusCode::from_u16(cached.status).ok()?, retry_after: None, body: cached.body })
+    }
+
+    fn put(&self, key: &str, response: &ApiResponse) -> std::io::Result<()> {
+        let has_errors = serde_json::from_str::<serde_json::Value>(&response.body)
+            .is_ok_and(|body| body.get("errors").is_some_and(|errors| !errors.is_null()));
+        if !response.status.is_success() || has_errors {
+            return Ok(());
+        }
+        std::fs::create_dir_all(&self.dir)?;
+        let cached = CachedResponse { status: response.status.as_u16(), body: response.body.clone() };
+        std::fs::write(self.dir.join(format!("{}.json", key)), serde_json::to_string(&cached)?)
+    }
+}
+
+/// Delay asked for by a response's `Retry-After` header, given in seconds or as an HTTP date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().unwrap_or_default())
+}
+
+/// How long to pause before repeating a request the server rate-limited (429, or 503 with a
+/// `Retry-After`), if it asked for a wait short enough to sit out
+fn rate_limit_pause(response: &Response) -> Option<Duration> {
+    if !matches!(response.status(), StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE) {
+        return None;
+    }
+    retry_after(response.headers()).filter(|delay| *delay <= Duration::from_secs(constants::MAX_RETRY_AFTER_SECS))
+}
+
+/// A request being built; see `reqwest::RequestBuilder` for the methods
+pub struct RequestBuilder {
+    builder: reqwest::RequestBuilder,
+    transport: Arc<dyn Transport>,
+    rules: Arc<Vec<CompiledRule>>,
+    rule: Option<usize>,
+    retry: Arc<RetryPolicy>,
+}
+
+impl RequestBuilder {
+    pub fn header<K, V>(mut self, key: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        self.builder = self.builder.header(key, value);
+        self
+    }
+
+    pub fn json<T: Serialize + ?Sized>(mut self, json: &T) -> Self {
+        self.builder = self.builder.json(json);
+        self
+    }
+
+    pub fn body<T: Into<reqwest::Body>>(mut self, body: T) -> Self {
+        self.builder = self.builder.body(body);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.builder = self.builder.timeout(timeout);
+        self
+    }
+
+    /// Applies the matching host rule's headers and rate limit, then sends the request, retrying
+    /// connection errors, timeouts and retryable statuses with exponential backoff
+    ///
+    /// A rate-limited response with a `Retry-After` is instead repeated after the asked-for delay,
+    /// with a warning, up to `constants::MAX_RATE_LIMIT_PAUSES` times and without using up retries.
+    pub async fn send(self) -> reqwest::Result<Response> {
+        let RequestBuilder {
+            mut builder,
+            transport,
+            rules,
+            rule,
+            retry,
+        } = self;
+        let rule = rule.map(|index| &rules[index]);
+        if let Some(rule) = rule {
+            builder = builder.headers(rule.headers.clone());
+        }
+        let mut request = builder.build()?;
+        let mut attempt = 0;
+        let mut pauses = 0;
+        loop {
+            // A request with a streaming body can't be repeated, so it only gets one attempt
+            let next = request.try_clone();
+            if let Some(rule) = rule {
+                rule.throttle().await;
+            }
+            tracing::debug!(method = %request.method(), url = %request.url(), attempt, "sending request");
+            let result = transport.execute(request).await;
+            match &result {
+                Ok(response) => tracing::debug!(status = %response.status(), "received response"),
+                Err(e) => tracing::debug!(error = %e, "request failed"),
+            }
+            let failure = match &result {
+                Ok(response) if retry.retries_status(response.status()) => {
+                    Some((response.url().to_string(), response.status().to_string()))
+                }
+                Err(e) if RetryPolicy::retries_error(e) => {
+                    Some((e.url().map_or_else(String::new, |url| url.to_string()), e.to_string()))
+                }
+                _ => None,
+            };
+            let pause = match &result {
+                Ok(response) if pauses < constants::MAX_RATE_LIMIT_PAUSES => {
+                    rate_limit_pause(response).map(|delay| (response.url().host_str().unwrap_or_default().to_string(), delay))
+                }
+                _ => None,
+            };
+            match (next, pause, failure) {
+                (Some(next), Some((host, delay)), _) => {
+                    pauses += 1;
+                    output::warning!("Rate limited by {}, resuming in {}s", host, delay.as_secs().max(1));
+                    tokio::time::sleep(delay).await;
+                    request = next;
+                }
+                (Some(next), None, Some((url, reason))) if attempt < retry.retries => {
+                    let delay = retry.delay(attempt);
+                    attempt += 1;
+                    output::verbose!("Retry {}/{} of {} in {:?}: {}", attempt, retry.retries, url, delay, reason);
+                    tokio::time::sleep(delay).await;
+                    request = next;
+                }
+                _ => return result,
+            }
+        }
+    }
+}
+
+/// Adds the proxies from `HTTP_PROXY`, `HTTPS_PROXY` and `ALL_PROXY` (lowercase names too), honoring `NO_PROXY`
+fn with_environment_proxies(mut builder: reqwest::ClientBuilder) -> Result<reqwest::ClientBuilder> {
+    let env = |name: &str| {
+        std::env::var(name.to_ascii_lowercase())
+            .or_else(|_| std::env::var(name))
+            .ok()
+            .filter(|value| !value.is_empty())
+    };
+    for name in ["HTTP_PROXY", "HTTPS_PROXY", "ALL_PROXY"] {
+        if let Some(url) = env(name) {
+            let proxy = match name {
+                "HTTP_PROXY" => reqwest::Proxy::http(&url),
+                "HTTPS_PROXY" => reqwest::Proxy::https(&url),
+                _ => reqwest::Proxy::all(&url),
+            };
+            let proxy = proxy.map_err(|e| Error::Invalid(format!("Invalid {} '{}': {}", name, url, e)))?;
+            builder = builder.proxy(proxy.no_proxy(reqwest::NoProxy::from_env()));
+        }
+    }
+    Ok(builder)
+}
@@ -0,0 +1,43 @@
+// src/notify.rs
+use crate::i18n::Msg;
+use crate::report::{BatchReport, ItemReport, ItemStatus};
+use crate::output;
+use notify_rust::Notification;
+
+const APP_NAME: &str = "Globo Play";
+
+/// Shows a desktop notification; a missing notification service only produces a warning
+fn show(summary: &str, body: &str) {
+    if let Err(e) = Notification::new().appname(APP_NAME).summary(summary).body(body).show() {
+        output::warning!("could not show desktop notification: {}", e);
+    }
+}
+
+/// Notifies about the outcome of a single download (`--notify`)
+pub fn item_finished(report: &ItemReport) {
+    let name = report.title.as_deref().unwrap_or(&report.video_id);
+    match &report.status {
+        ItemStatus::Downloaded => {
+            let body = match &report.path {
+                Some(path) => format!("{}\n{}", name, path.display()),
+                None => name.to_string(),
+            };
+            show(Msg::DownloadComplete.text(), &body);
+        }
+        // Only the first line; ffmpeg failures carry its whole stderr
+        ItemStatus::Failed { error } => {
+            show(Msg::DownloadFailed.text(), &format!("{}: {}", name, error.lines().next().unwrap_or_default()));
+        }
+        ItemStatus::Fetched | ItemStatus::Skipped { .. } => {}
+    }
+}
+
+/// Notifies about the outcome of a batch download (`--notify`)
+pub fn batch_finished(report: &BatchReport) {
+    let summary = if report.failed > 0 {
+        Msg::BatchCompleteWithFailures
+    } else {
+        Msg::BatchComplete
+    };
+    show(summary.text(), &report.counts());
+}
@@ -0,0 +1,165 @@
+// src/output.rs
+
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+/// Only results, warnings and errors (`--quiet`)
+pub const QUIET: u8 = 0;
+/// Progress messages such as "Fetching ..." (default)
+pub const NORMAL: u8 = 1;
+/// Extra detail such as the ffmpeg command line and output (`-v`)
+pub const VERBOSE: u8 = 2;
+
+static LEVEL: AtomicU8 = AtomicU8::new(NORMAL);
+static MACHINE_OUTPUT: AtomicBool = AtomicBool::new(false);
+static STDOUT_COLOR: AtomicBool = AtomicBool::new(false);
+static STDERR_COLOR: AtomicBool = AtomicBool::new(false);
+
+/// Sets the process-wide verbosity level, once at startup
+pub fn set_level(level: u8) {
+    LEVEL.store(level, Ordering::Relaxed);
+}
+
+/// Returns the current verbosity level
+pub fn level() -> u8 {
+    LEVEL.load(Ordering::Relaxed)
+}
+
+/// Returns true if messages of `level` should be printed
+pub fn enabled(level: u8) -> bool {
+    LEVEL.load(Ordering::Relaxed) >= level
+}
+
+/// Sends progress and status messages to stderr from now on, so stdout only carries the
+/// serialized results (`--output json`, `ndjson`, `csv`, `--print`); set once at startup
+pub fn set_machine_output(enabled: bool) {
+    MACHINE_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns true if stdout is reserved for serialized results
+pub fn machine_output() -> bool {
+    MACHINE_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Prints a line of human-readable text: to stdout, or to stderr in machine-output mode
+pub fn print_human(line: std::fmt::Arguments<'_>) {
+    if machine_output() {
+        eprintln!("{}", line);
+    } else {
+        println!("{}", line);
+    }
+}
+
+/// Prints a progress message unless `--quiet` is set
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if $crate::output::enabled($crate::output::NORMAL) {
+            $crate::output::print_human(format_args!($($arg)*));
+        }
+    };
+}
+pub use info;
+
+/// Prints a detail message only with `-v`
+#[macro_export]
+macro_rules! verbose {
+    ($($arg:tt)*) => {
+        if $crate::output::enabled($crate::output::VERBOSE) {
+            $crate::output::print_human(format_args!($($arg)*));
+        }
+    };
+}
+pub use verbose;
+
+/// Prints a warning to stderr, with a localized "Warning:" prefix that is yellow when colors are on
+#[macro_export]
+macro_rules! warning {
+    ($($arg:tt)*) => {{
+        let message = format!($($arg)*);
+        $crate::output::log_warning(&message);
+        eprintln!(
+            "{} {}",
+            $crate::output::paint_stderr($crate::i18n::Msg::Warning.text(), $crate::output::Color::Yellow),
+            message
+        )
+    }};
+}
+pub use warning;
+
+/// Prints an error to stderr, in red when colors are on
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)*) => {{
+        let message = format!($($arg)*);
+        $crate::output::log_error(&message);
+        eprintln!("{}", $crate::output::paint_stderr(message, $crate::output::Color::Red))
+    }};
+}
+pub use error;
+
+/// Records a warning printed to the console as a `tracing` event, for the log file (`--log-file`)
+///
+/// The events have the `globo_play::output` target, which the console's own log leaves out so
+/// the message isn't shown twice.
+pub fn log_warning(message: &str) {
+    tracing::warn!("{}", message);
+}
+
+/// Records an error printed to the console as a `tracing` event, like `log_warning`
+pub fn log_error(message: &str) {
+    tracing::error!("{}", message);
+}
+
+/// Terminal colors and styles used in human-readable output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Bold,
+    Dim,
+}
+
+impl Color {
+    fn ansi_code(self) -> &'static str {
+        match self {
+            Color::Red => "31",
+            Color::Green => "32",
+            Color::Yellow => "33",
+            Color::Bold => "1",
+            Color::Dim => "2",
+        }
+    }
+}
+
+/// Enables colors on stdout and stderr when they are terminals, unless `--no-color` or `NO_COLOR` is set
+pub fn init_color(no_color: bool) {
+    let allowed = !no_color && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty());
+    STDOUT_COLOR.store(allowed && std::io::stdout().is_terminal(), Ordering::Relaxed);
+    STDERR_COLOR.store(allowed && std::io::stderr().is_terminal(), Ordering::Relaxed);
+}
+
+/// Returns true if text printed to stderr may be colored
+pub fn stderr_color() -> bool {
+    STDERR_COLOR.load(Ordering::Relaxed)
+}
+
+fn paint_with(enabled: bool, text: impl Display, color: Color) -> String {
+    if enabled {
+        format!("\x1b[{}m{}\x1b[0m", color.ansi_code(), text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Colors text printed to stdout
+pub fn paint(text: impl Display, color: Color) -> String {
+    paint_with(STDOUT_COLOR.load(Ordering::Relaxed), text, color)
+}
+
+/// Colors text printed to stderr
+pub fn paint_stderr(text: impl Display, color: Color) -> String {
+    paint_with(STDERR_COLOR.load(Ordering::Relaxed), text, color)
+}
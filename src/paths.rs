@@ -0,0 +1,56 @@
+// src/paths.rs
+use directories::ProjectDirs;
+use std::path::PathBuf;
+
+/// Name of the application's directories under the platform base directories
+const APP_DIR_NAME: &str = "globo-play-rust";
+
+/// Platform directories: XDG base directories on Linux (honoring `XDG_CONFIG_HOME`,
+/// `XDG_CACHE_HOME`, `XDG_STATE_HOME`), `~/Library/...` on macOS and `%APPDATA%`/`%LOCALAPPDATA%` on Windows
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("", "", APP_DIR_NAME)
+}
+
+/// Fallback when no home directory can be determined: a hidden directory in the working directory
+fn fallback_dir(kind: &str) -> PathBuf {
+    PathBuf::from(format!(".{}", APP_DIR_NAME)).join(kind)
+}
+
+/// Directory of the config file, e.g. `~/.config/globo-play-rust`
+pub fn config_dir() -> PathBuf {
+    project_dirs().map_or_else(|| fallback_dir("config"), |dirs| dirs.config_dir().to_path_buf())
+}
+
+/// Location of the config file
+///
+/// The pre-XDG location `~/.config/globo-play-rust/config.toml` is still used on platforms where
+/// it differs from the config directory, if a file exists there and not in the new place.
+pub fn config_file() -> PathBuf {
+    let path = config_dir().join("config.toml");
+    let legacy = PathBuf::from(shellexpand::tilde(crate::constants::LEGACY_CONFIG_FILE_PATH).into_owned());
+    if !path.exists() && legacy.exists() {
+        legacy
+    } else {
+        path
+    }
+}
+
+/// Location of the secrets file written by `login`, next to the default config file
+pub fn secrets_file() -> PathBuf {
+    config_dir().join("secrets.toml")
+}
+
+/// Directory for caches that can be deleted at any time, e.g. `~/.cache/globo-play-rust`
+pub fn cache_dir() -> PathBuf {
+    project_dirs().map_or_else(|| fallback_dir("cache"), |dirs| dirs.cache_dir().to_path_buf())
+}
+
+/// Directory for state kept between runs (download history, sync state), e.g. `~/.local/state/globo-play-rust`
+///
+/// Only Linux has a state directory; elsewhere the local data directory is used.
+pub fn state_dir() -> PathBuf {
+    project_dirs().map_or_else(
+        || fallback_dir("state"),
+        |dirs| dirs.state_dir().unwrap_or(dirs.data_local_dir()).to_path_buf(),
+    )
+}
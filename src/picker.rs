@@ -0,0 +1,29 @@
+// src/picker.rs
+use crate::error::{Error, Result};
+use crate::i18n::Msg;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::FuzzySelect;
+use std::io::IsTerminal;
+
+/// Asks the user to choose one of `labels` from a type-to-filter list (`--interactive`)
+///
+/// The list is drawn on stderr so stdout stays clean for results.
+///
+/// # Arguments
+/// * `prompt` - Question shown above the list
+/// * `labels` - One line per choice
+///
+/// # Returns
+/// The index of the chosen label; fails when the user cancels or there is no terminal to ask on
+pub fn pick(prompt: &str, labels: &[String]) -> Result<usize> {
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        return Err(Error::Invalid(format!("{}: {}", Msg::NeedsTerminal.text(), prompt)));
+    }
+    FuzzySelect::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(labels)
+        .default(0)
+        .interact_opt()
+        .map_err(|dialoguer::Error::IO(e)| Error::io("Failed to read the selection", e))?
+        .ok_or_else(|| Error::Invalid(Msg::SelectionCancelled.text().to_string()))
+}
@@ -0,0 +1,181 @@
+// src/progress.rs
+//! Progress events from the download engine, for the command line's progress bar and for
+//! embedders drawing their own
+use crate::output;
+use crate::report::Phase;
+use serde::Serialize;
+use std::io::{IsTerminal, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+/// What a download has done so far
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProgressEvent {
+    /// The phase the item is in; downloaders report `Phase::Download`
+    pub phase: Phase,
+    /// Bytes written to the output file so far
+    pub bytes: u64,
+    /// HLS segments fetched so far and in total, when the downloader knows them
+    pub segments_done: Option<usize>,
+    pub segments_total: Option<usize>,
+    /// Average speed since the download started
+    pub bytes_per_second: Option<f64>,
+    /// True for the last event of the phase, sent whether it succeeded, failed or was cancelled
+    pub done: bool,
+}
+
+impl ProgressEvent {
+    /// An event marking the start of a phase
+    pub fn phase(phase: Phase) -> Self {
+        ProgressEvent { phase, bytes: 0, segments_done: None, segments_total: None, bytes_per_second: None, done: false }
+    }
+}
+
+/// Receives progress events, e.g. to update a progress bar
+///
+/// The callback runs in the download task, so it should return quickly; `channel` hands the
+/// events to another task instead.
+#[derive(Clone)]
+pub struct ProgressSink(Arc<dyn Fn(&ProgressEvent) + Send + Sync>);
+
+impl ProgressSink {
+    pub fn new(callback: impl Fn(&ProgressEvent) + Send + Sync + 'static) -> Self {
+        ProgressSink(Arc::new(callback))
+    }
+
+    pub fn send(&self, event: &ProgressEvent) {
+        (self.0)(event)
+    }
+}
+
+impl std::fmt::Debug for ProgressSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProgressSink")
+    }
+}
+
+/// A sink that forwards the events to a channel, for consumers running in their own task
+pub fn channel() -> (ProgressSink, mpsc::UnboundedReceiver<ProgressEvent>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let sink = ProgressSink::new(move |event| {
+        // The receiver going away only means nobody is listening anymore
+        let _ = sender.send(event.clone());
+    });
+    (sink, receiver)
+}
+
+/// Sends `event` to `sink`, if there is one
+pub fn emit(sink: Option<&ProgressSink>, event: ProgressEvent) {
+    if let Some(sink) = sink {
+        sink.send(&event);
+    }
+}
+
+/// Counts a download's bytes and segments and reports them to a sink
+///
+/// Dropping it sends the final `done` event, so the sink hears about failed and cancelled
+/// downloads too.
+#[cfg_attr(not(any(feature = "ffmpeg", feature = "native-downloader")), allow(dead_code))]
+pub(crate) struct Meter<'a> {
+    sink: Option<&'a ProgressSink>,
+    started: Instant,
+    bytes: u64,
+    segments_done: Option<usize>,
+    segments_total: Option<usize>,
+}
+
+#[cfg_attr(not(all(feature = "ffmpeg", feature = "native-downloader")), allow(dead_code))]
+impl<'a> Meter<'a> {
+    pub(crate) fn new(sink: Option<&'a ProgressSink>) -> Self {
+        Meter { sink, started: Instant::now(), bytes: 0, segments_done: None, segments_total: None }
+    }
+
+    pub(crate) fn set_segments_total(&mut self, total: usize) {
+        self.segments_total = Some(total);
+        self.segments_done = Some(0);
+    }
+
+    pub(crate) fn add_bytes(&mut self, bytes: u64) {
+        self.bytes += bytes;
+    }
+
+    pub(crate) fn set_bytes(&mut self, bytes: u64) {
+        self.bytes = bytes;
+    }
+
+    pub(crate) fn segment_done(&mut self) {
+        self.segments_done = Some(self.segments_done.unwrap_or(0) + 1);
+        self.report(false);
+    }
+
+    pub(crate) fn report(&self, done: bool) {
+        let seconds = self.started.elapsed().as_secs_f64();
+        emit(
+            self.sink,
+            ProgressEvent {
+                phase: Phase::Download,
+                bytes: self.bytes,
+                segments_done: self.segments_done,
+                segments_total: self.segments_total,
+                bytes_per_second: (seconds > 0.0).then(|| self.bytes as f64 / seconds),
+                done,
+            },
+        );
+    }
+}
+
+impl Drop for Meter<'_> {
+    fn drop(&mut self) {
+        self.report(true);
+    }
+}
+
+/// Formats a byte count with binary units, e.g. "12.3 MiB"
+pub fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", value as u64, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// A one-line progress bar on stderr, drawn while downloading when stderr is a terminal at the
+/// default verbosity (`-v` prints a line per segment instead)
+pub fn terminal_bar() -> ProgressSink {
+    // Width of the last line drawn, to blank it out when the download ends
+    let drawn = Mutex::new(0usize);
+    ProgressSink::new(move |event| {
+        if event.phase != Phase::Download || output::level() != output::NORMAL || !std::io::stderr().is_terminal() {
+            return;
+        }
+        let mut drawn = drawn.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut stderr = std::io::stderr().lock();
+        if event.done {
+            if *drawn > 0 {
+                let _ = write!(stderr, "\r{}\r", " ".repeat(*drawn));
+                *drawn = 0;
+            }
+            return;
+        }
+        let mut line = format!("Downloading: {}", format_bytes(event.bytes as f64));
+        if let (Some(done), Some(total)) = (event.segments_done, event.segments_total) {
+            let percent = (done * 100).checked_div(total).unwrap_or(0);
+            line.push_str(&format!(", {}/{} segments ({}%)", done, total, percent));
+        }
+        if let Some(speed) = event.bytes_per_second {
+            line.push_str(&format!(", {}/s", format_bytes(speed)));
+        }
+        let padding = drawn.saturating_sub(line.len());
+        let _ = write!(stderr, "\r{}{}", line, " ".repeat(padding));
+        let _ = stderr.flush();
+        *drawn = line.len();
+    })
+}
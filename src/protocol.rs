@@ -0,0 +1,786 @@
+// src/protocol.rs
+//! Request building and response parsing for the Globo Play APIs, independent of the HTTP stack
+//!
+//! This module, `models` and `error` also build for `wasm32-unknown-unknown` (with
+//! `--no-default-features`), so a browser extension or web dashboard can reuse the parsing code by
+//! giving `MetadataClient` its own `Fetch` implementation, e.g. over the browser's `fetch`.
+//! Native code uses `client::GloboClient`, whose API calls go through a `MetadataClient` over the
+//! shared `HttpClient`.
+use crate::constants;
+use crate::error::{Error, Result};
+use crate::ids::{TitleId, VideoId};
+use crate::models::{
+    ApiErrorResponse, Broadcast, Category, ContinueWatchingItem, DatedVideosResponse, EpgSlot, Episode, HighlightRail, MyList, ResultPage, SearchResults,
+    TitleDetails, TitleSummary, Video, VideoSession,
+};
+use crate::output;
+use crate::quality::Quality;
+use http::StatusCode;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// HTTP method of an `ApiRequest`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+}
+
+/// A request to one of the APIs, ready for a `Fetch` implementation to send
+#[derive(Debug, Clone)]
+pub struct ApiRequest {
+    pub method: Method,
+    pub url: String,
+    /// Headers on top of the ones every request carries (user agent, origin, session cookies)
+    pub headers: Vec<(&'static str, String)>,
+    /// JSON body of a POST request
+    pub body: Option<serde_json::Value>,
+    /// Whether a cached response may answer it: true for catalog metadata, false for playback
+    /// sessions and anything read from the logged-in account
+    pub cacheable: bool,
+}
+
+/// The parts of a response the parsers need
+#[derive(Debug, Clone)]
+pub struct ApiResponse {
+    pub status: StatusCode,
+    /// Delay asked for by a 429 response's `Retry-After` header, when given in seconds
+    pub retry_after: Option<Duration>,
+    pub body: String,
+}
+
+/// Sends `ApiRequest`s for a `MetadataClient`; `net::HttpClient` implements it for native builds
+///
+/// Failures to send the request or read the response are reported as `Error::Fetch` (or
+/// `Error::Request` by `HttpClient`); unsuccessful statuses are not errors here.
+///
+/// Native programs that want canned responses should give `GloboClient` a `net::Transport`
+/// instead, which `HttpClient`'s implementation sends through after applying its host rules,
+/// retries, rate limit and cache.
+pub trait Fetch {
+    fn fetch(&self, request: ApiRequest) -> impl Future<Output = Result<ApiResponse>>;
+}
+
+/// API base URLs and request settings used by a `MetadataClient`
+#[derive(Debug, Clone)]
+pub struct ApiSettings {
+    /// Base URL of the playback API that creates video sessions
+    pub playback_base_url: String,
+    /// URL of the GraphQL (Jarvis) API
+    pub graphql_url: String,
+    /// Quality requested when creating video sessions
+    pub quality: Quality,
+    /// Player fields sent when creating video sessions
+    pub session_request: VideoSessionRequest,
+    /// Affiliate code sent with GraphQL requests
+    pub region: Option<String>,
+    /// Persisted-query hash overrides by operation name
+    pub query_hashes: HashMap<String, String>,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        ApiSettings {
+            playback_base_url: constants::PLAYBACK_API_BASE_URL.to_string(),
+            graphql_url: constants::GRAPHQL_API_BASE_URL.to_string(),
+            quality: Quality::default(),
+            session_request: VideoSessionRequest::default(),
+            region: None,
+            query_hashes: HashMap::new(),
+        }
+    }
+}
+
+/// The player fields of a video-session request
+///
+/// The defaults are what the web player sends. Changing them asks for other streams, e.g.
+/// `VideoSessionRequest::new().consumption("download")` for download-consumption streams.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoSessionRequest {
+    player_type: String,
+    timezone: String,
+    capabilities: serde_json::Map<String, serde_json::Value>,
+    consumption: String,
+    device_type: String,
+}
+
+impl Default for VideoSessionRequest {
+    fn default() -> Self {
+        let mut capabilities = serde_json::Map::new();
+        capabilities.insert("low_latency".to_string(), serde_json::Value::Bool(true));
+        VideoSessionRequest {
+            player_type: "desktop".to_string(),
+            timezone: "-03:00".to_string(),
+            capabilities,
+            consumption: "streaming".to_string(),
+            device_type: "desktop".to_string(),
+        }
+    }
+}
+
+impl VideoSessionRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Player type, e.g. "desktop" or "mobile"
+    pub fn player_type(mut self, player_type: impl Into<String>) -> Self {
+        self.player_type = player_type.into();
+        self
+    }
+
+    /// UTC offset of the viewer, e.g. "-03:00"
+    pub fn timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.timezone = timezone.into();
+        self
+    }
+
+    /// Turns a player capability on or off, e.g. "low_latency"
+    pub fn capability(mut self, name: impl Into<String>, enabled: bool) -> Self {
+        self.capabilities.insert(name.into(), serde_json::Value::Bool(enabled));
+        self
+    }
+
+    /// How the video will be consumed: "streaming" or "download"
+    pub fn consumption(mut self, consumption: impl Into<String>) -> Self {
+        self.consumption = consumption.into();
+        self
+    }
+
+    /// Device type reported in the request metadata, e.g. "desktop" or "tv"
+    pub fn device_type(mut self, device_type: impl Into<String>) -> Self {
+        self.device_type = device_type.into();
+        self
+    }
+
+    /// The request body for `video_id`, with a fresh view session ID
+    pub fn body(&self, video_id: &VideoId, quality: Quality) -> serde_json::Value {
+        // Following the pattern from marine-traffic/gp-common-functions
+        serde_json::json!({
+            "player_type": self.player_type,
+            "video_id": video_id,
+            "quality": quality.to_string(),
+            "content_protection": "widevine",
+            "vsid": uuid::Uuid::new_v4().to_string(),
+            "tz": self.timezone,
+            "capabilities": self.capabilities,
+            "consumption": self.consumption,
+            "metadata": {
+                "name": "web",
+                "device": {
+                    "type": self.device_type,
+                    "os": {}
+                }
+            },
+            "version": 1
+        })
+    }
+}
+
+/// How a GraphQL operation is identified when sent to the Jarvis API
+#[derive(Debug, Clone, Copy)]
+pub enum GraphQlQuery<'a> {
+    /// A persisted query, referenced by its sha256 hash (sent as a GET request)
+    Persisted(&'a str),
+    /// The full query document (sent as a POST request)
+    Document(&'a str),
+    /// A persisted query that falls back to sending `document` if the API reports the hash as unknown
+    PersistedWithFallback { hash: &'a str, document: &'a str },
+}
+
+/// Error for a response body that doesn't match the expected model
+fn json_error(error: serde_json::Error) -> Error {
+    Error::parse("Failed to deserialize JSON response", error)
+}
+
+/// Returns true if a GraphQL operation reads the logged-in account, so its response must not be
+/// cached: one of the bundled account operations, or a query document selecting `user`
+fn reads_account(operation_name: &str, query: GraphQlQuery<'_>) -> bool {
+    let document = match query {
+        GraphQlQuery::Document(document) | GraphQlQuery::PersistedWithFallback { document, .. } => Some(document),
+        GraphQlQuery::Persisted(_) => None,
+    };
+    constants::ACCOUNT_OPERATIONS.contains(&operation_name)
+        || document.is_some_and(|document| {
+            document
+                .split(|c: char| !c.is_alphanumeric() && c != '_')
+                .any(|word| word == "user")
+        })
+}
+
+/// Returns true if a GraphQL response reports that the persisted-query hash is unknown (e.g. rotated)
+fn is_persisted_query_not_found(response: &serde_json::Value) -> bool {
+    response
+        .get("errors")
+        .and_then(|errors| errors.as_array())
+        .is_some_and(|errors| {
+            errors.iter().any(|e| {
+                e.get("message").and_then(|m| m.as_str()) == Some("PersistedQueryNotFound")
+                    || e.pointer("/extensions/code").and_then(|c| c.as_str()) == Some("PERSISTED_QUERY_NOT_FOUND")
+            })
+        })
+}
+
+/// The API calls, over any `Fetch` implementation
+///
+/// ```no_run
+/// # use globo_play::protocol::{ApiRequest, ApiResponse, ApiSettings, Fetch, MetadataClient};
+/// struct BrowserFetch;
+///
+/// impl Fetch for BrowserFetch {
+///     async fn fetch(&self, request: ApiRequest) -> globo_play::Result<ApiResponse> {
+///         // Send `request` with the platform's HTTP API and collect the status and body
+///         # unimplemented!()
+///     }
+/// }
+///
+/// # async fn example() -> globo_play::Result<()> {
+/// let client = MetadataClient::new(BrowserFetch, ApiSettings::default());
+/// let title = client.fetch_title("jornal-nacional").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MetadataClient<F> {
+    fetch: F,
+    settings: Arc<ApiSettings>,
+}
+
+impl<F> MetadataClient<F> {
+    pub fn new(fetch: F, settings: ApiSettings) -> Self {
+        MetadataClient { fetch, settings: Arc::new(settings) }
+    }
+
+    /// The `Fetch` implementation requests are sent with
+    pub fn fetcher(&self) -> &F {
+        &self.fetch
+    }
+
+    pub fn settings(&self) -> &ApiSettings {
+        &self.settings
+    }
+
+    /// Persisted-query hash for `operation`, honoring user overrides of the built-in `default`
+    pub fn query_hash<'a>(&'a self, operation: &str, default: &'a str) -> &'a str {
+        self.settings.query_hashes.get(operation).map_or(default, String::as_str)
+    }
+}
+
+impl<F: Fetch> MetadataClient<F> {
+    /// Creates a playback session with the player fields of the settings
+    pub async fn fetch_video_session(&self, video_id: &VideoId) -> Result<VideoSession> {
+        self.fetch_video_session_with(video_id, &self.settings.session_request).await
+    }
+
+    /// Creates a playback session with other player fields than the settings, e.g. to request
+    /// download-consumption streams for one video
+    #[tracing::instrument(skip(self))]
+    pub async fn fetch_video_session_with(
+        &self,
+        video_id: &VideoId,
+        request: &VideoSessionRequest,
+    ) -> Result<VideoSession> {
+        let url = format!("{}{}", self.settings.playback_base_url, constants::VIDEO_SESSION_URL_TEMPLATE);
+
+        tracing::debug!(%url, "fetching video session");
+
+        let request_body = request.body(video_id, self.settings.quality);
+
+        let response = self
+            .fetch
+            .fetch(ApiRequest { method: Method::Post, url, headers: Vec::new(), body: Some(request_body), cacheable: false })
+            .await?;
+
+        if response.status.is_success() {
+            tracing::trace!(body = %response.body, "video session response");
+            serde_json::from_str::<VideoSession>(&response.body).map_err(|e| {
+                tracing::debug!(error = %e, body = %response.body, "failed to parse the video session");
+                // Refusals such as geo-blocking can come back as an error object with a success status
+                match serde_json::from_str::<ApiErrorResponse>(&response.body) {
+                    Ok(api_error) => Error::from_refusal(api_error.message, api_error.code.as_deref()),
+                    Err(_) => json_error(e),
+                }
+            })
+        } else {
+            tracing::debug!(status = %response.status, body = %response.body, "video session request failed");
+            // Try to parse Globo API error structure
+            if let Ok(api_error) = serde_json::from_str::<ApiErrorResponse>(&response.body) {
+                Err(Error::from_refusal(api_error.message, api_error.code.as_deref()))
+            } else {
+                Err(Error::from_response(response.status, response.retry_after, response.body))
+            }
+        }
+    }
+
+    /// Sends a GraphQL operation to the Jarvis API and returns the whole response body
+    ///
+    /// Unlike `graphql_request`, GraphQL `errors` are returned as part of the body rather than
+    /// turned into an error; only transport, HTTP and JSON failures are errors.
+    /// `PersistedWithFallback` queries are retried with their document when the hash is unknown.
+    ///
+    /// # Arguments
+    /// * `operation_name` - The GraphQL operation name
+    /// * `query` - Persisted query hash or full query text
+    /// * `variables` - Operation variables
+    pub async fn graphql_raw_request(
+        &self,
+        operation_name: &str,
+        query: GraphQlQuery<'_>,
+        variables: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let response = self.send_graphql(operation_name, query, &variables).await;
+        let GraphQlQuery::PersistedWithFallback { hash, document } = query else {
+            return response;
+        };
+
+        let hash_unknown = match &response {
+            Ok(body) => is_persisted_query_not_found(body),
+            Err(Error::Http { body, .. }) => body.contains("PersistedQueryNotFound"),
+            Err(_) => false,
+        };
+        if !hash_unknown {
+            return response;
+        }
+        output::warning!(
+            "the API no longer knows the {} query hash {}; sending the bundled query text instead (update it with --query-hash)",
+            operation_name, hash
+        );
+        self.send_graphql(operation_name, GraphQlQuery::Document(document), &variables).await
+    }
+
+    /// Sends a single GraphQL request and parses the response body
+    #[tracing::instrument(skip(self, query, variables))]
+    async fn send_graphql(
+        &self,
+        operation_name: &str,
+        query: GraphQlQuery<'_>,
+        variables: &serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let cacheable = !reads_account(operation_name, query);
+        let mut request = match query {
+            GraphQlQuery::Persisted(query_hash) | GraphQlQuery::PersistedWithFallback { hash: query_hash, .. } => {
+                // Build extensions JSON
+                let extensions = serde_json::json!({
+                    "persistedQuery": {
+                        "version": 1,
+                        "sha256Hash": query_hash
+                    }
+                });
+
+                // URL encode parameters for URL
+                let variables_string = variables.to_string();
+                let extensions_string = extensions.to_string();
+                let encoded_variables = urlencoding::encode(&variables_string);
+                let encoded_extensions = urlencoding::encode(&extensions_string);
+
+                // Construct the URL
+                let url = format!(
+                    "{}?operationName={}&variables={}&extensions={}",
+                    &self.settings.graphql_url,
+                    operation_name,
+                    encoded_variables,
+                    encoded_extensions
+                );
+
+                tracing::debug!(%url, "persisted GraphQL query");
+                ApiRequest { method: Method::Get, url, headers: Vec::new(), body: None, cacheable }
+            }
+            GraphQlQuery::Document(document) => {
+                let body = serde_json::json!({
+                    "operationName": operation_name,
+                    "query": document,
+                    "variables": variables
+                });
+                tracing::debug!(%variables, "GraphQL query document");
+                ApiRequest {
+                    method: Method::Post,
+                    url: self.settings.graphql_url.clone(),
+                    headers: Vec::new(),
+                    body: Some(body),
+                    cacheable,
+                }
+            }
+        };
+
+        // Make the request with appropriate headers
+        request.headers.extend([
+            ("x-tenant-id", "globo-play".to_string()),
+            ("x-platform-id", "web".to_string()),
+            ("x-device-id", "desktop".to_string()),
+        ]);
+        if let Some(region) = &self.settings.region {
+            request.headers.push(("x-affiliate-code", region.clone()));
+        }
+        let response = self.fetch.fetch(request).await?;
+
+        if !response.status.is_success() {
+            return Err(Error::from_response(response.status, response.retry_after, response.body));
+        }
+
+        // Parse the GraphQL response format, which is different from the API response
+        tracing::trace!(body = %response.body, "GraphQL response");
+
+        serde_json::from_str(&response.body).map_err(json_error)
+    }
+
+    /// Sends a GraphQL operation to the Jarvis API and returns its `data` object
+    ///
+    /// # Arguments
+    /// * `operation_name` - The GraphQL operation name
+    /// * `query` - Persisted query hash or full query text
+    /// * `variables` - Operation variables
+    ///
+    /// # Returns
+    /// The `data` member of the GraphQL response, or an error if the response has `errors` and no data
+    pub async fn graphql_request(
+        &self,
+        operation_name: &str,
+        query: GraphQlQuery<'_>,
+        variables: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let mut graphql_response = self.graphql_raw_request(operation_name, query, variables).await?;
+
+        let data = graphql_response
+            .get_mut("data")
+            .map(serde_json::Value::take)
+            .filter(|data| !data.is_null());
+        match data {
+            Some(data) => Ok(data),
+            None => {
+                let errors = graphql_response.get("errors").and_then(|errors| errors.as_array());
+                let messages: Vec<String> = errors
+                    .map(|errors| {
+                        errors
+                            .iter()
+                            .filter_map(|e| e.get("message").and_then(|m| m.as_str()))
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                // The first error's code (e.g. "SUBSCRIPTION_REQUIRED") says more than the messages
+                let code = errors
+                    .and_then(|errors| errors.iter().find_map(|e| e.pointer("/extensions/code").and_then(|c| c.as_str())));
+                if messages.is_empty() {
+                    Err(Error::Api("GraphQL response has no data".to_string()))
+                } else {
+                    Err(Error::from_api_error(messages.join("; "), code))
+                }
+            }
+        }
+    }
+
+    pub async fn fetch_videos_by_date(
+        &self,
+        title_id: &TitleId,
+        from_date: &str, // YYYY-MM-DD
+        to_date: &str,   // YYYY-MM-DD
+        page: u32,
+        per_page: u32,
+    ) -> Result<DatedVideosResponse> {
+        // Build GraphQL request based on get-videos-by-date script
+        let variables = serde_json::json!({
+            "titleId": title_id,
+            "gte": from_date,
+            "lte": to_date,
+            "page": page,
+            "perPage": per_page
+        });
+
+        let data = self.graphql_request(
+            constants::VIDEOS_BY_DATE_OPERATION,
+            GraphQlQuery::PersistedWithFallback {
+                hash: self.query_hash(constants::VIDEOS_BY_DATE_OPERATION, constants::VIDEOS_BY_DATE_HASH),
+                document: constants::VIDEOS_BY_DATE_QUERY,
+            },
+            variables,
+        )
+        .await?;
+
+        // Extract the data.title.structure.excerpts.resources array
+        let resources = data
+            .get("title")
+            .and_then(|title| title.get("structure"))
+            .and_then(|structure| structure.get("excerpts"))
+            .and_then(|excerpts| excerpts.get("resources"))
+            .ok_or_else(|| Error::Api("Missing resources in GraphQL response".to_string()))?;
+
+        // Convert to our DatedVideosResponse format
+        let videos_response: DatedVideosResponse = serde_json::from_value(resources.clone())
+            .map_err(json_error)?;
+
+        Ok(videos_response)
+    }
+
+    /// Searches titles, videos and channels matching `query`
+    ///
+    /// # Arguments
+    /// * `query` - Free-text search terms
+    /// * `page` - Page number (1-based), applied to each result type
+    /// * `per_page` - Results per page for each result type
+    pub async fn search(
+        &self,
+        query: &str,
+        page: u32,
+        per_page: u32,
+    ) -> Result<SearchResults> {
+        let variables = serde_json::json!({
+            "query": query,
+            "page": page,
+            "perPage": per_page
+        });
+
+        let data = self.graphql_request(
+            constants::SEARCH_OPERATION,
+            GraphQlQuery::Document(constants::SEARCH_QUERY),
+            variables,
+        )
+        .await?;
+
+        let search = data
+            .get("search")
+            .ok_or_else(|| Error::Api("Missing search in GraphQL response".to_string()))?;
+        serde_json::from_value(search.clone()).map_err(json_error)
+    }
+
+    /// Fetches a title's program metadata by title ID or slug
+    ///
+    /// Values containing a dash (e.g. "jornal-nacional") are treated as slugs, everything else as title IDs.
+    pub async fn fetch_title(&self, id_or_slug: &str) -> Result<TitleDetails> {
+        let variables = if id_or_slug.contains('-') {
+            serde_json::json!({ "slug": id_or_slug })
+        } else {
+            serde_json::json!({ "titleId": id_or_slug })
+        };
+
+        let data = self.graphql_request(
+            constants::TITLE_OPERATION,
+            GraphQlQuery::Document(constants::TITLE_QUERY),
+            variables,
+        )
+        .await?;
+
+        let title = data
+            .get("title")
+            .filter(|title| !title.is_null())
+            .ok_or_else(|| Error::NotFound(format!("Title {} not found", id_or_slug)))?;
+        serde_json::from_value(title.clone()).map_err(json_error)
+    }
+
+    /// Fetches one page of a title's episodes, optionally restricted to a season
+    ///
+    /// # Arguments
+    /// * `title_id` - The ID of the title/program
+    /// * `season_id` - Season ID from the title structure; `None` for the default season or flat lists
+    /// * `page` - Page number (1-based)
+    /// * `per_page` - Episodes per page
+    pub async fn fetch_episodes(
+        &self,
+        title_id: &TitleId,
+        season_id: Option<&str>,
+        page: u32,
+        per_page: u32,
+    ) -> Result<ResultPage<Episode>> {
+        let variables = serde_json::json!({
+            "titleId": title_id,
+            "seasonId": season_id,
+            "page": page,
+            "perPage": per_page
+        });
+
+        let data = self.graphql_request(
+            constants::EPISODES_OPERATION,
+            GraphQlQuery::Document(constants::EPISODES_QUERY),
+            variables,
+        )
+        .await?;
+
+        let episodes = data
+            .get("title")
+            .and_then(|title| title.get("structure"))
+            .and_then(|structure| structure.get("episodes"))
+            .ok_or_else(|| Error::Api(format!("Title {} has no episode listing", title_id)))?;
+        serde_json::from_value(episodes.clone()).map_err(json_error)
+    }
+
+    /// Fetches the live broadcasts available to the account, with their current programming
+    pub async fn fetch_broadcasts(&self) -> Result<Vec<Broadcast>> {
+        let data = self.graphql_request(
+            constants::BROADCASTS_OPERATION,
+            GraphQlQuery::Document(constants::BROADCASTS_QUERY),
+            serde_json::json!({ "affiliateCode": self.settings.region }),
+        )
+        .await?;
+
+        let broadcasts = data
+            .get("broadcasts")
+            .ok_or_else(|| Error::Api("Missing broadcasts in GraphQL response".to_string()))?;
+        serde_json::from_value(broadcasts.clone()).map_err(json_error)
+    }
+
+    /// Fetches the electronic program guide of a live broadcast for one day
+    ///
+    /// # Arguments
+    /// * `media_id` - The broadcast's live media ID
+    /// * `date` - Day to list (YYYY-MM-DD)
+    pub async fn fetch_epg(&self, media_id: &VideoId, date: &str) -> Result<Vec<EpgSlot>> {
+        let variables = serde_json::json!({
+            "mediaId": media_id,
+            "date": date
+        });
+
+        let data = self.graphql_request(
+            constants::EPG_OPERATION,
+            GraphQlQuery::Document(constants::EPG_QUERY),
+            variables,
+        )
+        .await?;
+
+        let entries = data
+            .get("broadcast")
+            .and_then(|broadcast| broadcast.get("epgByDate"))
+            .and_then(|epg| epg.get("entries"))
+            .ok_or_else(|| Error::Api(format!("No program guide for broadcast {}", media_id)))?;
+        serde_json::from_value(entries.clone()).map_err(json_error)
+    }
+
+    /// Fetches the catalog categories
+    pub async fn fetch_categories(&self) -> Result<Vec<Category>> {
+        let data = self.graphql_request(
+            constants::CATEGORIES_OPERATION,
+            GraphQlQuery::Document(constants::CATEGORIES_QUERY),
+            serde_json::json!({}),
+        )
+        .await?;
+
+        let categories = data
+            .get("categories")
+            .ok_or_else(|| Error::Api("Missing categories in GraphQL response".to_string()))?;
+        serde_json::from_value(categories.clone()).map_err(json_error)
+    }
+
+    /// Fetches one page of the titles in a catalog category
+    ///
+    /// # Arguments
+    /// * `slug` - The category slug (e.g. "novelas")
+    /// * `page` - Page number (1-based)
+    /// * `per_page` - Titles per page
+    pub async fn fetch_category_titles(
+        &self,
+        slug: &str,
+        page: u32,
+        per_page: u32,
+    ) -> Result<ResultPage<TitleSummary>> {
+        let variables = serde_json::json!({
+            "slug": slug,
+            "page": page,
+            "perPage": per_page
+        });
+
+        let data = self.graphql_request(
+            constants::CATEGORY_TITLES_OPERATION,
+            GraphQlQuery::Document(constants::CATEGORY_TITLES_QUERY),
+            variables,
+        )
+        .await?;
+
+        let titles = data
+            .get("category")
+            .filter(|category| !category.is_null())
+            .and_then(|category| category.get("titles"))
+            .ok_or_else(|| Error::NotFound(format!("Category {} not found", slug)))?;
+        serde_json::from_value(titles.clone()).map_err(json_error)
+    }
+
+    /// Fetches one page of the authenticated account's continue-watching list
+    ///
+    /// # Arguments
+    /// * `page` - Page number (1-based)
+    /// * `per_page` - Items per page
+    pub async fn fetch_continue_watching(
+        &self,
+        page: u32,
+        per_page: u32,
+    ) -> Result<ResultPage<ContinueWatchingItem>> {
+        let variables = serde_json::json!({
+            "page": page,
+            "perPage": per_page
+        });
+
+        let data = self.graphql_request(
+            constants::CONTINUE_WATCHING_OPERATION,
+            GraphQlQuery::Document(constants::CONTINUE_WATCHING_QUERY),
+            variables,
+        )
+        .await?;
+
+        let list = data
+            .get("user")
+            .filter(|user| !user.is_null())
+            .and_then(|user| user.get("continueWatching"))
+            .ok_or_else(|| Error::Auth("Not logged in: no user in GraphQL response (check --cookie)".to_string()))?;
+        serde_json::from_value(list.clone()).map_err(json_error)
+    }
+
+    /// Fetches one page of the titles and videos saved to the authenticated account's "My List"
+    ///
+    /// # Arguments
+    /// * `page` - Page number (1-based), applied to both titles and videos
+    /// * `per_page` - Items per page
+    pub async fn fetch_my_list(&self, page: u32, per_page: u32) -> Result<MyList> {
+        let variables = serde_json::json!({
+            "page": page,
+            "perPage": per_page
+        });
+
+        let data = self.graphql_request(
+            constants::MY_LIST_OPERATION,
+            GraphQlQuery::Document(constants::MY_LIST_QUERY),
+            variables,
+        )
+        .await?;
+
+        let list = data
+            .get("user")
+            .filter(|user| !user.is_null())
+            .and_then(|user| user.get("myList"))
+            .ok_or_else(|| Error::Auth("Not logged in: no user in GraphQL response (check --cookie)".to_string()))?;
+        serde_json::from_value(list.clone()).map_err(json_error)
+    }
+
+    /// Fetches the home-page highlight rails (most watched, editor highlights, ...)
+    pub async fn fetch_highlights(&self) -> Result<Vec<HighlightRail>> {
+        let data = self.graphql_request(
+            constants::HIGHLIGHTS_OPERATION,
+            GraphQlQuery::Document(constants::HIGHLIGHTS_QUERY),
+            serde_json::json!({}),
+        )
+        .await?;
+
+        let rails = data
+            .get("home")
+            .and_then(|home| home.get("rails"))
+            .ok_or_else(|| Error::Api("Missing home rails in GraphQL response".to_string()))?;
+        serde_json::from_value(rails.clone()).map_err(json_error)
+    }
+
+    /// Fetches a video's metadata (title, program, duration, availability) without creating a playback session
+    ///
+    /// # Arguments
+    /// * `video_id` - The ID of the video
+    pub async fn fetch_video_details(&self, video_id: &VideoId) -> Result<Video> {
+        let data = self.graphql_request(
+            constants::VIDEO_OPERATION,
+            GraphQlQuery::Document(constants::VIDEO_QUERY),
+            serde_json::json!({ "videoId": video_id }),
+        )
+        .await?;
+
+        let video = data
+            .get("video")
+            .filter(|video| !video.is_null())
+            .ok_or_else(|| Error::NotFound(format!("Video {} not found", video_id)))?;
+        serde_json::from_value(video.clone()).map_err(json_error)
+    }
+}
@@ -0,0 +1,84 @@
+// src/quality.rs
+//! The video quality to download, parsed once from `--quality` or `default_quality`
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Which stream or HLS variant to pick among the available ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Quality {
+    /// The highest resolution ("max", also "high" or "best")
+    #[default]
+    Max,
+    /// The lowest resolution ("min", also "low" or "worst")
+    Min,
+    /// A resolution by its height, e.g. "720p"; the closest one below it if it isn't offered
+    Height(u32),
+    /// A bitrate in kbit/s, e.g. "2500k"; the highest HLS variant not above it
+    Bitrate(u32),
+}
+
+impl Quality {
+    /// Height to look for, for `Height`
+    pub fn height(self) -> Option<u32> {
+        match self {
+            Quality::Height(height) => Some(height),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Quality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Quality::Max => f.write_str("max"),
+            Quality::Min => f.write_str("min"),
+            Quality::Height(height) => write!(f, "{}p", height),
+            Quality::Bitrate(kbps) => write!(f, "{}k", kbps),
+        }
+    }
+}
+
+impl FromStr for Quality {
+    type Err = Error;
+
+    /// Accepts max, min (and their aliases), a height such as "720p" or "720", or a bitrate
+    /// such as "2500k" or "2500kbps"
+    fn from_str(input: &str) -> Result<Self> {
+        let input = input.trim().to_ascii_lowercase();
+        let number = |digits: &str| digits.parse::<u32>().ok().filter(|n| *n > 0);
+        let quality = match input.as_str() {
+            "max" | "high" | "best" => Some(Quality::Max),
+            "min" | "low" | "worst" => Some(Quality::Min),
+            _ => {
+                if let Some(kbps) = input.strip_suffix("kbps").or_else(|| input.strip_suffix('k')) {
+                    number(kbps).map(Quality::Bitrate)
+                } else {
+                    number(input.strip_suffix('p').unwrap_or(&input)).map(Quality::Height)
+                }
+            }
+        };
+        quality.ok_or_else(|| {
+            Error::Invalid(format!(
+                "'{}' is not a quality (max, min, a height such as 720p or a bitrate such as 2500k)",
+                input
+            ))
+        })
+    }
+}
+
+impl TryFrom<String> for Quality {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<Quality> for String {
+    fn from(quality: Quality) -> String {
+        quality.to_string()
+    }
+}
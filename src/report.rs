@@ -0,0 +1,253 @@
+// src/report.rs
+
+use crate::error::{self, Error, Result};
+use crate::i18n::Msg;
+use crate::ids::VideoId;
+use crate::output::{self, Color};
+use crate::utils;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Phases of processing a single item, used for `--timings`
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// Creating the playback session
+    Session,
+    /// Fetching and parsing HLS manifests
+    Manifest,
+    /// Downloading (ffmpeg also remuxes in this phase)
+    Download,
+    /// Post-processing such as audio normalization
+    PostProcess,
+}
+
+impl Phase {
+    fn label(&self) -> &'static str {
+        match self {
+            Phase::Session => "session",
+            Phase::Manifest => "manifest",
+            Phase::Download => "download",
+            Phase::PostProcess => "post-process",
+        }
+    }
+}
+
+/// Time spent in one phase
+#[derive(Debug, Serialize, Clone)]
+pub struct PhaseTiming {
+    pub phase: Phase,
+    pub seconds: f64,
+}
+
+fn format_timings(timings: &[PhaseTiming]) -> String {
+    let total: f64 = timings.iter().map(|t| t.seconds).sum();
+    let mut parts: Vec<String> = timings
+        .iter()
+        .map(|t| format!("{} {:.2}s", t.phase.label(), t.seconds))
+        .collect();
+    parts.push(format!("total {:.2}s", total));
+    parts.join(", ")
+}
+
+/// Final state of a single item processed by a command
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case", tag = "state")]
+pub enum ItemStatus {
+    /// Metadata was fetched but nothing was downloaded
+    Fetched,
+    Downloaded,
+    /// Download was skipped (e.g. already in the library and unchanged)
+    Skipped { reason: String },
+    Failed { error: String },
+}
+
+/// Outcome of processing one video, including non-fatal warnings collected along the way
+#[derive(Debug, Serialize, Clone)]
+pub struct ItemReport {
+    pub video_id: String,
+    pub title: Option<String>,
+    pub status: ItemStatus,
+    pub path: Option<PathBuf>,
+    /// Extra files written next to the media (description, contact sheet, ...)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sidecars: Vec<PathBuf>,
+    pub warnings: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub timings: Vec<PhaseTiming>,
+    #[serde(skip)]
+    timings_enabled: bool,
+}
+
+impl ItemReport {
+    /// Creates a report for `video_id`; phase timings are only kept when `timings_enabled` is set
+    pub fn new(video_id: &VideoId, timings_enabled: bool) -> Self {
+        ItemReport {
+            video_id: video_id.to_string(),
+            title: None,
+            status: ItemStatus::Fetched,
+            path: None,
+            sidecars: Vec::new(),
+            warnings: Vec::new(),
+            timings: Vec::new(),
+            timings_enabled,
+        }
+    }
+
+    /// Records how long a phase took, accumulating repeated phases
+    pub fn record_phase(&mut self, phase: Phase, elapsed: Duration) {
+        if !self.timings_enabled {
+            return;
+        }
+        match self.timings.iter_mut().find(|t| t.phase == phase) {
+            Some(timing) => timing.seconds += elapsed.as_secs_f64(),
+            None => self.timings.push(PhaseTiming {
+                phase,
+                seconds: elapsed.as_secs_f64(),
+            }),
+        }
+    }
+
+    /// Records a non-fatal issue for this item
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.warnings.push(message.into());
+    }
+
+    /// Marks the item as failed with the given error
+    pub fn fail(&mut self, error: &Error) {
+        self.status = ItemStatus::Failed {
+            error: error::with_causes(error),
+        };
+    }
+
+    fn print_compact(&self) {
+        let state = match &self.status {
+            ItemStatus::Fetched => Msg::Fetched.text().to_string(),
+            ItemStatus::Downloaded => output::paint(Msg::Downloaded.text(), Color::Green),
+            ItemStatus::Skipped { reason } => output::paint(format!("{}: {}", Msg::Skipped.text(), reason), Color::Yellow),
+            ItemStatus::Failed { error } => output::paint(format!("{}: {}", Msg::Failed.text(), error), Color::Red),
+        };
+        println!(
+            "  [{}] {} {}",
+            state,
+            self.video_id,
+            self.title.as_deref().unwrap_or("")
+        );
+        if let Some(path) = &self.path {
+            println!("      -> {}", path.display());
+        }
+        for sidecar in &self.sidecars {
+            println!("      +  {}", sidecar.display());
+        }
+        for warning in &self.warnings {
+            println!("      {} {}", output::paint(Msg::Warning.text().to_lowercase(), Color::Yellow), warning);
+        }
+        if !self.timings.is_empty() {
+            println!("      {}: {}", Msg::Timings.text(), format_timings(&self.timings));
+        }
+    }
+
+    /// Prints the phase timings as a status message, for commands whose result is printed separately
+    pub fn print_timings(&self) {
+        if !self.timings.is_empty() {
+            output::info!("{}: {}", Msg::Timings.text(), format_timings(&self.timings));
+        }
+    }
+
+    /// Prints the report in the configured output format
+    pub fn print(&self, output_format: &str) -> Result<()> {
+        match utils::format_output(self, output_format)? {
+            Some(output) => println!("{}", output),
+            None => self.print_compact(),
+        }
+        Ok(())
+    }
+}
+
+/// Summary of all items processed by a batch command
+#[derive(Debug, Serialize, Default)]
+pub struct BatchReport {
+    pub downloaded: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub warnings: usize,
+    /// Time spent per phase summed over all items
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub timings: Vec<PhaseTiming>,
+    pub items: Vec<ItemReport>,
+}
+
+impl BatchReport {
+    pub fn push(&mut self, item: ItemReport) {
+        match item.status {
+            ItemStatus::Downloaded => self.downloaded += 1,
+            ItemStatus::Skipped { .. } => self.skipped += 1,
+            ItemStatus::Failed { .. } => self.failed += 1,
+            ItemStatus::Fetched => {}
+        }
+        self.warnings += item.warnings.len();
+        for timing in &item.timings {
+            match self.timings.iter_mut().find(|t| t.phase == timing.phase) {
+                Some(total) => total.seconds += timing.seconds,
+                None => self.timings.push(timing.clone()),
+            }
+        }
+        self.items.push(item);
+    }
+
+    /// Downloaded, skipped and failed counts as one line, e.g. "3 downloaded, 1 skipped, 0 failed"
+    pub fn counts(&self) -> String {
+        format!(
+            "{} {}, {} {}, {} {}",
+            self.downloaded,
+            Msg::CountDownloaded.text(),
+            self.skipped,
+            Msg::CountSkipped.text(),
+            self.failed,
+            Msg::CountFailed.text()
+        )
+    }
+
+    /// Fails with a partial-failure error when any item in the batch failed
+    pub fn ensure_complete(&self) -> Result<()> {
+        if self.failed > 0 {
+            return Err(Error::Partial { failed: self.failed, total: self.items.len(), what: "items" });
+        }
+        Ok(())
+    }
+
+    /// Prints the report in the configured output format
+    pub fn print(&self, output_format: &str) -> Result<()> {
+        // CSV and NDJSON have one row per item; the totals are derivable from the rows
+        let formatted = if matches!(output_format, "csv" | "ndjson") {
+            utils::format_output(&self.items, output_format)?
+        } else {
+            utils::format_output(self, output_format)?
+        };
+        match formatted {
+            Some(output) => println!("{}", output),
+            None => {
+                let failed = format!("{} {}", self.failed, Msg::CountFailed.text());
+                println!(
+                    "{}: {} {}, {} {}, {}, {} {}",
+                    Msg::BatchReport.text(),
+                    self.downloaded,
+                    Msg::CountDownloaded.text(),
+                    self.skipped,
+                    Msg::CountSkipped.text(),
+                    if self.failed > 0 { output::paint(failed, Color::Red) } else { failed },
+                    self.warnings,
+                    Msg::CountWarnings.text()
+                );
+                if !self.timings.is_empty() {
+                    println!("{}: {}", Msg::AggregatedTimings.text(), format_timings(&self.timings));
+                }
+                for item in &self.items {
+                    item.print_compact();
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+// src/secrets.rs
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Credentials stored by `login`, kept out of the config file so it can be shared or
+/// committed to a dotfile repository
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Secrets {
+    /// Session cookies in Netscape cookie-file format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cookies: Option<String>,
+}
+
+/// Reads the secrets file, returning `None` if it doesn't exist
+///
+/// Warns when the file can be read by other users.
+pub fn load(path: &Path) -> Result<Option<Secrets>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    warn_if_exposed(path);
+    let content = fs::read_to_string(path).map_err(|e| Error::io(format!("Failed to read secrets file: {}", path.display()), e))?;
+    let secrets = toml::from_str(&content).map_err(|e| Error::parse(format!("Failed to parse secrets file: {}", path.display()), e))?;
+    Ok(Some(secrets))
+}
+
+/// Writes the secrets file, readable and writable only by the current user
+pub fn save(path: &Path, secrets: &Secrets) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| Error::io(format!("Failed to create directory: {}", parent.display()), e))?;
+    }
+    let content = toml::to_string(secrets).map_err(|e| Error::parse("Failed to serialize secrets", e))?;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(path)
+        .map_err(|e| Error::io(format!("Failed to write secrets file: {}", path.display()), e))?;
+    // The mode only applies to new files, so tighten an existing one too
+    #[cfg(unix)]
+    fs::set_permissions(path, std::os::unix::fs::PermissionsExt::from_mode(0o600))
+        .map_err(|e| Error::io(format!("Failed to restrict permissions of {}", path.display()), e))?;
+    file.write_all(content.as_bytes())
+        .map_err(|e| Error::io(format!("Failed to write secrets file: {}", path.display()), e))?;
+    Ok(())
+}
+
+/// Deletes the secrets file, returning whether there was one
+pub fn remove(path: &Path) -> Result<bool> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(Error::io(format!("Failed to delete secrets file: {}", path.display()), e)),
+    }
+}
+
+#[cfg(unix)]
+fn warn_if_exposed(path: &Path) {
+    use crate::output;
+    use std::os::unix::fs::PermissionsExt;
+    if let Ok(metadata) = fs::metadata(path) {
+        let mode = metadata.permissions().mode();
+        if mode & 0o077 != 0 {
+            output::warning!(
+                "{} is accessible by other users (mode {:o}); restrict it with: chmod 600 {}",
+                path.display(),
+                mode & 0o777,
+                path.display()
+            );
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn warn_if_exposed(_path: &Path) {}
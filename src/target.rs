@@ -0,0 +1,108 @@
+// src/target.rs
+use crate::config::AppConfig;
+use crate::error::{Error, Result};
+use crate::ids::{TitleId, VideoId};
+use crate::output;
+use regex::Regex;
+
+/// What a user-supplied ID or Globo Play URL points at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    Video(VideoId),
+    /// A title ID, or a slug when that was the input
+    Title(String),
+}
+
+/// Returns true if `input` looks like a URL rather than a bare ID
+pub fn is_url(input: &str) -> bool {
+    input.starts_with("http://") || input.starts_with("https://") || input.starts_with("globoplay.globo.com/")
+}
+
+/// Recognizes a Globo Play video (`/v/1234567/`) or program (`/<slug>/t/<titleId>/`) link in `text`
+fn find_globoplay_link(text: &str) -> Option<Target> {
+    VideoId::find_in(text)
+        .map(Target::Video)
+        .or_else(|| TitleId::find_in(text).map(|title_id| Target::Title(title_id.into_string())))
+}
+
+/// Returns the page's canonical URL (`og:url` or `rel="canonical"`)
+fn find_canonical_url(page: &str) -> Option<String> {
+    let canonical = Regex::new(r#"(?:property="og:url"\s+content|rel="canonical"\s+href)="([^"]+)""#).ok()?;
+    canonical.captures(page).map(|caps| caps[1].to_string())
+}
+
+/// Looks for video or title IDs in page markup (player config, JSON-LD, data attributes)
+fn find_embedded_id(page: &str) -> Option<Target> {
+    let video = Regex::new(r#"(?:"videoId"|data-video-id|"mediaId")\s*[:=]\s*"?(\d{5,})"#).ok()?;
+    if let Some(caps) = video.captures(page) {
+        return caps[1].parse().ok().map(Target::Video);
+    }
+    let title = Regex::new(r#""titleId"\s*:\s*"([A-Za-z0-9]+)""#).ok()?;
+    title.captures(page).map(|caps| Target::Title(caps[1].to_string()))
+}
+
+/// Resolves an ID or URL to what it points at
+///
+/// Bare numeric input is a video ID and other bare input a title ID or slug. URLs are matched
+/// by path first; otherwise they are fetched (following redirects, e.g. short links or g1 pages)
+/// and the final URL and page markup are searched for a video or title ID.
+///
+/// # Arguments
+/// * `input` - An ID, slug or URL
+/// * `config` - The application configuration
+///
+/// # Returns
+/// The video or title the input refers to
+pub async fn resolve(input: &str, config: &AppConfig) -> Result<Target> {
+    let input = input.trim();
+    if !is_url(input) {
+        return Ok(match input.parse() {
+            Ok(video_id) => Target::Video(video_id),
+            Err(_) => Target::Title(input.to_string()),
+        });
+    }
+    if let Some(target) = find_globoplay_link(input) {
+        return Ok(target);
+    }
+
+    let url = if input.starts_with("http") { input.to_string() } else { format!("https://{}", input) };
+    output::info!("Resolving {}", url);
+    let response = config
+        .client
+        .http()
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| Error::from(e).context(format!("Failed to fetch {}", url)))?;
+    let final_url = response.url().to_string();
+    if let Some(target) = find_globoplay_link(&final_url) {
+        return Ok(target);
+    }
+    let page = response
+        .text()
+        .await
+        .map_err(|e| Error::from(e).context(format!("Failed to read {}", final_url)))?;
+    // Pages link to related videos too, so the canonical URL and player config come first
+    let target = find_canonical_url(&page)
+        .and_then(|canonical| find_globoplay_link(&canonical))
+        .or_else(|| find_embedded_id(&page))
+        .or_else(|| find_globoplay_link(&page));
+    if let Some(target) = target {
+        return Ok(target);
+    }
+    Err(Error::NotFound(format!("Could not find a video or title ID in {}", url)))
+}
+
+/// Resolves an ID or URL that must point at a video
+pub async fn resolve_video_id(input: &str, config: &AppConfig) -> Result<VideoId> {
+    if !is_url(input) {
+        return input.parse();
+    }
+    match resolve(input, config).await? {
+        Target::Video(video_id) => Ok(video_id),
+        Target::Title(title_id) => Err(Error::Invalid(format!(
+            "{} is a program page (title {}); use the get or title command instead",
+            input, title_id
+        ))),
+    }
+}
@@ -0,0 +1,470 @@
+// src/tui.rs
+use crate::error::Error;
+use crate::config::AppConfig;
+use crate::models::{Category, Episode, ResultPage, TitleSummary};
+use crate::output;
+use crate::download::BatchItem;
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+/// Items requested per page when filling a pane
+const PAGE_SIZE: u32 = 50;
+
+const KEY_HELP: &str =
+    "↑/↓ move  Enter open/queue  Esc back  / search  d queue  m more  q quit and download  Ctrl-C quit";
+
+/// Pane currently shown in the list area
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum View {
+    Categories,
+    Titles,
+    Episodes,
+}
+
+/// Where the titles pane was filled from, so further pages can be requested
+#[derive(Debug, Clone)]
+enum TitleSource {
+    Search(String),
+    Category(String),
+}
+
+struct App {
+    config: AppConfig,
+    view: View,
+    categories: Vec<Category>,
+    category_state: ListState,
+    titles: Vec<TitleSummary>,
+    title_state: ListState,
+    title_source: Option<TitleSource>,
+    titles_next_page: Option<u32>,
+    current_title: Option<TitleSummary>,
+    episodes: Vec<Episode>,
+    episode_state: ListState,
+    episodes_next_page: Option<u32>,
+    queue: Vec<BatchItem>,
+    search_input: Option<String>,
+    status: String,
+}
+
+/// Runs the interactive browser until the user quits
+///
+/// # Arguments
+/// * `config` - The application configuration
+///
+/// # Returns
+/// The episodes queued for download, in the order they were queued
+pub async fn run(config: &AppConfig) -> Result<Vec<BatchItem>> {
+    // Progress output would draw over the interface
+    let level = output::level();
+    output::set_level(output::QUIET);
+
+    let mut app = App::new(config.clone());
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal).await;
+    ratatui::restore();
+    output::set_level(level);
+
+    result?;
+    Ok(app.queue)
+}
+
+/// Page to request next, if the API reports one
+fn next_page_of<T>(page: &ResultPage<T>) -> Option<u32> {
+    page.next_page.filter(|_| page.has_next_page)
+}
+
+impl App {
+    fn new(config: AppConfig) -> Self {
+        App {
+            config,
+            view: View::Categories,
+            categories: Vec::new(),
+            category_state: ListState::default(),
+            titles: Vec::new(),
+            title_state: ListState::default(),
+            title_source: None,
+            titles_next_page: None,
+            current_title: None,
+            episodes: Vec::new(),
+            episode_state: ListState::default(),
+            episodes_next_page: None,
+            queue: Vec::new(),
+            search_input: None,
+            status: String::new(),
+        }
+    }
+
+    async fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.load_categories(terminal).await?;
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                self.queue.clear();
+                return Ok(());
+            }
+            if self.search_input.is_some() {
+                self.handle_search_key(key.code, terminal).await?;
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') => return Ok(()),
+                KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+                KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+                KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => self.open_selected(terminal).await?,
+                KeyCode::Esc | KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => self.go_back(),
+                KeyCode::Char('/') => self.search_input = Some(String::new()),
+                KeyCode::Char('d') | KeyCode::Char(' ') => self.toggle_queued(),
+                KeyCode::Char('m') => self.load_more(terminal).await?,
+                _ => {}
+            }
+        }
+    }
+
+    /// Shows a status message immediately, before a slow request starts
+    fn show_status(&mut self, terminal: &mut DefaultTerminal, message: String) -> Result<()> {
+        self.status = message;
+        terminal.draw(|frame| self.draw(frame))?;
+        Ok(())
+    }
+
+    async fn handle_search_key(&mut self, code: KeyCode, terminal: &mut DefaultTerminal) -> Result<()> {
+        let Some(input) = self.search_input.as_mut() else {
+            return Ok(());
+        };
+        match code {
+            KeyCode::Esc => self.search_input = None,
+            KeyCode::Enter => {
+                let query = input.trim().to_string();
+                self.search_input = None;
+                if !query.is_empty() {
+                    self.open_titles(TitleSource::Search(query), terminal).await?;
+                }
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn load_categories(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.show_status(terminal, "Loading categories...".to_string())?;
+        match self.config.client.fetch_categories().await {
+            Ok(categories) => {
+                self.status = format!("{} categories; press / to search", categories.len());
+                self.category_state.select((!categories.is_empty()).then_some(0));
+                self.categories = categories;
+            }
+            Err(e) => self.status = format!("Failed to load categories: {}", e),
+        }
+        Ok(())
+    }
+
+    async fn fetch_titles(&self, source: &TitleSource, page: u32) -> Result<ResultPage<TitleSummary>, Error> {
+        match source {
+            TitleSource::Search(query) => {
+                let results = self.config.client.search(query, page, PAGE_SIZE).await?;
+                Ok(results.title_result.unwrap_or(ResultPage {
+                    has_next_page: false,
+                    next_page: None,
+                    resources: Vec::new(),
+                }))
+            }
+            TitleSource::Category(slug) => self.config.client.fetch_category_titles(slug, page, PAGE_SIZE).await,
+        }
+    }
+
+    async fn open_titles(&mut self, source: TitleSource, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.show_status(terminal, "Loading titles...".to_string())?;
+        match self.fetch_titles(&source, 1).await {
+            Ok(page) => {
+                self.titles_next_page = next_page_of(&page);
+                self.titles = page.resources;
+                self.titles.retain(|t| self.config.passes_kids_filter(t.content_rating.as_deref()));
+                self.title_state.select((!self.titles.is_empty()).then_some(0));
+                self.title_source = Some(source);
+                self.view = View::Titles;
+                self.status = format!("{} titles", self.titles.len());
+            }
+            Err(e) => self.status = format!("Failed to load titles: {}", e),
+        }
+        Ok(())
+    }
+
+    async fn open_episodes(&mut self, title: TitleSummary, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.show_status(terminal, format!("Loading episodes of {}...", title.headline.as_deref().unwrap_or(&title.id)))?;
+        match self.config.client.fetch_episodes(&title.id, None, 1, PAGE_SIZE).await {
+            Ok(page) => {
+                self.episodes_next_page = next_page_of(&page);
+                self.episodes = page.resources;
+                self.episode_state.select((!self.episodes.is_empty()).then_some(0));
+                self.current_title = Some(title);
+                self.view = View::Episodes;
+                self.status = format!("{} episodes", self.episodes.len());
+            }
+            Err(e) => self.status = format!("Failed to load episodes: {}", e),
+        }
+        Ok(())
+    }
+
+    /// Appends the next page of the current titles or episodes listing
+    async fn load_more(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        match self.view {
+            View::Categories => {}
+            View::Titles => {
+                let (Some(page), Some(source)) = (self.titles_next_page, self.title_source.clone()) else {
+                    self.status = "No more titles".to_string();
+                    return Ok(());
+                };
+                self.show_status(terminal, format!("Loading titles page {}...", page))?;
+                match self.fetch_titles(&source, page).await {
+                    Ok(result) => {
+                        self.titles_next_page = next_page_of(&result);
+                        let config = &self.config;
+                        self.titles.extend(
+                            result
+                                .resources
+                                .into_iter()
+                                .filter(|t| config.passes_kids_filter(t.content_rating.as_deref())),
+                        );
+                        self.status = format!("{} titles", self.titles.len());
+                    }
+                    Err(e) => self.status = format!("Failed to load titles: {}", e),
+                }
+            }
+            View::Episodes => {
+                let (Some(page), Some(title_id)) = (self.episodes_next_page, self.current_title.as_ref().map(|t| t.id.clone())) else {
+                    self.status = "No more episodes".to_string();
+                    return Ok(());
+                };
+                self.show_status(terminal, format!("Loading episodes page {}...", page))?;
+                match self.config.client.fetch_episodes(&title_id, None, page, PAGE_SIZE).await {
+                    Ok(result) => {
+                        self.episodes_next_page = next_page_of(&result);
+                        self.episodes.extend(result.resources);
+                        self.status = format!("{} episodes", self.episodes.len());
+                    }
+                    Err(e) => self.status = format!("Failed to load episodes: {}", e),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn open_selected(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        match self.view {
+            View::Categories => {
+                let Some(category) = self.category_state.selected().and_then(|i| self.categories.get(i)) else {
+                    return Ok(());
+                };
+                let slug = category.slug.clone().unwrap_or_else(|| category.id.clone());
+                self.open_titles(TitleSource::Category(slug), terminal).await
+            }
+            View::Titles => {
+                let Some(title) = self.title_state.selected().and_then(|i| self.titles.get(i)).cloned() else {
+                    return Ok(());
+                };
+                self.open_episodes(title, terminal).await
+            }
+            View::Episodes => {
+                self.toggle_queued();
+                Ok(())
+            }
+        }
+    }
+
+    fn go_back(&mut self) {
+        self.view = match self.view {
+            View::Episodes => View::Titles,
+            View::Titles | View::Categories => View::Categories,
+        };
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let (len, state) = match self.view {
+            View::Categories => (self.categories.len(), &mut self.category_state),
+            View::Titles => (self.titles.len(), &mut self.title_state),
+            View::Episodes => (self.episodes.len(), &mut self.episode_state),
+        };
+        if len == 0 {
+            return;
+        }
+        let current = state.selected().unwrap_or(0);
+        state.select(Some(current.saturating_add_signed(delta).min(len - 1)));
+    }
+
+    fn is_queued(&self, episode: &Episode) -> bool {
+        self.queue.iter().any(|item| &item.video_id == episode.video_id())
+    }
+
+    /// Adds the selected episode to the download queue, or removes it if already queued
+    fn toggle_queued(&mut self) {
+        if self.view != View::Episodes {
+            self.status = "Open a title and select an episode to queue it".to_string();
+            return;
+        }
+        let Some(episode) = self.episode_state.selected().and_then(|i| self.episodes.get(i)) else {
+            return;
+        };
+        let video_id = episode.video_id().clone();
+        let name = episode.headline.clone().unwrap_or_else(|| video_id.to_string());
+        if self.is_queued(episode) {
+            self.queue.retain(|item| item.video_id != video_id);
+            self.status = format!("Removed {} from the queue", name);
+        } else {
+            self.queue.push(BatchItem {
+                video_id,
+                headline: episode.headline.clone(),
+                summary: episode.description.clone(),
+            });
+            self.status = format!("Queued {} ({} in queue)", name, self.queue.len());
+        }
+    }
+
+    fn breadcrumb(&self) -> String {
+        let source = match &self.title_source {
+            Some(TitleSource::Search(query)) => format!("Search \"{}\"", query),
+            Some(TitleSource::Category(slug)) => format!("Category {}", slug),
+            None => String::new(),
+        };
+        match self.view {
+            View::Categories => "Categories".to_string(),
+            View::Titles => source,
+            View::Episodes => format!(
+                "{} > {}",
+                source,
+                self.current_title.as_ref().and_then(|t| t.headline.as_deref()).unwrap_or("Episodes")
+            ),
+        }
+    }
+
+    /// Metadata of the selected item, shown next to the list
+    fn preview(&self) -> String {
+        match self.view {
+            View::Categories => self
+                .category_state
+                .selected()
+                .and_then(|i| self.categories.get(i))
+                .map(|c| {
+                    format!(
+                        "{}\n\nSlug: {}\n\nEnter lists the category's titles.",
+                        c.name.as_deref().unwrap_or("N/A"),
+                        c.slug.as_deref().unwrap_or(&c.id)
+                    )
+                })
+                .unwrap_or_default(),
+            View::Titles => self
+                .title_state
+                .selected()
+                .and_then(|i| self.titles.get(i))
+                .map(|t| {
+                    format!(
+                        "{}\n\nID: {}\nType: {}\nRating: {}\n\n{}",
+                        t.headline.as_deref().unwrap_or("N/A"),
+                        t.id,
+                        t.title_type.as_deref().unwrap_or("N/A"),
+                        t.content_rating.as_deref().unwrap_or("N/A"),
+                        t.description.as_deref().unwrap_or("")
+                    )
+                })
+                .unwrap_or_default(),
+            View::Episodes => self
+                .episode_state
+                .selected()
+                .and_then(|i| self.episodes.get(i))
+                .map(|e| {
+                    format!(
+                        "{}\n\nVideo ID: {}\nSeason: {}  Episode: {}\nDuration: {}\nAired: {}\n{}\n\n{}",
+                        e.headline.as_deref().unwrap_or("N/A"),
+                        e.video_id(),
+                        e.season_number.map_or_else(|| "-".to_string(), |n| n.to_string()),
+                        e.number.map_or_else(|| "-".to_string(), |n| n.to_string()),
+                        e.duration.map_or_else(|| "N/A".to_string(), crate::dates::format_clock),
+                        e.exhibited_at.map_or_else(|| "N/A".to_string(), |t| t.format("%Y-%m-%d").to_string()),
+                        if self.is_queued(e) { "Queued for download" } else { "" },
+                        e.description.as_deref().unwrap_or("")
+                    )
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header_area, body_area, footer_area] =
+            Layout::vertical([Constraint::Length(3), Constraint::Min(5), Constraint::Length(2)]).areas(frame.area());
+        let [list_area, preview_area] =
+            Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)]).areas(body_area);
+
+        let header = match &self.search_input {
+            Some(input) => format!("Search: {}_", input),
+            None => self.breadcrumb(),
+        };
+        frame.render_widget(
+            Paragraph::new(header).block(Block::default().borders(Borders::ALL).title(" Globo Play ")),
+            header_area,
+        );
+
+        let preview = self.preview();
+        frame.render_widget(
+            Paragraph::new(preview).wrap(Wrap { trim: false }).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Details ({} queued) ", self.queue.len())),
+            ),
+            preview_area,
+        );
+
+        let (title, labels, state) = match self.view {
+            View::Categories => (
+                " Categories ",
+                self.categories
+                    .iter()
+                    .map(|c| c.name.clone().unwrap_or_else(|| c.id.clone()))
+                    .collect::<Vec<_>>(),
+                &mut self.category_state,
+            ),
+            View::Titles => (
+                " Titles ",
+                self.titles
+                    .iter()
+                    .map(|t| t.headline.clone().unwrap_or_else(|| t.id.to_string()))
+                    .collect(),
+                &mut self.title_state,
+            ),
+            View::Episodes => (
+                " Episodes ",
+                self.episodes
+                    .iter()
+                    .map(|e| {
+                        let queued = self.queue.iter().any(|item| &item.video_id == e.video_id());
+                        format!(
+                            "[{}] {}",
+                            if queued { "x" } else { " " },
+                            e.headline.as_deref().unwrap_or(e.video_id())
+                        )
+                    })
+                    .collect(),
+                &mut self.episode_state,
+            ),
+        };
+        let list = List::new(labels.into_iter().map(ListItem::new))
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, list_area, state);
+
+        frame.render_widget(Paragraph::new(format!("{}\n{}", self.status, KEY_HELP)), footer_area);
+    }
+}
@@ -1,41 +1,41 @@
 // src/utils.rs
 
-use anyhow::{Context, Result};
-use reqwest::Client; // Still useful for pre-checks
-use std::path::Path;
+use crate::downloader::{DownloadJob, Downloader};
+use crate::error::{Error, Result};
+use crate::output;
+use crate::net::HttpClient; // Still useful for pre-checks
+use std::path::{Path, PathBuf};
 use tokio::process::Command; // Changed to tokio::process::Command
 use std::process::Stdio; // Added for piping ffmpeg output
 
-// Basic file download utility using ffmpeg
-// TODO: Add progress bar (ffmpeg output parsing can be complex).
-// TODO: Check if ffmpeg is installed and provide a helpful error if not.
-// TODO: Allow configuring ffmpeg path.
-pub async fn download_file(client: &Client, url: &str, path: &Path) -> Result<()> {
-    println!(
-        "Attempting to download using ffmpeg. Input URL: \"{}\", Output Path: \"{}\"",
+/// A separate audio rendition to mux into the downloaded file
+#[derive(Debug, Clone)]
+pub struct AudioTrackInput {
+    pub url: String,
+    pub language: Option<String>,
+}
+
+// Basic file download utility: checks that the URL is reachable (unless `job.precheck` is off) and
+// creates the output directory, then hands the stream to `downloader` and returns the file it wrote
+#[tracing::instrument(skip_all, fields(url = job.url, path = %job.path.display()), err(level = "info"))]
+pub async fn download_file(client: &HttpClient, downloader: &dyn Downloader, job: DownloadJob<'_>) -> Result<PathBuf> {
+    let DownloadJob { url, path, cancel, .. } = job;
+    if cancel.is_cancelled() {
+        return Err(Error::Cancelled);
+    }
+    tracing::info!(downloader = ?downloader, "download started");
+    output::info!(
+        "Attempting to download. Input URL: \"{}\", Output Path: \"{}\"",
         url,
         path.display()
     );
 
-    // 1. Preliminary HEAD request to check URL accessibility
-    match client.head(url).send().await {
-        Ok(resp) => {
-            if !resp.status().is_success() {
-                return Err(anyhow::anyhow!(
-                    "HEAD request to URL {} failed with status: {}. Aborting ffmpeg download.",
-                    url,
-                    resp.status()
-                ));
-            }
-            println!("URL {} is accessible (status: {}). Proceeding with ffmpeg.", url, resp.status());
-        }
-        Err(e) => {
-            return Err(anyhow::anyhow!(
-                "Failed to make HEAD request to URL {}: {}. Aborting ffmpeg download.",
-                url,
-                e
-            ));
-        }
+    // 1. Preliminary request to check URL accessibility
+    if job.precheck {
+        precheck(client, url).await?;
+        output::verbose!("URL {} is accessible. Proceeding with {:?} download.", url, downloader);
+    } else {
+        output::verbose!("Skipping the pre-check of URL {}. Proceeding with {:?} download.", url, downloader);
     }
 
     // 2. Ensure the output directory exists
@@ -43,90 +43,561 @@ pub async fn download_file(client: &Client, url: &str, path: &Path) -> Result<()
         if !parent_dir.exists() {
             tokio::fs::create_dir_all(parent_dir)
                 .await
-                .context(format!("Failed to create directory: {}", parent_dir.display()))?;
-            println!("Created output directory: {}", parent_dir.display());
+                .map_err(|e| Error::io(format!("Failed to create directory: {}", parent_dir.display()), e))?;
+            output::verbose!("Created output directory: {}", parent_dir.display());
         }
     }
 
-    let output_path_str = path.to_str().ok_or_else(|| {
-        anyhow::anyhow!("Invalid output path for ffmpeg: {}", path.display())
-    })?;
+    let Some(limit) = job.timeout else {
+        let written = downloader.download(client, job).await?;
+        tracing::info!("download finished");
+        return Ok(written);
+    };
+    // Past the limit the backend is stopped through a child token, so it still removes its partial output
+    let deadline = cancel.child_token();
+    let download = downloader.download(client, DownloadJob { cancel: &deadline, ..job });
+    tokio::pin!(download);
+    tokio::select! {
+        result = &mut download => {
+            let written = result?;
+            tracing::info!("download finished");
+            return Ok(written);
+        }
+        _ = tokio::time::sleep(limit) => {}
+    }
+    deadline.cancel();
+    let _ = download.await;
+    Err(Error::Stalled(format!("The download of {} took longer than {}s", url, limit.as_secs())))
+}
 
-    // 3. Construct and execute ffmpeg command
-    println!(
-        "Executing ffmpeg command: ffmpeg -y -protocol_whitelist file,http,https,tcp,tls,crypto -i \"{}\" -c copy -bsf:a aac_adtstoasc \"{}\"",
-        url, output_path_str
-    );
+/// Checks that `url` answers before a download starts: with a HEAD request, or, as some CDNs
+/// reject HEAD while serving GET, with a GET of the first byte when HEAD fails
+async fn precheck(client: &HttpClient, url: &str) -> Result<()> {
+    let head_failure = match client.head(url).send().await {
+        Ok(resp) if resp.status().is_success() => return Ok(()),
+        Ok(resp) => format!("status {}", resp.status()),
+        Err(e) => e.to_string(),
+    };
+    tracing::debug!(url, head_failure, "HEAD request failed, probing with a ranged GET");
+    // The response is dropped unread, so a server ignoring the range doesn't send the whole file
+    let resp = client
+        .get(url)
+        .header(reqwest::header::RANGE, "bytes=0-0")
+        .send()
+        .await
+        .map_err(|e| Error::from(e).context(format!("Failed to reach URL {} (HEAD request: {}). Aborting download.", url, head_failure)))?;
+    if !resp.status().is_success() {
+        return Err(Error::Http {
+            status: resp.status(),
+            body: format!(
+                "URL {} failed the pre-check (HEAD request: {}). Aborting download; --no-precheck skips the check.",
+                url, head_failure
+            ),
+        });
+    }
+    Ok(())
+}
 
-    let mut cmd = Command::new("ffmpeg");
-    cmd.arg("-y") // Overwrite output files without asking
-        .arg("-protocol_whitelist")
-        .arg("file,http,https,tcp,tls,crypto")
-        .arg("-i")
-        .arg(url)
-        .arg("-c")
-        .arg("copy")
-        .arg("-bsf:a")
-        .arg("aac_adtstoasc")
-        .arg(output_path_str)
+/// Loudness measurements printed by ffmpeg's loudnorm filter in its first pass
+#[derive(Debug, serde::Deserialize)]
+struct LoudnormMeasurement {
+    input_i: String,
+    input_tp: String,
+    input_lra: String,
+    input_thresh: String,
+    target_offset: String,
+}
+
+/// Runs ffmpeg with the given arguments and returns its stderr, failing on a non-zero exit
+pub(crate) async fn run_ffmpeg(args: &[String]) -> Result<String> {
+    tracing::debug!(args = ?args, "running ffmpeg");
+    let output = Command::new("ffmpeg")
+        .args(args)
         .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| Error::Ffmpeg(format!("Failed to spawn ffmpeg command. Is ffmpeg installed and in your PATH? ({})", e)))?;
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    if !output.status.success() {
+        return Err(Error::Ffmpeg(format!(
+            "ffmpeg command failed with status: {}\n\nffmpeg stderr:\n{}",
+            output.status,
+            stderr
+        )));
+    }
+    Ok(stderr)
+}
+
+/// Normalizes the audio loudness of a downloaded file in place using ffmpeg's loudnorm filter
+///
+/// The two-pass mode measures the file first and feeds the measurements into the second pass,
+/// which gives accurate, linear normalization; single-pass is faster but applies dynamic processing.
+/// Every audio track is kept with its language tag and normalized on its own, since a dub and the
+/// original mix rarely have the same loudness.
+///
+/// # Arguments
+/// * `path` - The media file to normalize
+/// * `target_lufs` - Integrated loudness target (e.g. -16.0 for streaming, -23.0 for EBU R128)
+/// * `two_pass` - Whether to run a measurement pass first
+pub async fn normalize_audio(path: &Path, target_lufs: f64, two_pass: bool) -> Result<()> {
+    let input = path
+        .to_str()
+        .ok_or_else(|| Error::Invalid(format!("Invalid path for ffmpeg: {}", path.display())))?
+        .to_string();
+    let base_filter = format!("loudnorm=I={}:TP=-1.5:LRA=11", target_lufs);
+
+    let tracks = probe_audio_tracks(path).await?;
+    if tracks == 0 {
+        return Err(Error::Invalid(format!("{} has no audio to normalize", path.display())));
+    }
+
+    let mut filters = Vec::with_capacity(tracks);
+    for track in 0..tracks {
+        if !two_pass {
+            filters.push(base_filter.clone());
+            continue;
+        }
+        output::info!("Measuring loudness of {} (audio track {} of {})", path.display(), track + 1, tracks);
+        let stderr = run_ffmpeg(&[
+            "-hide_banner".to_string(),
+            "-i".to_string(),
+            input.clone(),
+            "-map".to_string(),
+            format!("0:a:{}", track),
+            "-af".to_string(),
+            format!("{}:print_format=json", base_filter),
+            "-f".to_string(),
+            "null".to_string(),
+            "-".to_string(),
+        ])
+        .await?;
+
+        // The measurement JSON is the last {...} block ffmpeg prints to stderr
+        let json_start = stderr
+            .rfind('{')
+            .ok_or_else(|| Error::Ffmpeg("loudnorm did not report measurements".to_string()))?;
+        let json_end = stderr[json_start..]
+            .find('}')
+            .map(|end| json_start + end + 1)
+            .ok_or_else(|| Error::Ffmpeg("loudnorm reported truncated measurements".to_string()))?;
+        let measured: LoudnormMeasurement = serde_json::from_str(&stderr[json_start..json_end])
+            .map_err(|e| Error::parse("Failed to parse loudnorm measurements", e))?;
+
+        filters.push(format!(
+            "{}:measured_I={}:measured_TP={}:measured_LRA={}:measured_thresh={}:offset={}:linear=true",
+            base_filter,
+            measured.input_i,
+            measured.input_tp,
+            measured.input_lra,
+            measured.input_thresh,
+            measured.target_offset
+        ));
+    }
+
+    // Write to a temporary file next to the original and swap it in on success
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("mp4");
+    let temp_path = path.with_extension(format!("loudnorm.{}", extension));
+    let temp_str = temp_path
+        .to_str()
+        .ok_or_else(|| Error::Invalid(format!("Invalid path for ffmpeg: {}", temp_path.display())))?
+        .to_string();
 
-    let child = cmd.spawn().context(
-        "Failed to spawn ffmpeg command. Is ffmpeg installed and in your PATH?",
-    )?;
+    output::info!("Normalizing audio of {} to {} LUFS", path.display(), target_lufs);
+    let mut args = vec![
+        "-hide_banner".to_string(),
+        "-y".to_string(),
+        "-i".to_string(),
+        input,
+        // Keep every stream and the file's tags, not just ffmpeg's pick of one video and one audio
+        "-map".to_string(),
+        "0".to_string(),
+        "-map_metadata".to_string(),
+        "0".to_string(),
+    ];
+    for (track, filter) in filters.into_iter().enumerate() {
+        args.push(format!("-filter:a:{}", track));
+        args.push(filter);
+    }
+    args.extend(
+        ["-c", "copy", "-c:a", "aac", "-ar", "48000"]
+            .into_iter()
+            .map(String::from)
+            .chain([temp_str]),
+    );
+    let result = run_ffmpeg(&args).await;
 
-    // 4. Wait for the command to complete and capture output
-    let output = child
-        .wait_with_output()
+    if let Err(e) = result {
+        let _ = tokio::fs::remove_file(&temp_path).await;
+        return Err(e.context(format!("Audio normalization failed for {}", path.display())));
+    }
+
+    tokio::fs::rename(&temp_path, path)
         .await
-        .context("Failed to wait for ffmpeg command execution")?;
-
-    // 5. Check ffmpeg's exit status
-    if output.status.success() {
-        println!(
-            "ffmpeg successfully downloaded {} to {}",
-            url,
-            path.display()
-        );
-        // Optionally print ffmpeg's stderr if it contains useful info (ffmpeg often uses stderr for progress/info)
-        let stderr_output = String::from_utf8_lossy(&output.stderr);
-        if !stderr_output.is_empty() {
-            println!("ffmpeg stderr:\n{}", stderr_output);
+        .map_err(|e| Error::io(format!("Failed to replace {} with normalized audio", path.display()), e))?;
+    Ok(())
+}
+
+/// Parses a human duration such as "1h30m", "90m", "45s", "1h" or plain seconds ("3600")
+///
+/// ```
+/// use globo_play::utils::parse_duration;
+/// assert_eq!(parse_duration("1h30m").unwrap().as_secs(), 5400);
+/// assert!(parse_duration("99999999999999999h").is_err());
+/// assert!(parse_duration("0").is_err());
+/// ```
+pub fn parse_duration(value: &str) -> Result<std::time::Duration> {
+    let value = value.trim();
+    let invalid = || Error::Invalid(format!("Invalid duration '{}': expected e.g. 1h30m", value));
+    if let Ok(seconds) = value.parse::<u64>() {
+        return match seconds {
+            0 => Err(invalid()),
+            seconds => Ok(std::time::Duration::from_secs(seconds)),
+        };
+    }
+
+    let mut total: u64 = 0;
+    let mut number = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let amount: u64 = number.parse().map_err(|_| invalid())?;
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(Error::Invalid(format!("Invalid duration unit '{}' in '{}'", c, value))),
+        };
+        // Durations too long to count in seconds are as invalid as malformed ones
+        total = amount.checked_mul(unit).and_then(|seconds| total.checked_add(seconds)).ok_or_else(invalid)?;
+        number.clear();
+    }
+    if !number.is_empty() || total == 0 {
+        return Err(invalid());
+    }
+    Ok(std::time::Duration::from_secs(total))
+}
+
+/// Writes a plain-text `.description` sidecar next to a downloaded media file
+///
+/// # Returns
+/// The path of the sidecar file
+pub fn write_description(media_path: &Path, description: &str) -> Result<PathBuf> {
+    let sidecar_path = media_path.with_extension("description");
+    std::fs::write(&sidecar_path, format!("{}\n", description.trim()))
+        .map_err(|e| Error::io(format!("Failed to write description: {}", sidecar_path.display()), e))?;
+    Ok(sidecar_path)
+}
+
+/// Returns the number of audio tracks in a media file using ffprobe
+async fn probe_audio_tracks(path: &Path) -> Result<usize> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-select_streams", "a", "-show_entries", "stream=index", "-of", "csv=p=0"])
+        .arg(path)
+        .output()
+        .await
+        .map_err(|e| Error::Ffmpeg(format!("Failed to spawn ffprobe. Is ffmpeg installed and in your PATH? ({})", e)))?;
+    if !output.status.success() {
+        return Err(Error::Ffmpeg(format!("ffprobe could not read the audio tracks of {}", path.display())));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).lines().filter(|line| !line.trim().is_empty()).count())
+}
+
+/// Returns the duration of a media file in seconds using ffprobe
+async fn probe_duration(path: &Path) -> Result<f64> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-show_entries", "format=duration", "-of", "csv=p=0"])
+        .arg(path)
+        .output()
+        .await
+        .map_err(|e| Error::Ffmpeg(format!("Failed to spawn ffprobe. Is ffmpeg installed and in your PATH? ({})", e)))?;
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<f64>()
+        .map_err(|e| Error::Ffmpeg(format!("ffprobe could not determine the duration of {}: {}", path.display(), e)))
+}
+
+/// Builds a contact sheet: a single image grid of frames sampled evenly across the video,
+/// each labeled with its timestamp
+///
+/// # Arguments
+/// * `path` - The downloaded media file
+/// * `columns` - Number of tiles per row
+/// * `rows` - Number of rows
+/// * `format` - Image format/extension ("jpg" or "png")
+///
+/// # Returns
+/// The path of the generated image, next to the media file
+pub async fn generate_contact_sheet(path: &Path, columns: u32, rows: u32, format: &str) -> Result<PathBuf> {
+    let duration = probe_duration(path).await?;
+    let tiles = (columns * rows).max(1);
+    let interval = (duration / tiles as f64).max(1.0);
+    let sheet_path = path.with_extension(format!("contact.{}", format));
+
+    let input = path
+        .to_str()
+        .ok_or_else(|| Error::Invalid(format!("Invalid path for ffmpeg: {}", path.display())))?
+        .to_string();
+    let output = sheet_path
+        .to_str()
+        .ok_or_else(|| Error::Invalid(format!("Invalid path for ffmpeg: {}", sheet_path.display())))?
+        .to_string();
+
+    let sample = format!("fps=1/{:.3},scale=320:-2", interval);
+    let timestamp = "drawtext=text='%{pts\\:hms}':x=8:y=h-th-8:fontsize=18:fontcolor=white:box=1:boxcolor=black@0.6";
+    let tile = format!("tile={}x{}", columns, rows);
+
+    let run = |filter: String| {
+        let args = vec![
+            "-hide_banner".to_string(),
+            "-y".to_string(),
+            "-i".to_string(),
+            input.clone(),
+            "-vf".to_string(),
+            filter,
+            "-frames:v".to_string(),
+            "1".to_string(),
+            output.clone(),
+        ];
+        async move { run_ffmpeg(&args).await }
+    };
+
+    // drawtext needs an ffmpeg built with freetype; fall back to a sheet without timestamps
+    if let Err(e) = run(format!("{},{},{}", sample, timestamp, tile)).await {
+        output::warning!("Timestamp overlay failed ({}), generating contact sheet without timestamps", e.without_context());
+        run(format!("{},{}", sample, tile)).await?;
+    }
+    Ok(sheet_path)
+}
+
+/// Formats data for the `--output` format: JSON, pretty JSON, NDJSON or CSV
+///
+/// Returns `None` for the compact format, which each command prints in its own layout.
+/// For NDJSON and CSV, a list becomes one row per element and an object a single row, except
+/// paginated results, whose `resources` become the rows. NDJSON writes each row as one line of JSON. Nested objects are flattened into dotted columns
+/// (`title.headline`), lists of plain values are joined with `;`, and the header is the union of
+/// all keys in first-seen order, so columns stay stable from run to run.
+pub fn format_output<T: serde::Serialize>(data: &T, format_type: &str) -> Result<Option<String>> {
+    match format_type {
+        "json" => serde_json::to_string(data).map_err(|e| Error::parse("Failed to serialize to JSON", e)).map(Some),
+        "pretty" => serde_json::to_string_pretty(data)
+            .map_err(|e| Error::parse("Failed to serialize to pretty JSON", e))
+            .map(Some),
+        "ndjson" => {
+            let value = serde_json::to_value(data).map_err(|e| Error::parse("Failed to serialize to NDJSON", e))?;
+            let lines = output_rows(value)
+                .iter()
+                .map(serde_json::to_string)
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|e| Error::parse("Failed to serialize to NDJSON", e))?;
+            Ok(Some(lines.join("\n")))
+        }
+        "csv" => {
+            let value = serde_json::to_value(data).map_err(|e| Error::parse("Failed to serialize to CSV", e))?;
+            Ok(Some(to_csv(value)))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Merges several lists into one, adding a `column` with each list's name to its items
+///
+/// Used to emit results that come in groups (e.g. search titles and videos) as a single CSV table.
+pub fn tagged_rows(column: &str, groups: Vec<(String, serde_json::Value)>) -> Vec<serde_json::Value> {
+    let mut rows = Vec::new();
+    for (name, list) in groups {
+        let items = match list {
+            serde_json::Value::Array(items) => items,
+            other => vec![other],
+        };
+        for item in items {
+            let mut row = serde_json::Map::new();
+            row.insert(column.to_string(), serde_json::Value::String(name.clone()));
+            match item {
+                serde_json::Value::Object(fields) => row.extend(fields),
+                other => {
+                    row.insert("value".to_string(), other);
+                }
+            }
+            rows.push(serde_json::Value::Object(row));
+        }
+    }
+    rows
+}
+
+/// Renders `template` once per output row (see `format_output`), one line each
+///
+/// Placeholders name fields of an item, with dotted paths for nested fields, e.g.
+/// `{id}\t{headline}\t{title.headline}`. Unknown fields render as empty strings, and the
+/// escapes `\t`, `\n` and `\\` are expanded so templates can be typed in a shell.
+pub fn render_print_template<T: serde::Serialize>(template: &str, data: &T) -> Result<String> {
+    let template = expand_escapes(template);
+    let placeholder = regex::Regex::new(r"\{([A-Za-z0-9_.]+)\}").expect("placeholder pattern is valid");
+    let value = serde_json::to_value(data).map_err(|e| Error::parse("Failed to serialize output", e))?;
+
+    let lines: Vec<String> = output_rows(value)
+        .iter()
+        .map(|row| {
+            let mut cells = Vec::new();
+            flatten_csv_value("", row, &mut cells);
+            placeholder
+                .replace_all(&template, |caps: &regex::Captures| {
+                    cells
+                        .iter()
+                        .find(|(key, _)| key == &caps[1])
+                        .map_or(String::new(), |(_, value)| value.clone())
+                })
+                .into_owned()
+        })
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+/// Lays out rows as a plain-text table with left-aligned, space-separated columns
+pub fn format_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let render = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = *width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    let mut lines = vec![output::paint(render(headers.to_vec()), output::Color::Bold)];
+    lines.extend(rows.iter().map(|row| render(row.iter().map(String::as_str).collect())));
+    lines.join("\n")
+}
+
+/// Reads an argument value, or the contents of a file when the value is `@path`
+pub fn read_inline_or_file(value: &str) -> Result<String> {
+    match value.strip_prefix('@') {
+        Some(path) => {
+            let path = shellexpand::tilde(path).into_owned();
+            std::fs::read_to_string(&path).map_err(|e| Error::io(format!("Failed to read {}", path), e))
+        }
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Expands `\t`, `\n` and `\\` in text typed on the command line
+fn expand_escapes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('\\') => out.push('\\'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
         }
-        Ok(())
+    }
+    out
+}
+
+/// Splits serialized data into rows: list elements, the `resources` of a page, or the value itself
+fn output_rows(value: serde_json::Value) -> Vec<serde_json::Value> {
+    match value {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Object(mut fields) => match fields.remove("resources") {
+            Some(serde_json::Value::Array(items)) => items,
+            Some(resources) => {
+                fields.insert("resources".to_string(), resources);
+                vec![serde_json::Value::Object(fields)]
+            }
+            None => vec![serde_json::Value::Object(fields)],
+        },
+        other => vec![other],
+    }
+}
+
+fn to_csv(value: serde_json::Value) -> String {
+    let rows = output_rows(value);
+
+    let mut headers: Vec<String> = Vec::new();
+    let mut flat_rows = Vec::new();
+    for row in &rows {
+        let mut cells = Vec::new();
+        flatten_csv_value("", row, &mut cells);
+        for (key, _) in &cells {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+        flat_rows.push(cells);
+    }
+
+    let mut out = headers.iter().map(|h| csv_field(h)).collect::<Vec<_>>().join(",");
+    out.push('\n');
+    for cells in flat_rows {
+        let line = headers
+            .iter()
+            .map(|h| {
+                cells
+                    .iter()
+                    .find(|(key, _)| key == h)
+                    .map_or(String::new(), |(_, value)| csv_field(value))
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+fn flatten_csv_value(prefix: &str, value: &serde_json::Value, cells: &mut Vec<(String, String)>) {
+    let key = if prefix.is_empty() { "value".to_string() } else { prefix.to_string() };
+    match value {
+        serde_json::Value::Object(fields) => {
+            for (name, field) in fields {
+                let column = if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) };
+                flatten_csv_value(&column, field, cells);
+            }
+        }
+        serde_json::Value::Array(items) if items.iter().all(|i| !i.is_object() && !i.is_array()) => {
+            let joined = items.iter().map(csv_scalar).collect::<Vec<_>>().join(";");
+            cells.push((key, joined));
+        }
+        serde_json::Value::Array(_) => cells.push((key, value.to_string())),
+        scalar => cells.push((key, csv_scalar(scalar))),
+    }
+}
+
+fn csv_scalar(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Quotes a CSV field when it contains a separator, quote or line break
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
     } else {
-        let stdout_str = String::from_utf8_lossy(&output.stdout);
-        let stderr_str = String::from_utf8_lossy(&output.stderr);
-        Err(anyhow::anyhow!(
-            "ffmpeg command failed with status: {}.\\nInput URL: {}\\nOutput Path: {}\\n\\nffmpeg stdout:\\n{}\\n\\nffmpeg stderr:\\n{}",
-            output.status,
-            url,
-            path.display(),
-            stdout_str,
-            stderr_str
-        ))
-    }
-}
-
-// Helper for formatting output (JSON, pretty JSON, compact text)
-// pub fn format_output<T: serde::Serialize>(
-//     data: &T,
-//     format_type: &str,
-//     debug_mode: bool,
-// ) -> Result<String> {
-//     match format_type {
-//         "json" => serde_json::to_string(data).context("Failed to serialize to JSON"),
-//         "pretty" => serde_json::to_string_pretty(data).context("Failed to serialize to pretty JSON"),
-//         "compact" => {
-//             // Implement a compact text representation
-//             // This will depend on the type T
-//             // For now, just use debug print as a placeholder
-//             Ok(format!("{:#?}", data))
-//         }
-//         _ => Ok(format!("{:#?}", data)), // Default to debug print
-//     }
-// }
+        text.to_string()
+    }
+}
 
 // You might add other utilities here, like:
 // - Date parsing and formatting
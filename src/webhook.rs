@@ -0,0 +1,130 @@
+// src/webhook.rs
+use crate::config::AppConfig;
+use crate::ids::TitleId;
+use crate::output;
+use crate::report::{ItemReport, ItemStatus};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Time allowed for a webhook endpoint to answer before the event is dropped
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Kinds of events posted to `--webhook` URLs
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum EventKind {
+    DownloadStarted,
+    DownloadComplete,
+    DownloadFailed,
+    /// A watched title (watch command or daemon sync job) published a new episode
+    NewEpisodeFound,
+}
+
+/// JSON payload of a webhook event
+#[derive(Debug, Serialize, Clone)]
+pub struct WebhookEvent {
+    pub event: EventKind,
+    pub video_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub timestamp: String,
+}
+
+impl WebhookEvent {
+    pub fn new(event: EventKind, video_id: &str, title: Option<&str>) -> Self {
+        WebhookEvent {
+            event,
+            video_id: video_id.to_string(),
+            title: title.map(str::to_string),
+            title_id: None,
+            path: None,
+            error: None,
+            timestamp: chrono::Local::now().to_rfc3339(),
+        }
+    }
+
+    pub fn with_path(mut self, path: &Path) -> Self {
+        self.path = Some(path.to_path_buf());
+        self
+    }
+
+    pub fn with_title_id(mut self, title_id: &TitleId) -> Self {
+        self.title_id = Some(title_id.to_string());
+        self
+    }
+
+    /// The download-complete or download-failed event for a processed item, if it was downloaded or failed
+    pub fn from_report(report: &ItemReport) -> Option<Self> {
+        let (kind, error) = match &report.status {
+            ItemStatus::Downloaded => (EventKind::DownloadComplete, None),
+            ItemStatus::Failed { error } => (EventKind::DownloadFailed, Some(error.clone())),
+            ItemStatus::Fetched | ItemStatus::Skipped { .. } => return None,
+        };
+        let mut event = WebhookEvent::new(kind, &report.video_id, report.title.as_deref());
+        event.path = report.path.clone();
+        event.error = error;
+        Some(event)
+    }
+}
+
+/// Renders `--webhook-template`, replacing `{field}` placeholders with the event's fields
+///
+/// Values are JSON-escaped (without quotes) so they can sit inside JSON strings, e.g.
+/// `{"content": "{event}: {title}"}` for Discord.
+fn render_template(template: &str, event: &WebhookEvent) -> String {
+    let Ok(serde_json::Value::Object(fields)) = serde_json::to_value(event) else {
+        return template.to_string();
+    };
+    let Ok(placeholder) = regex::Regex::new(r"\{([a-z_]+)\}") else {
+        return template.to_string();
+    };
+    placeholder
+        .replace_all(template, |caps: &regex::Captures| match fields.get(&caps[1]) {
+            Some(serde_json::Value::String(text)) => {
+                let quoted = serde_json::to_string(text).unwrap_or_default();
+                quoted[1..quoted.len() - 1].to_string()
+            }
+            Some(value) => value.to_string(),
+            None => String::new(),
+        })
+        .into_owned()
+}
+
+/// Posts an event to every configured webhook; failures only produce warnings
+pub async fn send(event: &WebhookEvent, config: &AppConfig) {
+    if config.webhooks.is_empty() {
+        return;
+    }
+    let body = match &config.webhook_template {
+        Some(template) => render_template(template, event),
+        None => match serde_json::to_string(event) {
+            Ok(body) => body,
+            Err(e) => {
+                output::warning!("could not serialize webhook event: {}", e);
+                return;
+            }
+        },
+    };
+    for url in &config.webhooks {
+        let result = config
+            .client
+            .http()
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .timeout(WEBHOOK_TIMEOUT)
+            .body(body.clone())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = result {
+            output::warning!("webhook {} failed: {}", url, e);
+        }
+    }
+}
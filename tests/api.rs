@@ -0,0 +1,279 @@
+// tests/api.rs
+//! API calls against canned responses, through a `net::Transport` that never reaches Globo's servers
+use futures_util::future::BoxFuture;
+use futures_util::TryStreamExt;
+use globo_play::client::GloboClient;
+use globo_play::constants;
+use globo_play::ids::TitleId;
+use globo_play::net::Transport;
+use globo_play::protocol::GraphQlQuery;
+use globo_play::Error;
+use std::sync::{Arc, Mutex};
+
+/// A request as seen by the transport
+#[derive(Debug, Clone)]
+struct Sent {
+    method: reqwest::Method,
+    url: String,
+    body: Option<serde_json::Value>,
+}
+
+type Responder = dyn Fn(&Sent) -> (u16, String) + Send + Sync;
+
+/// Answers each request with the status and body `respond` returns for it, recording the requests
+#[derive(Clone)]
+struct Canned {
+    respond: Arc<Responder>,
+    sent: Arc<Mutex<Vec<Sent>>>,
+}
+
+impl Canned {
+    fn new(respond: impl Fn(&Sent) -> (u16, String) + Send + Sync + 'static) -> Self {
+        Canned { respond: Arc::new(respond), sent: Arc::default() }
+    }
+
+    fn sent(&self) -> Vec<Sent> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+impl std::fmt::Debug for Canned {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Canned").finish_non_exhaustive()
+    }
+}
+
+impl Transport for Canned {
+    fn execute(&self, request: reqwest::Request) -> BoxFuture<'_, reqwest::Result<reqwest::Response>> {
+        let sent = Sent {
+            method: request.method().clone(),
+            url: request.url().to_string(),
+            body: request.body().and_then(|body| body.as_bytes()).and_then(|bytes| serde_json::from_slice(bytes).ok()),
+        };
+        let (status, body) = (self.respond)(&sent);
+        self.sent.lock().unwrap().push(sent);
+        let response = http::Response::builder().status(status).body(body).unwrap();
+        Box::pin(async move { Ok(response.into()) })
+    }
+}
+
+fn client(transport: &Canned) -> GloboClient {
+    GloboClient::builder()
+        .transport(transport.clone())
+        .api_rate_limit(None)
+        .build()
+        .unwrap()
+}
+
+/// A videos-by-date GraphQL response holding one item per page and a `next` link if `more`
+fn dated_page(page: u64, more: bool) -> String {
+    serde_json::json!({
+        "data": {"title": {"structure": {"excerpts": {"resources": {
+            "items": [{"id": format!("{}", 1000 + page), "title": format!("Video {}", page)}],
+            "count": 1,
+            "next": more.then(|| format!("page-{}", page + 1)),
+        }}}}}
+    })
+    .to_string()
+}
+
+/// The `page` variable of a videos-by-date request, sent in the URL of persisted queries
+fn requested_page(sent: &Sent) -> u64 {
+    let url = reqwest::Url::parse(&sent.url).unwrap();
+    let variables = url.query_pairs().find(|(name, _)| name == "variables").unwrap().1;
+    serde_json::from_str::<serde_json::Value>(&variables).unwrap()["page"].as_u64().unwrap()
+}
+
+#[tokio::test]
+async fn parses_a_video_session() {
+    let transport = Canned::new(|_| {
+        let body = serde_json::json!({
+            "session": "abc",
+            "sources": [
+                {"type": "primary", "url": "https://cdn.example/primary.m3u8", "cdn": "c1", "expiration_time": 1_700_000_000},
+                {"sourceType": "backup", "url": "https://cdn.example/backup.m3u8"}
+            ],
+            "resource": {"id": "12345678"},
+            "unmodeled": true
+        });
+        (200, body.to_string())
+    });
+    let session = client(&transport).fetch_video_session(&"12345678".parse().unwrap()).await.unwrap();
+
+    assert_eq!(session.session, "abc");
+    assert_eq!(session.sources.len(), 2);
+    assert!(session.sources[0].is_primary());
+    assert!(!session.sources[1].is_primary());
+    assert_eq!(session.sources[0].cdn.as_deref(), Some("c1"));
+    assert!(session.sources[0].expiration_time.is_some());
+    assert_eq!(session.extra.get("unmodeled"), Some(&serde_json::Value::Bool(true)));
+
+    let sent = transport.sent();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].method, reqwest::Method::POST);
+    assert!(sent[0].url.starts_with(constants::PLAYBACK_API_BASE_URL));
+}
+
+#[tokio::test]
+async fn maps_graphql_errors_to_error_kinds() {
+    let cases = [
+        ("SUBSCRIPTION_REQUIRED", "Conteúdo exclusivo"),
+        ("GEO_BLOCKED", "Not here"),
+        ("CONTENT_EXPIRED", "Gone"),
+        ("SOMETHING_ELSE", "Internal failure"),
+    ];
+    for (code, message) in cases {
+        let transport = Canned::new(move |_| {
+            let body = serde_json::json!({"data": null, "errors": [{"message": message, "extensions": {"code": code}}]});
+            (200, body.to_string())
+        });
+        let result = client(&transport).fetch_title("jornal-nacional").await;
+        match (code, result) {
+            ("SUBSCRIPTION_REQUIRED", Err(Error::SubscriptionRequired(text)))
+            | ("GEO_BLOCKED", Err(Error::GeoBlocked(text)))
+            | ("CONTENT_EXPIRED", Err(Error::Unavailable(text)))
+            | ("SOMETHING_ELSE", Err(Error::Api(text))) => assert_eq!(text, message),
+            (code, result) => panic!("unexpected result for {}: {:?}", code, result),
+        }
+    }
+}
+
+#[tokio::test]
+async fn leaves_graphql_errors_without_a_code_unsorted() {
+    let messages = [
+        r#"Variable "$region" of required type "String!" was not provided."#,
+        "Cannot query field \"location\" on type \"Title\". The field was removed.",
+    ];
+    for message in messages {
+        let transport = Canned::new(move |_| {
+            let body = serde_json::json!({"data": null, "errors": [{"message": message}]});
+            (200, body.to_string())
+        });
+        match client(&transport).fetch_title("jornal-nacional").await {
+            Err(Error::Api(text)) => assert_eq!(text, message),
+            result => panic!("unexpected result for {}: {:?}", message, result),
+        }
+    }
+}
+
+#[tokio::test]
+async fn keeps_graphql_errors_in_raw_responses() {
+    let transport = Canned::new(|_| (200, r#"{"data": null, "errors": [{"message": "boom"}]}"#.to_string()));
+    let body = client(&transport)
+        .graphql_raw_request("anything", GraphQlQuery::Document("query anything { x }"), serde_json::json!({}))
+        .await
+        .unwrap();
+    assert_eq!(body["errors"][0]["message"], "boom");
+}
+
+#[tokio::test]
+async fn falls_back_to_the_query_document_when_the_hash_is_unknown() {
+    let transport = Canned::new(|sent| match sent.method {
+        reqwest::Method::GET => (200, r#"{"errors": [{"message": "PersistedQueryNotFound"}]}"#.to_string()),
+        _ => (200, dated_page(1, false)),
+    });
+    let title_id: TitleId = "12345".parse().unwrap();
+    let response = client(&transport)
+        .fetch_videos_by_date(&title_id, "2024-01-01", "2024-01-10", 1, 10)
+        .await
+        .unwrap();
+    assert_eq!(response.items.len(), 1);
+
+    let sent = transport.sent();
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[0].method, reqwest::Method::GET);
+    assert!(sent[0].url.contains(constants::VIDEOS_BY_DATE_OPERATION));
+    assert_eq!(sent[1].method, reqwest::Method::POST);
+    let body = sent[1].body.as_ref().unwrap();
+    assert_eq!(body["operationName"], constants::VIDEOS_BY_DATE_OPERATION);
+    assert_eq!(body["query"], constants::VIDEOS_BY_DATE_QUERY);
+}
+
+#[tokio::test(start_paused = true)]
+async fn follows_next_until_it_is_empty() {
+    let transport = Canned::new(|sent| {
+        let page = requested_page(sent);
+        (200, dated_page(page, page < 3))
+    });
+    let client = client(&transport);
+    let title_id: TitleId = "12345".parse().unwrap();
+    let pages: Vec<_> = client
+        .videos_by_date_pages(&title_id, "2024-01-01", "2024-01-10", 1, 1)
+        .try_collect()
+        .await
+        .unwrap();
+
+    assert_eq!(pages.len(), 3);
+    assert!(pages[2].next.is_none());
+    let requested: Vec<u64> = transport.sent().iter().map(requested_page).collect();
+    assert_eq!(requested, [1, 2, 3]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn stops_following_next_at_the_page_cap() {
+    let transport = Canned::new(|sent| (200, dated_page(requested_page(sent), true)));
+    let client = client(&transport);
+    let title_id: TitleId = "12345".parse().unwrap();
+    let response = client
+        .fetch_all_videos_by_date(&title_id, "2024-01-01", "2024-01-10", 1, 1)
+        .await
+        .unwrap();
+
+    let max_pages = constants::PAGE_FOLLOW_MAX_PAGES as usize;
+    assert_eq!(transport.sent().len(), max_pages);
+    assert_eq!(response.items.len(), max_pages);
+    // A capped result keeps `next` so callers can tell it is incomplete
+    assert!(response.next.is_some());
+}
+
+#[tokio::test(start_paused = true)]
+async fn sorts_failed_responses_only_by_their_error_object() {
+    let cases = [
+        (500, "<html>Geometry service in region sa-east-1 failed: license server unavailable</html>".to_string()),
+        (502, r#"{"message": "Upstream failed", "code": "BAD_GATEWAY"}"#.to_string()),
+        (500, r#"{"message": "Not available here", "code": "GEO_BLOCKED"}"#.to_string()),
+    ];
+    for (status, body) in cases {
+        let response = body.clone();
+        let transport = Canned::new(move |_| (status, response.clone()));
+        let result = client(&transport).fetch_title("jornal-nacional").await;
+        match (body.contains("GEO_BLOCKED"), result) {
+            (false, Err(Error::Http { status: got, .. })) => assert_eq!(got.as_u16(), status),
+            (true, Err(Error::GeoBlocked(text))) => assert_eq!(text, "Not available here"),
+            (_, result) => panic!("unexpected result for {}: {:?}", body, result),
+        }
+    }
+}
+
+#[cfg(feature = "native-downloader")]
+#[tokio::test]
+async fn downloads_the_chosen_variant_in_order_as_ts() {
+    let transport = Canned::new(|sent| {
+        // Segments answer with their own name, so the file shows the order they were written in
+        let body = match sent.url.rsplit('/').next().unwrap() {
+            "master.m3u8" => "#EXTM3U\n#EXT-X-STREAM-INF:BANDWIDTH=800000,RESOLUTION=640x360\nlow.m3u8\n#EXT-X-STREAM-INF:BANDWIDTH=4000000,RESOLUTION=1920x1080\nhigh.m3u8\n".to_string(),
+            "low.m3u8" => format!("#EXTM3U\n{}", (1..=6).map(|n| format!("#EXTINF:6,\nlow{}.ts\n", n)).collect::<String>()),
+            segment => segment.trim_end_matches(".ts").to_string(),
+        };
+        (200, body)
+    });
+    let client = client(&transport);
+    let dir = std::env::temp_dir().join(format!("globo-play-native-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let written = globo_play::hls::download_stream(
+        client.http(),
+        "https://cdn.example/master.m3u8",
+        &dir.join("video.mp4"),
+        "360p".parse().unwrap(),
+        &Default::default(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(written, dir.join("video.ts"));
+    assert_eq!(std::fs::read_to_string(&written).unwrap(), "low1low2low3low4low5low6");
+    assert!(!transport.sent().iter().any(|sent| sent.url.contains("high")));
+    std::fs::remove_dir_all(&dir).unwrap();
+}